@@ -2,15 +2,18 @@ use crate::memory_page_manager::MemoryPageManager;
 use crate::receipt::TransactionReceipt;
 use storage::Storage;
 use vm::vm::VM;
+use vm::gas::GasMeter;
 use vm::registers::Register;
+use vm::trap::{AccessKind, VmTrap};
 use state::{State, Account};
 use crate::transaction::{TransactionType, Transaction};
+use crate::block::{self, Block, BlockHeader};
+use crate::signed_transaction::SignedTransaction;
 use crate::global::Config;
 use crate::execution_context::{ExecutionContext, ContextStack};
 use crate::host_interface::HostShim;
 use types::address::Address;
 use types::result::Result;
-use std::{panic::{catch_unwind, AssertUnwindSafe}, usize};
 use std::rc::Rc;
 use core::cell::RefCell;
 use core::fmt::Write;
@@ -78,6 +81,16 @@ pub struct AVM {
     
     /// Optional writer for verbose output. If None, outputs to console.
     pub verbose_writer: Option<Rc<RefCell<dyn Write>>>,
+
+    /// Gas budget given to each transaction.
+    pub gas_limit: u64,
+
+    /// Gas meter of the transaction currently executing.
+    /// 
+    /// EDUCATIONAL: Every VM spawned by `call_contract` (including nested
+    /// calls) charges this same meter, so a runaway contract anywhere in the
+    /// call tree exhausts the transaction's budget instead of hanging the node.
+    pub gas_meter: Rc<RefCell<GasMeter>>,
 }
 
 impl std::fmt::Debug for AVM {
@@ -88,6 +101,8 @@ impl std::fmt::Debug for AVM {
             .field("state", &self.state)
             .field("verbose", &self.verbose)
             .field("verbose_writer", &self.verbose_writer.as_ref().map(|_| "Some(<writer>)"))
+            .field("gas_limit", &self.gas_limit)
+            .field("gas_meter", &self.gas_meter)
             .finish()
     }
 }
@@ -102,6 +117,11 @@ impl AVM {
     pub fn set_verbose_writer(&mut self, writer: Rc<RefCell<dyn Write>>) {
         self.verbose_writer = Some(writer);
     }
+
+    /// Sets the gas budget given to each transaction.
+    pub fn set_gas_limit(&mut self, limit: u64) {
+        self.gas_limit = limit;
+    }
     
     /// Helper method to log output to either console or the configured writer
     /// Only logs if verbose is true and self.verbose is enabled
@@ -142,7 +162,26 @@ impl AVM {
             state,
             verbose: false, // Default to no verbose logging
             verbose_writer: None, // Default to console output
+            gas_limit: Config::DEFAULT_GAS_LIMIT,
+            gas_meter: Rc::new(RefCell::new(GasMeter::new(Config::DEFAULT_GAS_LIMIT))),
+        }
+    }
+
+    /// Authenticates and executes a signed transaction.
+    /// 
+    /// EDUCATIONAL PURPOSE: This is how transactions from the outside world
+    /// should enter the AVM. The sender is recovered from the signature and
+    /// must match `tx.from`; otherwise the transaction is rejected with
+    /// `Config::INVALID_SIGNATURE` before anything (including the nonce) changes.
+    /// 
+    /// `run_tx` skips this check and trusts `from`, which is only appropriate
+    /// for transactions the host already authenticated (tests, genesis setup).
+    pub fn run_signed_tx(&mut self, signed: SignedTransaction) -> TransactionReceipt {
+        if !signed.verify() {
+            self.log(&format!("Invalid signature for transaction from {}", signed.tx.from), true);
+            return TransactionReceipt::new(signed.tx, Result::new(false, Config::INVALID_SIGNATURE));
         }
+        self.run_tx(signed.tx)
     }
 
     /// Executes a transaction, which can be a transfer, account creation, or contract call.
@@ -166,72 +205,162 @@ impl AVM {
     /// all succeed or all fail. This ensures data consistency even if the
     /// system crashes during transaction processing.
     /// 
-    /// ERROR HANDLING: Uses catch_unwind to prevent panics from crashing the entire
-    /// system. This is crucial in blockchain systems where one bad transaction
-    /// shouldn't affect others.
+    /// ERROR HANDLING: Contract failures (illegal instructions, memory faults,
+    /// guest panics, out of gas) come back from the VM as a `VmTrap` and are
+    /// recorded in the receipt instead of crashing the entire system. This is
+    /// crucial in blockchain systems where one bad transaction shouldn't
+    /// affect others.
     /// 
-    /// GAS ACCOUNTING: In real blockchains, each operation costs gas, and
-    /// transactions have gas limits. This implementation is simplified and
-    /// doesn't include gas accounting.
+    /// GAS ACCOUNTING: Each transaction starts with a fresh meter holding
+    /// `gas_limit` units. Every instruction executed by the contract and its
+    /// nested calls is charged against it; the total is reported in the receipt.
+    /// Transfers and account creation run no code and pay the flat
+    /// `Config::NATIVE_TX_GAS` instead.
+    /// 
+    /// REPLAY PROTECTION: `tx.nonce` must equal the sender's current account
+    /// nonce, otherwise the transaction is rejected with `Config::INVALID_NONCE`
+    /// and nothing changes. An accepted transaction bumps the nonce even when
+    /// its execution fails, so the same transaction can never run twice.
+    /// 
+    /// TRUST: `tx.from` is taken at its word - there is no signature to
+    /// check. Anything submitted by users must enter through `run_signed_tx`
+    /// (or `run_block`); call this directly only for transactions the host
+    /// itself vouches for, such as tests and genesis setup.
     /// 
     /// RETURN VALUE: Returns a Result indicating success/failure and any error codes
     pub fn run_tx(&mut self, tx: Transaction) -> TransactionReceipt {
-        match tx.tx_type {
+        // EDUCATIONAL: Reject replays and out-of-order transactions up front
+        let expected_nonce = self.state.get_account(&tx.from).map(|a| a.nonce).unwrap_or(0);
+        if tx.nonce != expected_nonce {
+            self.log(&format!(
+                "Invalid nonce for {}: expected {}, got {}",
+                tx.from, expected_nonce, tx.nonce
+            ), true);
+            return TransactionReceipt::new(tx, Result::new(false, Config::INVALID_NONCE));
+        }
+        let sender = tx.from;
+
+        // EDUCATIONAL: Fresh gas budget for this transaction
+        self.gas_meter = Rc::new(RefCell::new(GasMeter::new(self.gas_limit)));
+
+        // EDUCATIONAL: Remember where this transaction's state changes start
+        let checkpoint = self.state.checkpoint();
+
+        let receipt = match tx.tx_type {
             TransactionType::Transfer => {
                 // EDUCATIONAL: Value transfer between accounts
-                let ok = self.apply_transfer(tx.from, tx.to, tx.value);
-                return TransactionReceipt::new(tx, Result::new(ok, if ok { 0 } else { 1 }));
+                self.run_native_tx(tx, |avm, tx| avm.apply_transfer(tx.from, tx.to, tx.value))
             }
 
             TransactionType::CreateAccount => {
-                let result = catch_unwind(AssertUnwindSafe(|| {
-                    self.create_account(tx.from, tx.to, tx.data.clone());
-                }));
-
                 // EDUCATIONAL: Handle deployment failures gracefully
-                        if let Err(_e) = result {
-            return TransactionReceipt::new(tx, Result::new(false, 1));
-        } else {
-            return TransactionReceipt::new(tx, Result::new(true, 0));
-        }
+                self.run_native_tx(tx, |avm, tx| avm.create_account(tx.from, tx.to, tx.data.clone()))
             }
 
             TransactionType::ProgramCall => {
                 // EDUCATIONAL: Execute an existing smart contract
-                // First verify the destination is actually a contract
-                assert!(self.state.is_contract(tx.to), "destination address is not a contract");
-
-                // EDUCATIONAL: Call the contract and extract the result
-                let (result_ptr, context_index) = self.call_contract(tx.from, tx.to, tx.data.clone());
-
-                // verify context stack is empty
-                if !self.context_stack.is_empty() {
+                // First verify the call can run at all (a contract, sane input)
+                if let Some(error_code) = self.reject_call(tx.to, tx.data.len()) {
+                    TransactionReceipt::new(tx, Result::new(false, error_code))
+                } else {
+                    // EDUCATIONAL: Call the contract and extract the result
+                    let (result_ptr, context_index) = self.call_contract(tx.from, tx.to, tx.data.clone(), tx.value);
+
+                    // EDUCATIONAL: Every call marks its context done before
+                    // returning; anything else is a host bug, and the
+                    // transaction fails rather than taking the node down
                     if self.context_stack.iter().any(|ctx| !ctx.exe_done) {
-                        panic!("context stack has unfinished contexts after tx execution");
+                        self.log("Context stack has unfinished contexts after tx execution", true);
+                        TransactionReceipt::new(tx, Result::new(false, 1))
+                    } else {
+                        // extract result
+                        let res = self.extract_result(result_ptr, context_index);
+                        let gas_used = self.gas_meter.borrow().used();
+                        let trap = self.context_stack.get(context_index).and_then(|ctx| ctx.trap.clone());
+                        TransactionReceipt::new(tx, res)
+                            // Include events from this context and any nested calls.
+                            .set_events(self.context_stack.collect_events_from(context_index))
+                            .set_gas_used(gas_used)
+                            .set_trap(trap)
                     }
                 }
+            }
+        };
+
+        // EDUCATIONAL: All-or-nothing - a failed transaction leaves no trace in state
+        if !receipt.result.success {
+            self.log("Transaction failed, reverting state changes", true);
+            self.state.revert_to(checkpoint);
+        }
 
-                // extract result 
-                let res = self.extract_result(result_ptr, context_index);
-                TransactionReceipt::new(tx, res)
-                    // Include events from this context and any nested calls.
-                    .set_events(self.context_stack.collect_events_from(context_index))
+        // EDUCATIONAL: The transaction was included, so its nonce is used up
+        // whatever the outcome (this happens after the revert on purpose)
+        self.state.set_nonce(&sender, expected_nonce + 1);
+        self.state.commit();
+
+        receipt
+    }
+
+    /// Runs a transaction that executes no contract code: it pays
+    /// `Config::NATIVE_TX_GAS` up front, then `apply` reports whether it
+    /// succeeded. Without enough gas nothing is applied and the receipt
+    /// carries the out-of-gas trap.
+    fn run_native_tx(&mut self, tx: Transaction, apply: impl FnOnce(&mut Self, &Transaction) -> bool) -> TransactionReceipt {
+        let charged = self.gas_meter.borrow_mut().charge(Config::NATIVE_TX_GAS);
+        let gas_used = self.gas_meter.borrow().used();
+        match charged {
+            Ok(()) => {
+                let ok = apply(self, &tx);
+                TransactionReceipt::new(tx, Result::new(ok, if ok { 0 } else { 1 })).set_gas_used(gas_used)
+            }
+            Err(out_of_gas) => {
+                let trap = VmTrap::OutOfGas(out_of_gas);
+                TransactionReceipt::new(tx, Result::new(false, trap.error_code()))
+                    .set_gas_used(gas_used)
+                    .set_trap(Some(trap))
             }
         }
     }
 
+    /// Executes signed transactions as the block following `parent`.
+    /// 
+    /// EDUCATIONAL PURPOSE: Blocks are how a chain moves forward. Transactions
+    /// run one after another through `run_signed_tx`, each seeing the state
+    /// left by the previous one. A transaction that fails (or is rejected,
+    /// e.g. with `INVALID_SIGNATURE`) still gets a receipt and is still part
+    /// of the block; only its state changes are discarded.
+    /// 
+    /// The returned header links to `parent` and commits to the signed
+    /// transactions, their receipts and the resulting state, so feeding it
+    /// back in as the next `parent` builds a chain.
+    pub fn run_block(&mut self, parent: &BlockHeader, timestamp: u64, transactions: Vec<SignedTransaction>) -> (Block, Vec<TransactionReceipt>) {
+        let receipts: Vec<TransactionReceipt> = transactions
+            .iter()
+            .map(|signed| self.run_signed_tx(signed.clone()))
+            .collect();
+
+        // EDUCATIONAL: The signature is part of what the block commits to
+        let tx_hashes: Vec<_> = transactions.iter().map(SignedTransaction::hash).collect();
+        let receipt_hashes: Vec<_> = receipts.iter().map(|r| r.hash()).collect();
+        let header = BlockHeader {
+            number: parent.number + 1,
+            parent_hash: parent.hash(),
+            timestamp,
+            state_root: self.state.state_root(),
+            transactions_root: block::merkle_root(&tx_hashes),
+            receipts_root: block::merkle_root(&receipt_hashes),
+        };
+        self.log(&format!("Block #{} executed {} transactions", header.number, receipts.len()), true);
+
+        (Block { header, transactions }, receipts)
+    }
+
     /// Moves native tokens between two accounts. Returns true on success.
+    /// 
+    /// JOURNALING: Balance changes go through `State::transfer`, so they are
+    /// undone if the surrounding transaction fails.
     pub fn apply_transfer(&mut self, from: Address, to: Address, amount: u64) -> bool {
-        let amount = amount as u128;
-        let from_account = self.state.get_account_mut(&from);
-        if from_account.balance < amount {
-            return false;
-        }
-        from_account.balance -= amount;
-
-        let to_account = self.state.get_account_mut(&to);
-        to_account.balance = to_account.balance.saturating_add(amount);
-        true
+        self.state.transfer(&from, &to, amount as u128)
     }
 
     /// Extracts the result of a contract execution from memory.
@@ -247,20 +376,20 @@ impl AVM {
     /// - 256 bytes: data array
     /// 
     /// MEMORY SAFETY: Validates that the result pointer is within bounds
-    /// to prevent reading invalid memory.
+    /// to prevent reading invalid memory. A result that does not fit in the
+    /// page reads as a failed call with the `OutOfBounds` trap's error code.
     fn extract_result(&self, _result_ptr: u32, context_index: usize) -> Result {
         // EDUCATIONAL: Get the memory page where the result was stored
         let ee = self.context_stack.get(context_index).expect("missing execution context");
         let vm = ee.vm.borrow();
         let page = vm.memory.borrow();
 
-        // EDUCATIONAL: Use the memory page's offset calculation to get the correct memory location
-        let start = page.offset(Config::RESULT_ADDR as usize); // Use memory page offset
-
-        // EDUCATIONAL: Validate memory bounds to prevent out-of-bounds access
-        if start + Config::MAX_RESULT_SIZE > page.size() {
-            panic!("Result struct out of bounds at 0x{:08x}", start);
-        }
+        // EDUCATIONAL: Translate the address and validate memory bounds to
+        // prevent out-of-bounds access
+        let start = match page.checked_offset(Config::RESULT_ADDR as usize, Config::MAX_RESULT_SIZE, AccessKind::Load) {
+            Ok(start) => start,
+            Err(trap) => return Result::new(false, trap.error_code()),
+        };
 
         // EDUCATIONAL: Extract the result fields from memory using the correct offset
         let mem = page.mem();
@@ -276,6 +405,25 @@ impl AVM {
         return Result { success, error_code, data_len, data };
     }
 
+    /// Writes a Result struct into a VM's memory at `RESULT_ADDR`, using the
+    /// same layout `extract_result` reads. Does nothing if the page is too
+    /// small to hold it (`extract_result` then reports the failure).
+    fn write_result(vm: &VM, res: &Result) {
+        let page = vm.memory.borrow();
+        let start = match page.checked_offset(Config::RESULT_ADDR as usize, Config::MAX_RESULT_SIZE, AccessKind::Store) {
+            Ok(start) => start,
+            Err(_) => return,
+        };
+        let (success, error_code, data_len, data) = (res.success, res.error_code, res.data_len, res.data);
+
+        let mut bytes = Vec::with_capacity(Config::MAX_RESULT_SIZE);
+        bytes.push(success as u8);
+        bytes.extend_from_slice(&error_code.to_le_bytes());
+        bytes.extend_from_slice(&data_len.to_le_bytes());
+        bytes.extend_from_slice(&data);
+        page.mem_mut()[start..start + bytes.len()].copy_from_slice(&bytes);
+    }
+
     /// Creates a new account (smart contract) with the provided code.
     /// 
     /// EDUCATIONAL PURPOSE: This demonstrates smart contract deployment.
@@ -292,7 +440,9 @@ impl AVM {
     /// - balance: 0 (no initial funds)
     /// - nonce: 0 (no transactions yet)
     /// - is_contract: true (marks this as a contract account)
-   pub fn create_account(&mut self, _from: Address, to: Address, data: Vec<u8>) {
+    /// 
+    /// Returns false (and changes nothing) if a check fails.
+   pub fn create_account(&mut self, _from: Address, to: Address, data: Vec<u8>) -> bool {
         // EDUCATIONAL: Deploy a new smart contract
         // This creates a new account with the provided code
         let is_contract = !data.is_empty();
@@ -307,19 +457,21 @@ impl AVM {
     
         // EDUCATIONAL: Check that the target address is not already in use
         // This prevents overwriting existing accounts
-        if self.state.accounts.contains_key(&to) {
-            panic!("account already exists");
+        if self.state.get_account(&to).is_some() {
+            self.log(&format!("Account {} already exists", to), true);
+            return false;
         }
 
         // EDUCATIONAL: Validate code size limits
         // This prevents resource exhaustion attacks
         let max = Config::CODE_SIZE_LIMIT + Config::RO_DATA_SIZE_LIMIT;
         if data.len() > max {
-            panic!(
+            self.log(&format!(
                 "❌ Code size ({}) exceeds CODE_SIZE_LIMIT ({} bytes)",
                 data.len(),
                 max
-            );
+            ), true);
+            return false;
         }
 
         // EDUCATIONAL: Create and insert new account with code
@@ -331,7 +483,30 @@ impl AVM {
             is_contract: true,             // Mark as contract account
         };
 
-        self.state.accounts.insert(to, account);
+        self.state.insert_account(to, account);
+        true
+    }
+
+    /// Checks that a call to `to` with `input_len` bytes of input can run.
+    /// Returns the error code to fail it with, or None if it can go ahead.
+    /// 
+    /// EDUCATIONAL: These are checks on the request itself, so they happen
+    /// before any memory is allocated or any value moves.
+    fn reject_call(&self, to: Address, input_len: usize) -> Option<u32> {
+        if !self.state.is_contract(to) {
+            self.log(&format!("Destination address {} is not a contract", to), true);
+            return Some(Config::NOT_A_CONTRACT);
+        }
+        // EDUCATIONAL: Validate input size to prevent resource exhaustion
+        if input_len > Config::MAX_INPUT_LEN {
+            self.log(&format!(
+                "Input length {} exceeds MAX_INPUT_LEN ({})",
+                input_len,
+                Config::MAX_INPUT_LEN
+            ), true);
+            return Some(Config::INPUT_TOO_LARGE);
+        }
+        None
     }
 
     /// Handles calling a new contract, spinning up a fresh VM with its own memory page.
@@ -356,7 +531,16 @@ impl AVM {
     /// - a2: Input data pointer
     /// - a3: Input data length
     /// - a4: Result pointer (where to write the result)
-    pub fn call_contract(&mut self, from: Address, to: Address, input_data: Vec<u8>) -> (u32, usize) {
+    /// 
+    /// VALUE: `value` native tokens move from `from` to `to` before the
+    /// contract starts, as part of the call. The contract can read the amount
+    /// through the call-value syscall. If the call fails (or `from` cannot pay)
+    /// the value goes back together with the rest of the call's effects.
+    /// 
+    /// A call that cannot run at all (the destination is not a contract, or
+    /// the input is longer than `MAX_INPUT_LEN`) returns a null result
+    /// pointer, which the calling contract sees as a failed call.
+    pub fn call_contract(&mut self, from: Address, to: Address, input_data: Vec<u8>, value: u64) -> (u32, usize) {
         self.log(&format!(
             "Tx calling program at address {} with data 0x{}",
             to,
@@ -366,6 +550,10 @@ impl AVM {
         // Save address for later use in termination log
         let to_addr_str = to.to_string();
 
+        if self.reject_call(to, input_data.len()).is_some() {
+            return (0, 0);
+        }
+
         // SAFETY NOTE:
         // This line creates a HostShim containing a raw pointer (*mut AVM) to self.
         // Even though raw pointers don't participate in Rust's borrow checker,
@@ -375,15 +563,17 @@ impl AVM {
         // To avoid this, ensure all other mutable uses of `self` happen *before* or *after* this line.
         let shim = HostShim::new(self);
 
-        // EDUCATIONAL: Get mutable reference to the contract account
-        let account = self.state.get_account_mut(&to);
-        if !account.is_contract {
-            panic!("destination address {} is not a contract", to);
-        }
+        // EDUCATIONAL: Look up the contract account. Code and storage are
+        // copied out so no borrow of the state is held while the contract
+        // runs (nested calls modify the state too).
+        let (code, account_storage) = match self.state.get_account(&to) {
+            Some(account) => (account.code.clone(), account.storage.clone()),
+            None => return (0, 0),
+        };
         
         // EDUCATIONAL: Allocate memory and clone storage for isolation
         let memory_page = self.memory_manager.new_page();
-        let storage = Rc::new(RefCell::new(Storage::with_map(account.storage.clone())));
+        let storage = Rc::new(RefCell::new(Storage::with_map(account_storage)));
 
         // EDUCATIONAL: Create and configure child VM
         // We use Box here to heap-allocate the HostShim and pass it as a trait object (Box<dyn HostInterface>).
@@ -393,7 +583,8 @@ impl AVM {
         // - Enables recursive call_contract logic, since the Box owns the host and doesn't borrow `self`
         // Without Box, we would need to track lifetimes manually and would hit borrow checker issues.
         let mut vm: VM = VM::new_with_writer(memory_page, storage.clone(), Box::new(shim), self.verbose_writer.clone());
-        vm.set_code(0, Config::PROGRAM_START_ADDR, &account.code);
+        let loaded = vm.set_code(0, Config::PROGRAM_START_ADDR, &code);
+        vm.set_gas_meter(self.gas_meter.clone());
         vm.cpu.verbose = self.verbose;
         
         // Set up logging writer for CPU to use AVM's logging mechanism
@@ -401,47 +592,78 @@ impl AVM {
             vm.cpu.set_verbose_writer(writer.clone());
         }
 
+        // EDUCATIONAL: Checkpoint the state so this call (and everything it
+        // calls) can be undone on its own if it fails
+        let checkpoint = self.state.checkpoint();
+
         // add new context execution
-        let context_index = self.context_stack.push(from, to, input_data, vm);
+        let context_index = self.context_stack.push(from, to, input_data, value, vm, checkpoint);
         let context = self.context_stack.current_mut().expect("missing execution context");
 
         // EDUCATIONAL: Set up function parameters in registers
-        // This follows the RISC-V calling convention
-        let _address_ptr = context.vm.borrow_mut().set_reg_to_data(Register::A0, to.0.as_ref());      // Contract address
-        let _pubkey_ptr = context.vm.borrow_mut().set_reg_to_data(Register::A1, from.0.as_ref());     // Caller address
-
-        // EDUCATIONAL: Validate input size to prevent resource exhaustion
-        let input_len = context.input_data.len();
-        if input_len > Config::MAX_INPUT_LEN {
-            panic!(
-                "Entrypoint: input length {} exceeds MAX_INPUT_LEN ({})",
-                input_len,
-                Config::MAX_INPUT_LEN
-            );
+        // This follows the RISC-V calling convention. Code or input that
+        // does not fit in the page traps like any other bad memory access.
+        let setup = loaded.and_then(|()| {
+            let mut vm = context.vm.borrow_mut();
+            vm.set_reg_to_data(Register::A0, to.0.as_ref())?;      // Contract address
+            vm.set_reg_to_data(Register::A1, from.0.as_ref())?;    // Caller address
+
+            // EDUCATIONAL: Set up input data (no result pointer needed)
+            let input_len = context.input_data.len();
+            vm.set_reg_to_data(Register::A2, &context.input_data)?;       // Input data
+            vm.set_reg_u32(Register::A3, input_len as u32);               // Input length
+            Ok(())
+        });
+
+        // EDUCATIONAL: Keep our own handle to the VM. Nested calls push new
+        // contexts while this one runs, so we must not hold a reference into
+        // the context stack across `run`.
+        let vm = Rc::clone(&context.vm);
+
+        // EDUCATIONAL: Move the attached value before the contract runs, so
+        // it can already use it. This happens after the checkpoint, so a
+        // failed call hands the value back to the caller.
+        let trap = if let Err(trap) = setup {
+            Some(trap)
+        } else if value != 0 && !self.apply_transfer(from, to, value) {
+            // EDUCATIONAL: The caller cannot pay; the contract never runs
+            self.log(&format!("Caller {} cannot attach {} to the call", from, value), true);
+            Self::write_result(&vm.borrow(), &Result::new(false, Config::INSUFFICIENT_FUNDS));
+            None
+        } else {
+            // EDUCATIONAL: Run the VM. Failures come back as a VmTrap value
+            // rather than a panic, so this works even with `panic = "abort"`.
+            let outcome = vm.borrow_mut().run();
+            outcome.err()
+        };
+        if let Some(trap) = &trap {
+            // EDUCATIONAL: The contract never finished, so a failed Result
+            // is written where the caller expects it.
+            self.log(&format!("VM trapped: {}", trap), true);
+            Self::write_result(&vm.borrow(), &Result::new(false, trap.error_code()));
         }
-
-        // EDUCATIONAL: Set up input data (no result pointer needed)
-        let _input_ptr = context.vm.borrow_mut().set_reg_to_data(Register::A2, &context.input_data);          // Input data
-        context.vm.borrow_mut().set_reg_u32(Register::A3, input_len as u32);                          // Input length
-
-        // EDUCATIONAL: Run the VM safely with panic handling
-        let result = catch_unwind(AssertUnwindSafe(|| {
-            context.vm.borrow_mut().raw_run();
-        }));
-
-        // EDUCATIONAL: Handle VM panics gracefully
-        if let Err(e) = result {
-            eprintln!("💥 VM panicked: {:?}", e);
-            panic!("VM panicked");
+        let succeeded = trap.is_none() && self.extract_result(Config::RESULT_ADDR, context_index).success;
+
+        if succeeded {
+            // EDUCATIONAL: Copy storage back into account
+            // This persists any changes the contract made to storage
+            // (journaled, so it is still undone if an outer call fails)
+            let updated_map = storage.borrow().map.borrow().clone();
+            self.state.set_storage(&to, updated_map);
+        } else {
+            // EDUCATIONAL: Only this call's effects are rolled back - balance
+            // changes and storage writes of nested calls included. The caller
+            // receives the failed Result and decides what to do with it.
+            self.log(&format!("Call to {} failed, reverting its state changes", to_addr_str), true);
+            self.state.revert_to(checkpoint);
+            self.context_stack.discard_events_from(context_index);
         }
-        
-        // EDUCATIONAL: Copy storage back into account
-        // This persists any changes the contract made to storage
-        let updated_map = storage.borrow().map.borrow().clone();
-        account.storage = updated_map;
 
-        // EDUCATIONAL: set context execution done
+        // EDUCATIONAL: set context execution done and return to the caller's context
+        let context = self.context_stack.get_mut(context_index).expect("missing execution context");
+        context.trap = trap;
         context.exe_done = true;
+        self.context_stack.pop();
         
         // Log execution termination for binary comparison tracking (after all borrows are done)
         self.log(&format!("Execution terminated for address {}", to_addr_str), false);
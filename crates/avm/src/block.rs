@@ -0,0 +1,111 @@
+use state::State;
+
+use crate::codec::{hash_bytes, DecodeError, Decoder, Encoder, Hash};
+use crate::signed_transaction::SignedTransaction;
+
+/// Summary of a block that later blocks link to.
+///
+/// EDUCATIONAL PURPOSE: A chain is a list of headers where each one commits
+/// to its parent (`parent_hash`), to what it contains (`transactions_root`),
+/// to what happened (`receipts_root`) and to where it left the world
+/// (`state_root`). Changing anything in an old block changes its hash and
+/// breaks every link after it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockHeader {
+    /// Height of the block; the genesis block is 0
+    pub number: u64,
+    /// Hash of the previous header (all zeros for genesis)
+    pub parent_hash: Hash,
+    /// Time the block was produced, as supplied by the producer
+    pub timestamp: u64,
+    /// Merkle root of the state after executing the block (see `State::state_root`)
+    pub state_root: Hash,
+    /// Merkle root over the signed transaction hashes (`SignedTransaction::hash`), in order
+    pub transactions_root: Hash,
+    /// Merkle root over the receipt hashes, in order
+    pub receipts_root: Hash,
+}
+
+impl BlockHeader {
+    /// Header of block 0, committing to the initial state.
+    pub fn genesis(state: &State) -> Self {
+        BlockHeader {
+            number: 0,
+            parent_hash: [0u8; 32],
+            timestamp: 0,
+            state_root: state.state_root(),
+            transactions_root: merkle_root(&[]),
+            receipts_root: merkle_root(&[]),
+        }
+    }
+
+    /// Canonical binary encoding (see `codec`), starting with the format version.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut enc = Encoder::versioned();
+        enc.u64(self.number);
+        enc.fixed(&self.parent_hash);
+        enc.u64(self.timestamp);
+        enc.fixed(&self.state_root);
+        enc.fixed(&self.transactions_root);
+        enc.fixed(&self.receipts_root);
+        enc.finish()
+    }
+
+    /// Decodes bytes produced by `encode`, rejecting anything else.
+    pub fn decode(bytes: &[u8]) -> Result<Self, DecodeError> {
+        let mut dec = Decoder::versioned(bytes)?;
+        let header = BlockHeader {
+            number: dec.u64()?,
+            parent_hash: dec.fixed(32)?.try_into().unwrap(),
+            timestamp: dec.u64()?,
+            state_root: dec.fixed(32)?.try_into().unwrap(),
+            transactions_root: dec.fixed(32)?.try_into().unwrap(),
+            receipts_root: dec.fixed(32)?.try_into().unwrap(),
+        };
+        dec.finish()?;
+        Ok(header)
+    }
+
+    /// Block hash: SHA-256 of the canonical header encoding.
+    pub fn hash(&self) -> Hash {
+        hash_bytes(&self.encode())
+    }
+}
+
+/// A header together with the signed transactions it commits to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Block {
+    pub header: BlockHeader,
+    pub transactions: Vec<SignedTransaction>,
+}
+
+impl Block {
+    pub fn hash(&self) -> Hash {
+        self.header.hash()
+    }
+}
+
+/// Binary Merkle root over an ordered list of hashes.
+///
+/// EDUCATIONAL: Leaves and inner nodes are hashed with different prefixes
+/// (0x00 / 0x01) so an inner node can never be passed off as a leaf. An odd
+/// node at the end of a level is carried up unchanged. The root of an empty
+/// list is the hash of nothing.
+pub fn merkle_root(leaves: &[Hash]) -> Hash {
+    if leaves.is_empty() {
+        return hash_bytes(&[]);
+    }
+
+    let mut level: Vec<Hash> = leaves.iter().map(|leaf| hash_bytes(&[&[0x00][..], leaf].concat())).collect();
+    while level.len() > 1 {
+        level = level
+            .chunks(2)
+            .map(|pair| match pair {
+                [left, right] => hash_bytes(&[&[0x01][..], left, right].concat()),
+                [single] => *single,
+                _ => unreachable!(),
+            })
+            .collect();
+    }
+    level[0]
+}
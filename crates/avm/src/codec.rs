@@ -0,0 +1,267 @@
+use core::fmt;
+use sha2::{Digest, Sha256};
+use types::address::Address;
+use types::result::{Result as ProgramResult, RESULT_DATA_SIZE};
+use vm::gas::OutOfGas;
+use vm::trap::{AccessKind, VmTrap};
+
+/// Version byte that starts every top-level encoding.
+///
+/// EDUCATIONAL PURPOSE: Once bytes are persisted or sent to another process,
+/// the layout can never silently change. Bumping this version is how a new
+/// layout is introduced; decoders reject versions they do not understand.
+pub const ENCODING_VERSION: u8 = 1;
+
+/// A 32-byte SHA-256 digest identifying a transaction, receipt or block.
+pub type Hash = [u8; 32];
+
+/// SHA-256 of `bytes`.
+pub fn hash_bytes(bytes: &[u8]) -> Hash {
+    Sha256::digest(bytes).into()
+}
+
+/// Why a byte string could not be decoded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The input ended in the middle of a value
+    UnexpectedEof,
+    /// The leading version byte is not one this build understands
+    UnsupportedVersion(u8),
+    /// An enum tag byte does not name a known variant
+    InvalidTag { what: &'static str, tag: u8 },
+    /// A length field is larger than the value it describes allows
+    InvalidLength { what: &'static str, len: usize },
+    /// A string field is not valid UTF-8
+    InvalidUtf8,
+    /// The value was decoded but input bytes are left over
+    TrailingBytes(usize),
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::UnexpectedEof => write!(f, "unexpected end of input"),
+            DecodeError::UnsupportedVersion(v) => write!(f, "unsupported encoding version {}", v),
+            DecodeError::InvalidTag { what, tag } => write!(f, "invalid {} tag {}", what, tag),
+            DecodeError::InvalidLength { what, len } => write!(f, "invalid {} length {}", what, len),
+            DecodeError::InvalidUtf8 => write!(f, "invalid utf-8 string"),
+            DecodeError::TrailingBytes(n) => write!(f, "{} trailing bytes after value", n),
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+/// Appends fixed-width little-endian fields to a buffer.
+///
+/// Variable-length values (byte strings, lists) are prefixed with their
+/// length as a u32, so every value has exactly one encoding.
+#[derive(Debug, Default)]
+pub struct Encoder {
+    buf: Vec<u8>,
+}
+
+impl Encoder {
+    pub fn new() -> Self {
+        Self { buf: Vec::new() }
+    }
+
+    /// Starts a top-level value with the format version.
+    pub fn versioned() -> Self {
+        let mut enc = Self::new();
+        enc.u8(ENCODING_VERSION);
+        enc
+    }
+
+    pub fn u8(&mut self, v: u8) {
+        self.buf.push(v);
+    }
+
+    pub fn bool(&mut self, v: bool) {
+        self.u8(v as u8);
+    }
+
+    pub fn u32(&mut self, v: u32) {
+        self.buf.extend_from_slice(&v.to_le_bytes());
+    }
+
+    pub fn u64(&mut self, v: u64) {
+        self.buf.extend_from_slice(&v.to_le_bytes());
+    }
+
+    /// Raw bytes of a fixed, known size (addresses, hashes, signatures).
+    pub fn fixed(&mut self, bytes: &[u8]) {
+        self.buf.extend_from_slice(bytes);
+    }
+
+    /// Length-prefixed byte string.
+    pub fn bytes(&mut self, bytes: &[u8]) {
+        self.u32(bytes.len() as u32);
+        self.fixed(bytes);
+    }
+
+    pub fn address(&mut self, addr: &Address) {
+        self.fixed(&addr.0);
+    }
+
+    pub fn finish(self) -> Vec<u8> {
+        self.buf
+    }
+}
+
+/// Reads values written by `Encoder`, failing instead of panicking on bad input.
+#[derive(Debug)]
+pub struct Decoder<'a> {
+    input: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Decoder<'a> {
+    pub fn new(input: &'a [u8]) -> Self {
+        Self { input, pos: 0 }
+    }
+
+    /// Starts reading a top-level value, checking the format version.
+    pub fn versioned(input: &'a [u8]) -> Result<Self, DecodeError> {
+        let mut dec = Self::new(input);
+        match dec.u8()? {
+            ENCODING_VERSION => Ok(dec),
+            v => Err(DecodeError::UnsupportedVersion(v)),
+        }
+    }
+
+    pub fn fixed(&mut self, len: usize) -> Result<&'a [u8], DecodeError> {
+        let end = self.pos.checked_add(len).ok_or(DecodeError::UnexpectedEof)?;
+        let bytes = self.input.get(self.pos..end).ok_or(DecodeError::UnexpectedEof)?;
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    pub fn u8(&mut self) -> Result<u8, DecodeError> {
+        Ok(self.fixed(1)?[0])
+    }
+
+    pub fn bool(&mut self) -> Result<bool, DecodeError> {
+        match self.u8()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            tag => Err(DecodeError::InvalidTag { what: "bool", tag }),
+        }
+    }
+
+    pub fn u32(&mut self) -> Result<u32, DecodeError> {
+        Ok(u32::from_le_bytes(self.fixed(4)?.try_into().unwrap()))
+    }
+
+    pub fn u64(&mut self) -> Result<u64, DecodeError> {
+        Ok(u64::from_le_bytes(self.fixed(8)?.try_into().unwrap()))
+    }
+
+    pub fn bytes(&mut self) -> Result<Vec<u8>, DecodeError> {
+        let len = self.u32()? as usize;
+        Ok(self.fixed(len)?.to_vec())
+    }
+
+    pub fn address(&mut self) -> Result<Address, DecodeError> {
+        Ok(Address(self.fixed(20)?.try_into().unwrap()))
+    }
+
+    /// Fails if any input is left; every top-level decode ends with this.
+    pub fn finish(self) -> Result<(), DecodeError> {
+        match self.input.len() - self.pos {
+            0 => Ok(()),
+            n => Err(DecodeError::TrailingBytes(n)),
+        }
+    }
+}
+
+/// Encodes a program `Result`. Only the first `data_len` data bytes are
+/// written, so stale bytes past the end never affect the encoding.
+pub fn encode_result(enc: &mut Encoder, res: &ProgramResult) {
+    let len = (res.data_len as usize).min(RESULT_DATA_SIZE);
+    enc.bool(res.success);
+    enc.u32(res.error_code);
+    enc.bytes(&res.data[..len]);
+}
+
+pub fn decode_result(dec: &mut Decoder) -> Result<ProgramResult, DecodeError> {
+    let success = dec.bool()?;
+    let error_code = dec.u32()?;
+    let data = dec.bytes()?;
+    if data.len() > RESULT_DATA_SIZE {
+        return Err(DecodeError::InvalidLength { what: "result data", len: data.len() });
+    }
+    Ok(ProgramResult::new_with_data(success, error_code, &data))
+}
+
+fn access_kind_tag(kind: AccessKind) -> u8 {
+    match kind {
+        AccessKind::Load => 0,
+        AccessKind::Store => 1,
+        AccessKind::Fetch => 2,
+    }
+}
+
+fn decode_access_kind(dec: &mut Decoder) -> Result<AccessKind, DecodeError> {
+    match dec.u8()? {
+        0 => Ok(AccessKind::Load),
+        1 => Ok(AccessKind::Store),
+        2 => Ok(AccessKind::Fetch),
+        tag => Err(DecodeError::InvalidTag { what: "access kind", tag }),
+    }
+}
+
+pub fn encode_trap(enc: &mut Encoder, trap: &VmTrap) {
+    match trap {
+        VmTrap::IllegalInstruction { pc, bytes } => {
+            enc.u8(0);
+            enc.u32(*pc);
+            enc.bytes(bytes);
+        }
+        VmTrap::MisalignedAccess { addr, size, kind } => {
+            enc.u8(1);
+            enc.u64(*addr as u64);
+            enc.u64(*size as u64);
+            enc.u8(access_kind_tag(*kind));
+        }
+        VmTrap::OutOfBounds { addr, size, kind } => {
+            enc.u8(2);
+            enc.u64(*addr as u64);
+            enc.u64(*size as u64);
+            enc.u8(access_kind_tag(*kind));
+        }
+        VmTrap::UnknownSyscall(id) => {
+            enc.u8(3);
+            enc.u32(*id);
+        }
+        VmTrap::GuestPanic(msg) => {
+            enc.u8(4);
+            enc.bytes(msg.as_bytes());
+        }
+        VmTrap::OutOfGas(err) => {
+            enc.u8(5);
+            enc.u64(err.requested);
+            enc.u64(err.remaining);
+        }
+    }
+}
+
+pub fn decode_trap(dec: &mut Decoder) -> Result<VmTrap, DecodeError> {
+    Ok(match dec.u8()? {
+        0 => VmTrap::IllegalInstruction { pc: dec.u32()?, bytes: dec.bytes()? },
+        1 => VmTrap::MisalignedAccess {
+            addr: dec.u64()? as usize,
+            size: dec.u64()? as usize,
+            kind: decode_access_kind(dec)?,
+        },
+        2 => VmTrap::OutOfBounds {
+            addr: dec.u64()? as usize,
+            size: dec.u64()? as usize,
+            kind: decode_access_kind(dec)?,
+        },
+        3 => VmTrap::UnknownSyscall(dec.u32()?),
+        4 => VmTrap::GuestPanic(String::from_utf8(dec.bytes()?).map_err(|_| DecodeError::InvalidUtf8)?),
+        5 => VmTrap::OutOfGas(OutOfGas { requested: dec.u64()?, remaining: dec.u64()? }),
+        tag => return Err(DecodeError::InvalidTag { what: "trap", tag }),
+    })
+}
@@ -2,6 +2,8 @@ use types::address::Address;
 use std::rc::Rc;
 use std::cell::RefCell;
 use vm::vm::VM;
+use vm::trap::VmTrap;
+use state::Checkpoint;
 
 /// Represents a single execution context during contract calls.
 #[derive(Debug, Clone)]
@@ -15,6 +17,9 @@ pub struct ExecutionContext {
     // Data passed to the contract call
     pub input_data: Rc<Vec<u8>>, 
 
+    /// Native value moved from `from` to `to` when the call started.
+    pub value: u64,
+
     // Memory page
     pub vm: Rc<RefCell<VM>>,
 
@@ -22,6 +27,13 @@ pub struct ExecutionContext {
 
     // is exe_done marks context as executed
     pub exe_done: bool,
+
+    /// Trap that stopped this context's VM, if it did not halt normally.
+    pub trap: Option<VmTrap>,
+
+    /// State checkpoint taken right before this call started.
+    /// Reverting to it undoes this call and everything it called.
+    pub checkpoint: Checkpoint,
 }
 
 impl ExecutionContext {
@@ -29,50 +41,63 @@ impl ExecutionContext {
         from: Address,
          to: Address,
          input_data: Vec<u8>,
+         value: u64,
          vm: VM,
+         checkpoint: Checkpoint,
     ) -> Self {
         Self { 
             from,
             to,
             input_data: Rc::new(input_data), 
+            value,
             vm: Rc::new(RefCell::new(vm)), 
             events: Vec::new(),
             exe_done: false,
+            trap: None,
+            checkpoint,
          }
     }
 }
 
 /// A call stack for nested execution contexts in the VM.
+/// 
+/// Every context ever pushed during a transaction is kept (indexed by the
+/// value `push` returns) so callers can still read a finished callee's
+/// memory and events. `active` tracks which of them are currently running.
 #[derive(Debug)]
 pub struct ContextStack {
     stack: Vec<ExecutionContext>,
+    active: Vec<usize>,
 }
 
 impl ContextStack {
     /// Create a new, empty context stack.
     pub fn new() -> Self {
-        Self { stack: Vec::new() }
+        Self { stack: Vec::new(), active: Vec::new() }
     }
 
     /// Push a new context onto the stack (e.g., when a contract calls another).
+    /// `checkpoint` is the state checkpoint to revert to if this call fails.
     /// returns index of the new execution context
-    pub fn push(&mut self, from: Address, to: Address, input_data: Vec<u8>, vm: VM) -> usize {
+    pub fn push(&mut self, from: Address, to: Address, input_data: Vec<u8>, value: u64, vm: VM, checkpoint: Checkpoint) -> usize {
         let index = self.stack.len();
-        self.stack.push(
-            ExecutionContext { 
-                from, 
-                to, 
-                input_data:Rc::new(input_data), 
-                vm:Rc::new(RefCell::new(vm)), 
-                events: Vec::new(),
-                exe_done: false,
-            });
+        self.stack.push(ExecutionContext::new(from, to, input_data, value, vm, checkpoint));
+        self.active.push(index);
         index
     }
 
-    /// Pop the most recent context off the stack (e.g., when returning from a call).
-    pub fn pop(&mut self) -> Option<ExecutionContext> {
-        self.stack.pop()
+    /// Pop the running context (e.g., when returning from a call).
+    /// The context stays readable through `get`; returns its index.
+    pub fn pop(&mut self) -> Option<usize> {
+        self.active.pop()
+    }
+
+    /// Drops the events of context `start` and every context it spawned.
+    /// Used when a call fails and its effects are reverted.
+    pub fn discard_events_from(&mut self, start: usize) {
+        for ctx in self.stack.iter_mut().skip(start) {
+            ctx.events.clear();
+        }
     }
 
     /// Peek execution context index without modifying the stack.
@@ -84,13 +109,19 @@ impl ContextStack {
         self.stack.get_mut(i)
     }
 
-    /// Peek at the current execution context without modifying the stack.
+    /// Peek at the current (running) execution context without modifying the stack.
     pub fn current(&self) -> Option<&ExecutionContext> {
-        self.stack.last()
+        self.active.last().and_then(|&i| self.stack.get(i))
     }
 
     pub fn current_mut(&mut self) -> Option<&mut ExecutionContext> {
-        self.stack.last_mut()
+        let index = *self.active.last()?;
+        self.stack.get_mut(index)
+    }
+
+    /// Number of calls currently running (the call depth).
+    pub fn depth(&self) -> usize {
+        self.active.len()
     }
 
 
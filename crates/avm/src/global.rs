@@ -9,4 +9,26 @@ impl Config {
 
     pub const PROGRAM_START_ADDR: u32 = 0x400;
     pub const RESULT_ADDR: u32 = 0x100;
+
+    /// Gas available to a single transaction, shared by all nested calls.
+    pub const DEFAULT_GAS_LIMIT: u64 = 100_000_000;
+
+    /// Flat gas paid by transactions that run no contract code (transfers
+    /// and account creation).
+    pub const NATIVE_TX_GAS: u64 = 1_000;
+
+    /// Error code of a call whose caller cannot pay the value attached to it.
+    pub const INSUFFICIENT_FUNDS: u32 = 0xFFFF_0100;
+
+    /// Error code of a transaction whose nonce does not match the sender's account.
+    pub const INVALID_NONCE: u32 = 0xFFFF_0101;
+
+    /// Error code of a signed transaction whose signature does not match its sender.
+    pub const INVALID_SIGNATURE: u32 = 0xFFFF_0102;
+
+    /// Error code of a call to an address that holds no contract code.
+    pub const NOT_A_CONTRACT: u32 = 0xFFFF_0103;
+
+    /// Error code of a call whose input is longer than `MAX_INPUT_LEN`.
+    pub const INPUT_TOO_LARGE: u32 = 0xFFFF_0104;
 }
@@ -33,9 +33,16 @@ impl HostShim {
 }
 
 impl<'a> HostInterface for HostShim {
-    fn call_program(&mut self, from: [u8; 20], to: [u8; 20], input_data: Vec<u8>) -> (u32, usize) {
+    fn call_program(&mut self, to: [u8; 20], input_data: Vec<u8>, value: u64) -> (u32, usize) {
         unsafe {
-            return (*self.avm_ptr).call_contract(Address(from), Address(to), input_data);
+            let avm = &mut *self.avm_ptr;
+
+            // The caller is whoever is executing right now, never a guest-supplied address.
+            let from = match avm.context_stack.current() {
+                Some(ctx) => ctx.to,
+                None => return (0, 0),
+            };
+            avm.call_contract(from, Address(to), input_data, value)
         }
     }
 
@@ -83,12 +90,19 @@ impl<'a> HostInterface for HostShim {
             let avm = &mut *self.avm_ptr;
             let to_addr = Address(to);
 
-            // Use the active execution context to determine the sender.
+            // The executing contract pays from its own balance, never its caller's.
             let ctx = match avm.context_stack.current() {
                 Some(c) => c,
                 None => return false,
             };
-            avm.apply_transfer(ctx.from, to_addr, value)
+            avm.apply_transfer(ctx.to, to_addr, value)
+        }
+    }
+
+    fn call_value(&mut self) -> u64 {
+        unsafe {
+            let avm = &*self.avm_ptr;
+            avm.context_stack.current().map(|ctx| ctx.value).unwrap_or(0)
         }
     }
 
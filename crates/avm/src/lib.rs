@@ -3,7 +3,10 @@ pub extern crate hex;
 
 // exports
 pub mod avm;
+pub mod codec;
 pub mod transaction;
+pub mod signed_transaction;
+pub mod block;
 pub mod memory_page_manager;
 pub mod global;
 pub mod execution_context;
@@ -1,8 +1,10 @@
 use types::{Result}; 
 use crate::transaction::Transaction;
+use vm::trap::VmTrap;
+use crate::codec::{self, hash_bytes, DecodeError, Decoder, Encoder, Hash};
 
 /// Represents the result of a transaction execution.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct TransactionReceipt {
     /// Hash of the transaction.
     pub tx: Transaction,
@@ -10,11 +12,14 @@ pub struct TransactionReceipt {
     /// Cumulative gas used in the block including this transaction.
     // pub cumulative_gas_used: u64,
 
-    // /// Gas used by this transaction alone.
-    // pub gas_used: u64,
+    /// Gas used by this transaction alone.
+    pub gas_used: u64,
 
     pub result: Result,
 
+    /// Trap that aborted execution, if the program did not halt normally.
+    pub trap: Option<VmTrap>,
+
     /// List of log entries generated during execution.
     pub events: Vec<Vec<u8>>,
 }
@@ -25,8 +30,9 @@ impl TransactionReceipt {
         TransactionReceipt {
             tx,
             // cumulative_gas_used: 0,
-            // gas_used: 0,
+            gas_used: 0,
             result,
+            trap: None,
             events: Vec::new(),
         }
     }
@@ -42,6 +48,61 @@ impl TransactionReceipt {
         self.events = events;
         self
     }
+
+    /// Records the trap that aborted execution, if any.
+    pub fn set_trap(mut self, trap: Option<VmTrap>) -> Self {
+        self.trap = trap;
+        self
+    }
+
+    /// Records the gas consumed by the transaction.
+    pub fn set_gas_used(mut self, gas_used: u64) -> Self {
+        self.gas_used = gas_used;
+        self
+    }
+    /// Canonical binary encoding (see `codec`), starting with the format version.
+    ///
+    /// LAYOUT: transaction | gas used (8) | result | trap flag (1) [+ trap]
+    /// | event count (4) | events (4 + n each)
+    pub fn encode(&self) -> Vec<u8> {
+        let mut enc = Encoder::versioned();
+        self.tx.encode_into(&mut enc);
+        enc.u64(self.gas_used);
+        codec::encode_result(&mut enc, &self.result);
+        match &self.trap {
+            Some(trap) => {
+                enc.bool(true);
+                codec::encode_trap(&mut enc, trap);
+            }
+            None => enc.bool(false),
+        }
+        enc.u32(self.events.len() as u32);
+        for event in &self.events {
+            enc.bytes(event);
+        }
+        enc.finish()
+    }
+
+    /// Decodes bytes produced by `encode`, rejecting anything else.
+    pub fn decode(bytes: &[u8]) -> core::result::Result<Self, DecodeError> {
+        let mut dec = Decoder::versioned(bytes)?;
+        let tx = Transaction::decode_from(&mut dec)?;
+        let gas_used = dec.u64()?;
+        let result = codec::decode_result(&mut dec)?;
+        let trap = if dec.bool()? { Some(codec::decode_trap(&mut dec)?) } else { None };
+        let count = dec.u32()?;
+        let mut events = Vec::new();
+        for _ in 0..count {
+            events.push(dec.bytes()?);
+        }
+        dec.finish()?;
+        Ok(TransactionReceipt { tx, gas_used, result, trap, events })
+    }
+
+    /// Receipt hash: SHA-256 of the canonical encoding.
+    pub fn hash(&self) -> Hash {
+        hash_bytes(&self.encode())
+    }
 }
 
 use core::fmt;
@@ -49,9 +110,14 @@ use core::fmt;
 impl fmt::Display for TransactionReceipt {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         writeln!(f, "=== Transaction Receipt ===")?;
+        writeln!(f, "Tx hash: 0x{}", hex::encode(self.tx.hash()))?;
         writeln!(f, "From: {:?}", self.tx.from)?;
         writeln!(f, "To: {:?}", self.tx.to)?;
         writeln!(f, "Result: {:?}", self.result)?;
+        writeln!(f, "Gas used: {}", self.gas_used)?;
+        if let Some(trap) = &self.trap {
+            writeln!(f, "Trap: {}", trap)?;
+        }
         writeln!(f, "Events:")?;
 
         for (i, event) in self.events.iter().enumerate() {
@@ -63,7 +129,7 @@ impl fmt::Display for TransactionReceipt {
     }
 }
 
-use compiler::{EventAbi, ParamType};
+use compiler::EventAbi;
 
 impl TransactionReceipt {
     pub fn print_events_pretty(&self, abi_registry: &Vec<EventAbi>, writer: &mut dyn fmt::Write) {
@@ -86,92 +152,20 @@ impl TransactionReceipt {
 
         let mut id = [0u8; 32];
         id.copy_from_slice(&event[..32]);
-        let data = &event[32..];
+        let mut data = &event[32..];
 
         if let Some(abi) = abi_registry.iter().find(|abi| abi.id() == id) {
             let _ = writeln!(writer, "  {}: (", abi.name);
-            let mut offset = 0;
 
             let _ = writeln!(writer, "        ID: 0x{}", hex::encode(id));
             for (i, param) in abi.inputs.iter().enumerate() {
                 let val = if param.indexed {
                     "<indexed>".to_string()
                 } else {
-                    match param.kind {
-                        ParamType::Address => {
-                            if offset + 20 > data.len() {
-                                let _ = writeln!(writer, "  {}: <invalid - data too short>", param.name);
-                                break;
-                            }
-                            let bytes: &[u8] = &data[offset..offset + 20];
-                            offset += 20;
-                            format!("0x{}", hex::encode(bytes))
-                        }
-                        ParamType::Uint(256) => {
-                            if offset + 32 > data.len() {
-                                let _ = writeln!(writer, "  {}: <invalid - data too short>", param.name);
-                                break;
-                            }
-                            let bytes = &data[offset..offset + 32];
-                            offset += 32;
-                            format!("0x{}", hex::encode(bytes))
-                        }
-                        ParamType::Uint(128) => {
-                            if offset + 16 > data.len() {
-                                let _ = writeln!(writer, "  {}: <invalid - data too short>", param.name);
-                                break;
-                            }
-                            let bytes = &data[offset..offset + 16];
-                            offset += 16;
-                            let raw = u128::from_le_bytes(bytes.try_into().unwrap());
-                            format!("{}", raw)
-                        }
-                        ParamType::Uint(64) => {
-                            if offset + 8 > data.len() {
-                                let _ = writeln!(writer, "  {}: <invalid - data too short>", param.name);
-                                break;
-                            }
-                            let bytes = &data[offset..offset + 8];
-                            offset += 8;
-                            let raw = u64::from_le_bytes(bytes.try_into().unwrap());
-                            format!("{}", raw)
-                        }
-                        ParamType::Uint(32) => {
-                            if offset + 4 > data.len() {
-                                let _ = writeln!(writer, "  {}: <invalid - data too short>", param.name);
-                                break;
-                            }
-                            let bytes = &data[offset..offset + 4];
-                            offset += 4;
-                            let raw = u32::from_le_bytes(bytes.try_into().unwrap());
-                            format!("{}", raw)
-                        }
-                        ParamType::Bool => {
-                            if offset + 1 > data.len() {
-                                let _ = writeln!(writer, "  {}: <invalid - data too short>", param.name);
-                                break;
-                            }
-                            let b = data[offset];
-                            offset += 1;
-                            format!("{}", b != 0)
-                        }
-                        ParamType::Bytes => {
-                            if offset + 1 > data.len() {
-                                let _ = writeln!(writer, "  {}: <invalid - data too short>", param.name);
-                                break;
-                            }
-                            let len = data[offset] as usize;
-                            offset += 1;
-                            if offset + len > data.len() {
-                                let _ = writeln!(writer, "  {}: <invalid - data too short>", param.name);
-                                break;
-                            }
-                            let bytes = &data[offset..offset + len];
-                            offset += len;
-                            format!("0x{}", hex::encode(bytes))
-                        }
-                        _ => {
-                            let _ = writeln!(writer, "  {}: <unimplemented type>", param.name);
+                    match param.kind.format_value(&mut data) {
+                        Ok(val) => val,
+                        Err(err) => {
+                            let _ = writeln!(writer, "  {}: <invalid - {}>", param.name, err);
                             break;
                         }
                     }
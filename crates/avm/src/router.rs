@@ -1,9 +1,23 @@
+use types::Encode;
+
 /// Represents a function call input for the VM router
 pub struct HostFuncCall {
     pub selector: u8,
     pub args: Vec<u8>,
 }
 
+impl HostFuncCall {
+    /// A call whose arguments are encoded with `types::Encode`, the layout
+    /// the guest's `DataParser` reads. Pass several arguments as a tuple:
+    /// `HostFuncCall::new(0x02, &(to, amount))`.
+    pub fn new(selector: u8, args: &impl Encode) -> Self {
+        Self {
+            selector,
+            args: args.to_encoded_bytes(),
+        }
+    }
+}
+
 /// Encodes multiple function calls into a single buffer for the guest VM router.
 pub fn encode_router_calls(calls: &[HostFuncCall]) -> Vec<u8> {
     let mut encoded = Vec::new();
@@ -0,0 +1,99 @@
+use k256::ecdsa::{RecoveryId, Signature, SigningKey, VerifyingKey};
+use sha2::{Digest, Sha256};
+use types::address::Address;
+
+use crate::codec::{hash_bytes, DecodeError, Decoder, Encoder, Hash};
+use crate::transaction::Transaction;
+
+/// Length of a recoverable signature: r (32) || s (32) || recovery id (1).
+pub const SIGNATURE_LEN: usize = 65;
+
+/// A transaction together with the sender's secp256k1 signature.
+///
+/// EDUCATIONAL PURPOSE: `Transaction::from` on its own is just a claim. The
+/// signature proves that whoever holds the private key behind `from` approved
+/// exactly these fields. Like Ethereum, we use a *recoverable* signature: the
+/// public key (and so the address) can be computed from the signature and the
+/// signed hash, so transactions do not need to carry the public key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignedTransaction {
+    pub tx: Transaction,
+    pub signature: [u8; SIGNATURE_LEN],
+}
+
+impl SignedTransaction {
+    /// Signs `tx` with `key`. The signature always has a low `s`.
+    ///
+    /// The caller is responsible for setting `tx.from` to the key's address;
+    /// otherwise the AVM will reject the transaction.
+    pub fn sign(tx: Transaction, key: &SigningKey) -> Self {
+        let (mut sig, mut recovery_id) = key
+            .sign_prehash_recoverable(&tx.signing_hash())
+            .expect("signing a 32-byte hash cannot fail");
+        if let Some(normalized) = sig.normalize_s() {
+            sig = normalized;
+            recovery_id = RecoveryId::new(!recovery_id.is_y_odd(), recovery_id.is_x_reduced());
+        }
+
+        let mut signature = [0u8; SIGNATURE_LEN];
+        signature[..64].copy_from_slice(&sig.to_bytes());
+        signature[64] = recovery_id.to_byte();
+        Self { tx, signature }
+    }
+
+    /// Recovers the address that produced the signature, if it is well formed.
+    ///
+    /// EDUCATIONAL: For every valid signature (r, s) there is a second one,
+    /// (r, n - s) with the other recovery id, that recovers the same key.
+    /// Accepting both would let anyone rewrite a signature without the key
+    /// and so change the transaction's hash. Like Ethereum, only the low-s
+    /// form is valid.
+    pub fn recover_sender(&self) -> Option<Address> {
+        let sig = Signature::from_slice(&self.signature[..64]).ok()?;
+        if sig.normalize_s().is_some() {
+            return None;
+        }
+        let recovery_id = RecoveryId::from_byte(self.signature[64])?;
+        let key = VerifyingKey::recover_from_prehash(&self.tx.signing_hash(), &sig, recovery_id).ok()?;
+        Some(address_from_key(&key))
+    }
+
+    /// True if the signature was made by the key behind `tx.from`.
+    pub fn verify(&self) -> bool {
+        self.recover_sender() == Some(self.tx.from)
+    }
+    /// Canonical binary encoding: version, transaction, then the 65 signature bytes.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut enc = Encoder::versioned();
+        self.tx.encode_into(&mut enc);
+        enc.fixed(&self.signature);
+        enc.finish()
+    }
+
+    /// Decodes bytes produced by `encode`. The signature is not checked here.
+    pub fn decode(bytes: &[u8]) -> Result<Self, DecodeError> {
+        let mut dec = Decoder::versioned(bytes)?;
+        let tx = Transaction::decode_from(&mut dec)?;
+        let signature = dec.fixed(SIGNATURE_LEN)?.try_into().unwrap();
+        dec.finish()?;
+        Ok(SignedTransaction { tx, signature })
+    }
+
+    /// Id of the signed transaction: SHA-256 of its canonical encoding.
+    pub fn hash(&self) -> Hash {
+        hash_bytes(&self.encode())
+    }
+}
+
+/// Derives an account address from a public key.
+///
+/// EDUCATIONAL: The address is the last 20 bytes of the SHA-256 hash of the
+/// uncompressed public key (without its 0x04 prefix). Ethereum does the same
+/// with Keccak-256.
+pub fn address_from_key(key: &VerifyingKey) -> Address {
+    let point = key.to_encoded_point(false);
+    let hash = Sha256::digest(&point.as_bytes()[1..]);
+    let mut addr = [0u8; 20];
+    addr.copy_from_slice(&hash[12..]);
+    Address(addr)
+}
@@ -1,6 +1,8 @@
 use types::address::Address;
+use sha2::{Digest, Sha256};
+use crate::codec::{hash_bytes, DecodeError, Decoder, Encoder, Hash};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum TransactionType {
     /// Type 0 - Regular value transfer (not a contract)
     Transfer = 0,
@@ -12,7 +14,7 @@ pub enum TransactionType {
     ProgramCall = 2,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Transaction {
     pub tx_type: TransactionType, // type of transaction
     pub to: Address,              // recipient address
@@ -22,8 +24,106 @@ pub struct Transaction {
     pub nonce: u64,               // transaction nonce
 }
 
+impl TransactionType {
+    /// Wire value of the transaction type.
+    pub fn as_u8(&self) -> u8 {
+        self.clone() as u8
+    }
+
+    pub fn from_u8(tag: u8) -> Result<Self, DecodeError> {
+        match tag {
+            0 => Ok(TransactionType::Transfer),
+            1 => Ok(TransactionType::CreateAccount),
+            2 => Ok(TransactionType::ProgramCall),
+            tag => Err(DecodeError::InvalidTag { what: "transaction type", tag }),
+        }
+    }
+}
+
+/// Prefix of every transaction signing payload.
+///
+/// EDUCATIONAL: A domain tag makes sure a signature over a transaction can
+/// never be mistaken for a signature over some other kind of message.
+pub const TX_SIGNING_DOMAIN: &[u8] = b"AVM-TX";
+
+/// Version of the signing payload layout.
+pub const TX_SIGNING_VERSION: u8 = 1;
+
+impl Transaction {
+    /// Canonical bytes a sender signs to authorize this transaction.
+    ///
+    /// LAYOUT (integers little-endian):
+    /// - "AVM-TX" domain tag, 1-byte version
+    /// - 1 byte: transaction type
+    /// - 20 bytes: from, 20 bytes: to
+    /// - 8 bytes: value, 8 bytes: nonce
+    /// - 4 bytes: data length, followed by the data
+    ///
+    /// Every field is covered, so changing any of them invalidates the signature.
+    pub fn signing_payload(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(TX_SIGNING_DOMAIN.len() + 62 + self.data.len());
+        out.extend_from_slice(TX_SIGNING_DOMAIN);
+        out.push(TX_SIGNING_VERSION);
+        out.push(self.tx_type.as_u8());
+        out.extend_from_slice(&self.from.0);
+        out.extend_from_slice(&self.to.0);
+        out.extend_from_slice(&self.value.to_le_bytes());
+        out.extend_from_slice(&self.nonce.to_le_bytes());
+        out.extend_from_slice(&(self.data.len() as u32).to_le_bytes());
+        out.extend_from_slice(&self.data);
+        out
+    }
+
+    /// SHA-256 of the signing payload; this is what actually gets signed.
+    pub fn signing_hash(&self) -> [u8; 32] {
+        Sha256::digest(self.signing_payload()).into()
+    }
+
+    /// Canonical binary encoding (see `codec`), starting with the format version.
+    ///
+    /// LAYOUT: type (1) | from (20) | to (20) | value (8) | nonce (8) | data (4 + n)
+    pub fn encode(&self) -> Vec<u8> {
+        let mut enc = Encoder::versioned();
+        self.encode_into(&mut enc);
+        enc.finish()
+    }
+
+    /// Decodes bytes produced by `encode`, rejecting anything else.
+    pub fn decode(bytes: &[u8]) -> Result<Self, DecodeError> {
+        let mut dec = Decoder::versioned(bytes)?;
+        let tx = Self::decode_from(&mut dec)?;
+        dec.finish()?;
+        Ok(tx)
+    }
+
+    /// Transaction id: SHA-256 of the canonical encoding.
+    pub fn hash(&self) -> Hash {
+        hash_bytes(&self.encode())
+    }
+
+    pub(crate) fn encode_into(&self, enc: &mut Encoder) {
+        enc.u8(self.tx_type.as_u8());
+        enc.address(&self.from);
+        enc.address(&self.to);
+        enc.u64(self.value);
+        enc.u64(self.nonce);
+        enc.bytes(&self.data);
+    }
+
+    pub(crate) fn decode_from(dec: &mut Decoder) -> Result<Self, DecodeError> {
+        Ok(Transaction {
+            tx_type: TransactionType::from_u8(dec.u8()?)?,
+            from: dec.address()?,
+            to: dec.address()?,
+            value: dec.u64()?,
+            nonce: dec.u64()?,
+            data: dec.bytes()?,
+        })
+    }
+}
+
 /// Holds a set of transactions to be processed as a unit
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct TransactionBundle {
     pub transactions: Vec<Transaction>,
 }
@@ -44,4 +144,26 @@ impl TransactionBundle {
     pub fn is_empty(&self) -> bool {
         self.transactions.is_empty()
     }
+
+    /// Canonical binary encoding: version, transaction count (4), then each transaction.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut enc = Encoder::versioned();
+        enc.u32(self.transactions.len() as u32);
+        for tx in &self.transactions {
+            tx.encode_into(&mut enc);
+        }
+        enc.finish()
+    }
+
+    /// Decodes bytes produced by `encode`, rejecting anything else.
+    pub fn decode(bytes: &[u8]) -> Result<Self, DecodeError> {
+        let mut dec = Decoder::versioned(bytes)?;
+        let count = dec.u32()?;
+        let mut transactions = Vec::new();
+        for _ in 0..count {
+            transactions.push(Transaction::decode_from(&mut dec)?);
+        }
+        dec.finish()?;
+        Ok(TransactionBundle { transactions })
+    }
 }
\ No newline at end of file
@@ -0,0 +1,99 @@
+#[path = "common/chain.rs"]
+mod chain;
+
+use avm::block::{merkle_root, BlockHeader};
+use avm::global::Config;
+use avm::signed_transaction::{address_from_key, SignedTransaction};
+use chain::*;
+use k256::ecdsa::SigningKey;
+use types::address::Address;
+
+fn signer(seed: u8) -> (SigningKey, Address) {
+    let key = key(seed);
+    let addr = address_from_key(key.verifying_key());
+    (key, addr)
+}
+
+#[test]
+fn test_run_block_links_to_parent() {
+    let (alice_key, alice) = signer(1);
+    let (bob_key, bob) = signer(2);
+    let mut avm = funded_avm(&alice, 1_000);
+    let genesis = BlockHeader::genesis(&avm.state);
+
+    let transactions = vec![signed_transfer(&alice_key, bob, 10, 0), signed_transfer(&alice_key, bob, 20, 1)];
+    let (block1, receipts) = avm.run_block(&genesis, 1_000, transactions.clone());
+    assert_eq!(receipts.len(), 2);
+    assert!(receipts.iter().all(|r| r.result.success));
+    assert_eq!(avm.state.get_account(&bob).unwrap().balance, 30);
+
+    assert_eq!(block1.header.number, 1);
+    assert_eq!(block1.header.parent_hash, genesis.hash());
+    assert_eq!(block1.header.timestamp, 1_000);
+    assert_eq!(block1.transactions, transactions);
+    assert_eq!(block1.header.state_root, avm.state.state_root());
+    assert_ne!(block1.header.state_root, genesis.state_root);
+
+    // The block commits to the signed transactions, signatures included
+    let tx_hashes: Vec<_> = transactions.iter().map(SignedTransaction::hash).collect();
+    let receipt_hashes: Vec<_> = receipts.iter().map(|r| r.hash()).collect();
+    assert_eq!(block1.header.transactions_root, merkle_root(&tx_hashes));
+    assert_eq!(block1.header.receipts_root, merkle_root(&receipt_hashes));
+
+    let (block2, _) = avm.run_block(&block1.header, 2_000, vec![signed_transfer(&bob_key, alice, 5, 0)]);
+    assert_eq!(block2.header.number, 2);
+    assert_eq!(block2.header.parent_hash, block1.hash());
+}
+
+#[test]
+fn test_failed_transactions_still_get_receipts() {
+    let (alice_key, alice) = signer(1);
+    let (mallory_key, _) = signer(3);
+    let mut avm = funded_avm(&alice, 1_000);
+    let genesis = BlockHeader::genesis(&avm.state);
+
+    // Mallory signs a transfer out of Alice's account
+    let mut forged = signed_transfer(&alice_key, BOB, 500, 0);
+    forged.signature = SignedTransaction::sign(forged.tx.clone(), &mallory_key).signature;
+
+    // Wrong nonce, a forgery, an overdraft, then a valid transfer
+    let transactions = vec![
+        signed_transfer(&alice_key, BOB, 10, 7),
+        forged,
+        signed_transfer(&alice_key, BOB, 5_000, 0),
+        signed_transfer(&alice_key, BOB, 10, 1),
+    ];
+    let (_, receipts) = avm.run_block(&genesis, 1, transactions);
+
+    assert_eq!(receipts.len(), 4);
+    assert_eq!({ receipts[0].result.error_code }, Config::INVALID_NONCE);
+    assert_eq!({ receipts[1].result.error_code }, Config::INVALID_SIGNATURE);
+    assert!(!receipts[2].result.success);
+    assert!(receipts[3].result.success);
+    assert_eq!(avm.state.get_account(&BOB).unwrap().balance, 10);
+}
+
+#[test]
+fn test_block_execution_is_deterministic() {
+    let (alice_key, alice) = signer(1);
+    let transactions = vec![signed_transfer(&alice_key, BOB, 10, 0), signed_transfer(&alice_key, BOB, 1, 1)];
+
+    let mut a = funded_avm(&alice, 1_000);
+    let mut b = funded_avm(&alice, 1_000);
+    let genesis = BlockHeader::genesis(&a.state);
+    assert_eq!(genesis, BlockHeader::genesis(&b.state));
+
+    let (block_a, _) = a.run_block(&genesis, 42, transactions.clone());
+    let (block_b, _) = b.run_block(&genesis, 42, transactions);
+    assert_eq!(block_a.hash(), block_b.hash());
+    assert_eq!(BlockHeader::decode(&block_a.header.encode()), Ok(block_a.header));
+}
+
+#[test]
+fn test_merkle_root_depends_on_order() {
+    let leaves = [[1u8; 32], [2u8; 32], [3u8; 32]];
+    let swapped = [[2u8; 32], [1u8; 32], [3u8; 32]];
+    assert_ne!(merkle_root(&leaves), merkle_root(&swapped));
+    assert_ne!(merkle_root(&leaves[..1]), leaves[0]);
+    assert_ne!(merkle_root(&[]), merkle_root(&leaves[..1]));
+}
@@ -0,0 +1,76 @@
+#[path = "common/asm.rs"]
+mod asm;
+#[path = "common/chain.rs"]
+mod chain;
+
+use asm::*;
+use avm::avm::AVM;
+use avm::global::Config;
+use chain::*;
+use storage::StorageKey;
+use types::address::Address;
+use vm::sys_call::{SYSCALL_CALL_VALUE, SYSCALL_STORAGE_SET};
+
+/// Stores the call's value (8 bytes) under domain "V", key "k", then either
+/// halts or traps.
+fn value_recorder(fail: bool) -> Vec<u8> {
+    let program = Program::new()
+        .data(0x10, b"V")
+        .data(0x20, b"k")
+        .li(A7, SYSCALL_CALL_VALUE as i32)
+        .ecall()
+        .addi(A5, A0, 0).li(A6, 8)
+        .li(A1, 0x10).li(A2, 1)
+        .li(A3, 0x20).li(A4, 1)
+        .li(A7, SYSCALL_STORAGE_SET as i32)
+        .ecall();
+    if fail {
+        program.illegal().build()
+    } else {
+        program.succeed().ebreak().build()
+    }
+}
+
+fn with_recorder(fail: bool) -> AVM {
+    let mut avm = new_avm(1_000);
+    avm.run_tx(deploy(CONTRACT, value_recorder(fail), 0));
+    avm
+}
+
+fn balance(avm: &AVM, addr: &Address) -> u128 {
+    avm.state.get_account(addr).unwrap().balance
+}
+
+#[test]
+fn test_value_moves_with_the_call() {
+    let mut avm = with_recorder(false);
+
+    let receipt = avm.run_tx(call(CONTRACT, vec![], 25, 1));
+    assert!(receipt.result.success);
+
+    assert_eq!((balance(&avm, &ALICE), balance(&avm, &CONTRACT)), (975, 25));
+    let storage = &avm.state.get_account(&CONTRACT).unwrap().storage;
+    assert_eq!(storage.get(&StorageKey::new(b"V", b"k")), Some(&25u64.to_le_bytes().to_vec()));
+}
+
+#[test]
+fn test_unaffordable_value_fails_the_call() {
+    let mut avm = with_recorder(false);
+
+    let receipt = avm.run_tx(call(CONTRACT, vec![], 5_000, 1));
+    assert!(!receipt.result.success);
+    assert_eq!({ receipt.result.error_code }, Config::INSUFFICIENT_FUNDS);
+
+    assert_eq!((balance(&avm, &ALICE), balance(&avm, &CONTRACT)), (1_000, 0));
+    assert!(avm.state.get_account(&CONTRACT).unwrap().storage.is_empty());
+}
+
+#[test]
+fn test_failed_call_returns_value() {
+    let mut avm = with_recorder(true);
+
+    let receipt = avm.run_tx(call(CONTRACT, vec![], 25, 1));
+    assert!(!receipt.result.success);
+
+    assert_eq!((balance(&avm, &ALICE), balance(&avm, &CONTRACT)), (1_000, 0));
+}
@@ -0,0 +1,110 @@
+use avm::codec::{DecodeError, ENCODING_VERSION};
+use avm::receipt::TransactionReceipt;
+use avm::signed_transaction::SignedTransaction;
+use avm::transaction::{Transaction, TransactionBundle, TransactionType};
+use k256::ecdsa::SigningKey;
+use types::address::Address;
+use types::result::Result;
+use vm::gas::OutOfGas;
+use vm::trap::{AccessKind, VmTrap};
+
+fn sample_tx() -> Transaction {
+    Transaction {
+        tx_type: TransactionType::ProgramCall,
+        to: Address([0x22; 20]),
+        from: Address([0x11; 20]),
+        data: vec![0xde, 0xad],
+        value: 7,
+        nonce: 3,
+    }
+}
+
+#[test]
+fn test_transaction_layout_is_stable() {
+    let mut expected = vec![ENCODING_VERSION, 2];
+    expected.extend_from_slice(&[0x11; 20]);
+    expected.extend_from_slice(&[0x22; 20]);
+    expected.extend_from_slice(&7u64.to_le_bytes());
+    expected.extend_from_slice(&3u64.to_le_bytes());
+    expected.extend_from_slice(&[2, 0, 0, 0, 0xde, 0xad]);
+
+    assert_eq!(sample_tx().encode(), expected);
+    assert_eq!(Transaction::decode(&expected), Ok(sample_tx()));
+}
+
+#[test]
+fn test_hash_covers_every_field() {
+    let tx = sample_tx();
+    assert_eq!(tx.hash(), sample_tx().hash());
+
+    let mut other = sample_tx();
+    other.nonce += 1;
+    assert_ne!(tx.hash(), other.hash());
+
+    let mut other = sample_tx();
+    other.data.push(0);
+    assert_ne!(tx.hash(), other.hash());
+}
+
+#[test]
+fn test_bundle_roundtrip() {
+    let mut second = sample_tx();
+    second.tx_type = TransactionType::Transfer;
+    second.data.clear();
+    let bundle = TransactionBundle::new(vec![sample_tx(), second]);
+
+    assert_eq!(TransactionBundle::decode(&bundle.encode()), Ok(bundle));
+}
+
+#[test]
+fn test_receipt_roundtrip() {
+    let receipts = [
+        TransactionReceipt::new(sample_tx(), Result::new_with_data(true, 0, &[1, 2, 3]))
+            .set_gas_used(1234)
+            .set_events(vec![vec![9, 9], vec![]]),
+        TransactionReceipt::new(sample_tx(), Result::new(false, 0xFFFF_0001))
+            .set_trap(Some(VmTrap::OutOfGas(OutOfGas { requested: 10, remaining: 2 }))),
+        TransactionReceipt::new(sample_tx(), Result::new(false, 0xFFFF_0004))
+            .set_trap(Some(VmTrap::OutOfBounds { addr: 0x1000, size: 4, kind: AccessKind::Store })),
+        TransactionReceipt::new(sample_tx(), Result::new(false, 0xFFFF_0006))
+            .set_trap(Some(VmTrap::GuestPanic("boom".to_string()))),
+    ];
+
+    for receipt in receipts {
+        let bytes = receipt.encode();
+        assert_eq!(TransactionReceipt::decode(&bytes).as_ref(), Ok(&receipt));
+        assert_eq!(TransactionReceipt::decode(&bytes).unwrap().hash(), receipt.hash());
+    }
+}
+
+#[test]
+fn test_signed_transaction_roundtrip() {
+    let key = SigningKey::from_bytes(&[5u8; 32].into()).unwrap();
+    let signed = SignedTransaction::sign(sample_tx(), &key);
+
+    let decoded = SignedTransaction::decode(&signed.encode()).unwrap();
+    assert_eq!(decoded, signed);
+    assert_ne!(signed.hash(), sample_tx().hash());
+}
+
+#[test]
+fn test_decode_rejects_malformed_input() {
+    let bytes = sample_tx().encode();
+
+    let mut wrong_version = bytes.clone();
+    wrong_version[0] = 99;
+    assert_eq!(Transaction::decode(&wrong_version), Err(DecodeError::UnsupportedVersion(99)));
+
+    assert_eq!(Transaction::decode(&bytes[..bytes.len() - 1]), Err(DecodeError::UnexpectedEof));
+
+    let mut trailing = bytes.clone();
+    trailing.push(0);
+    assert_eq!(Transaction::decode(&trailing), Err(DecodeError::TrailingBytes(1)));
+
+    let mut bad_type = bytes;
+    bad_type[1] = 7;
+    assert_eq!(
+        Transaction::decode(&bad_type),
+        Err(DecodeError::InvalidTag { what: "transaction type", tag: 7 })
+    );
+}
@@ -0,0 +1,76 @@
+#![allow(dead_code)]
+
+use avm::global::Config;
+
+/// Tiny RV32 assembler for hand-written test contracts.
+///
+/// Contracts are flat images: `set_code` loads them at address 0 and starts
+/// executing at `Config::PROGRAM_START_ADDR`, so data can live below that
+/// address and instructions start right at it.
+pub struct Program {
+    image: Vec<u8>,
+}
+
+impl Program {
+    pub fn new() -> Self {
+        Self { image: vec![0u8; Config::PROGRAM_START_ADDR as usize] }
+    }
+
+    /// Places raw bytes at a fixed address below the entry point.
+    pub fn data(mut self, addr: usize, bytes: &[u8]) -> Self {
+        assert!(addr + bytes.len() <= Config::PROGRAM_START_ADDR as usize);
+        self.image[addr..addr + bytes.len()].copy_from_slice(bytes);
+        self
+    }
+
+    fn emit(mut self, word: u32) -> Self {
+        self.image.extend_from_slice(&word.to_le_bytes());
+        self
+    }
+
+    pub fn addi(self, rd: u32, rs1: u32, imm: i32) -> Self {
+        self.emit((((imm as u32) & 0xfff) << 20) | (rs1 << 15) | (rd << 7) | 0x13)
+    }
+
+    /// Loads a small constant (fits in 12 signed bits).
+    pub fn li(self, rd: u32, imm: i32) -> Self {
+        self.addi(rd, 0, imm)
+    }
+
+    pub fn sb(self, rs2: u32, rs1: u32, offset: i32) -> Self {
+        let imm = (offset as u32) & 0xfff;
+        self.emit(((imm >> 5) << 25) | (rs2 << 20) | (rs1 << 15) | ((imm & 0x1f) << 7) | 0x23)
+    }
+
+    pub fn ecall(self) -> Self {
+        self.emit(0x0000_0073)
+    }
+
+    pub fn ebreak(self) -> Self {
+        self.emit(0x0010_0073)
+    }
+
+    /// An encoding the decoder rejects.
+    pub fn illegal(self) -> Self {
+        self.emit(0xffff_ffff)
+    }
+
+    /// Writes `success = true` into the Result struct the AVM reads back.
+    pub fn succeed(self) -> Self {
+        self.li(T0, 1).sb(T0, 0, Config::RESULT_ADDR as i32)
+    }
+
+    pub fn build(self) -> Vec<u8> {
+        self.image
+    }
+}
+
+pub const T0: u32 = 5;
+pub const A0: u32 = 10;
+pub const A1: u32 = 11;
+pub const A2: u32 = 12;
+pub const A3: u32 = 13;
+pub const A4: u32 = 14;
+pub const A5: u32 = 15;
+pub const A6: u32 = 16;
+pub const A7: u32 = 17;
@@ -0,0 +1,63 @@
+#![allow(dead_code)]
+
+use avm::avm::AVM;
+use avm::signed_transaction::{address_from_key, SignedTransaction};
+use avm::transaction::{Transaction, TransactionType};
+use k256::ecdsa::SigningKey;
+use state::State;
+use types::address::Address;
+
+/// Accounts shared by the AVM tests.
+pub const ALICE: Address = Address([0xa1; 20]);
+pub const BOB: Address = Address([0xb0; 20]);
+pub const CONTRACT: Address = Address([0xc0; 20]);
+
+/// An AVM whose committed state gives `ALICE` `alice_balance` AM.
+pub fn new_avm(alice_balance: u128) -> AVM {
+    funded_avm(&ALICE, alice_balance)
+}
+
+/// An AVM whose committed state gives `addr` `balance` AM.
+pub fn funded_avm(addr: &Address, balance: u128) -> AVM {
+    let mut state = State::new();
+    state.get_account_mut(addr).balance = balance;
+    state.commit();
+    AVM::new(4, 64 * 1024, state)
+}
+
+/// A deterministic signing key.
+pub fn key(seed: u8) -> SigningKey {
+    SigningKey::from_bytes(&[seed; 32].into()).unwrap()
+}
+
+/// Builds a transaction with every field spelled out; the helpers below
+/// cover the common shapes.
+pub fn tx(tx_type: TransactionType, from: Address, to: Address, data: Vec<u8>, value: u64, nonce: u64) -> Transaction {
+    Transaction { tx_type, to, from, data, value, nonce }
+}
+
+/// `ALICE` deploys `code` at `to`.
+pub fn deploy(to: Address, code: Vec<u8>, nonce: u64) -> Transaction {
+    tx(TransactionType::CreateAccount, ALICE, to, code, 0, nonce)
+}
+
+/// `ALICE` calls the contract at `to`, attaching `value`.
+pub fn call(to: Address, data: Vec<u8>, value: u64, nonce: u64) -> Transaction {
+    tx(TransactionType::ProgramCall, ALICE, to, data, value, nonce)
+}
+
+/// A native transfer of `value` from `from` to `to`.
+pub fn transfer(from: Address, to: Address, value: u64, nonce: u64) -> Transaction {
+    tx(TransactionType::Transfer, from, to, vec![], value, nonce)
+}
+
+/// A transfer from the address behind `key`, signed with it.
+pub fn signed_transfer(key: &SigningKey, to: Address, value: u64, nonce: u64) -> SignedTransaction {
+    let from = address_from_key(key.verifying_key());
+    SignedTransaction::sign(transfer(from, to, value, nonce), key)
+}
+
+/// The account nonce of `addr` (0 if it does not exist yet).
+pub fn nonce(avm: &AVM, addr: &Address) -> u64 {
+    avm.state.get_account(addr).map(|a| a.nonce).unwrap_or(0)
+}
@@ -0,0 +1,148 @@
+// Auto-generated ABI host client code
+// DO NOT EDIT - Generated from ABI
+
+use avm::transaction::{Transaction, TransactionType};
+use types::address::Address;
+use types::result::Result;
+use types::{Decode, Encode};
+
+/// Builds transactions calling the TokenClient contract
+pub struct TokenClient {
+    /// Address of the deployed contract
+    pub address: Address,
+    /// Account the transactions are sent from
+    pub from: Address,
+}
+
+impl TokenClient {
+    /// Create a new contract client
+    pub fn new(address: Address, from: Address) -> Self {
+        Self { address, from }
+    }
+
+    /// Builds a call to `transfer` (selector 0x02), or `None` if the
+    /// arguments are too long for the router
+    pub fn transfer(
+        &self,
+        nonce: u64,
+        to: Address,
+        amount: u32,
+    ) -> Option<Transaction> {
+        let mut __args = Vec::new();
+        Encode::encode(&to, &mut __args);
+        Encode::encode(&amount, &mut __args);
+        self.routed_call(nonce, 0x02, __args)
+    }
+
+    /// Builds a call to `balance_of` (selector 0x05), or `None` if the
+    /// arguments are too long for the router
+    pub fn balance_of(
+        &self,
+        nonce: u64,
+        owner: Address,
+    ) -> Option<Transaction> {
+        let mut __args = Vec::new();
+        Encode::encode(&owner, &mut __args);
+        self.routed_call(nonce, 0x05, __args)
+    }
+
+    /// Decodes the result of `balance_of`, or `None` if the call failed
+    pub fn decode_balance_of_result(result: &Result) -> Option<u32> {
+        if !result.success {
+            return None;
+        }
+        let len = (result.data_len as usize).min(result.data.len());
+        Decode::decode_exact(&result.data[..len])
+    }
+
+    /// Builds a call to `tag` (selector 0x07), or `None` if the
+    /// arguments are too long for the router
+    pub fn tag(
+        &self,
+        nonce: u64,
+        nonce_arg: u64,
+        holders: &[Address],
+        label: &str,
+    ) -> Option<Transaction> {
+        let mut __args = Vec::new();
+        Encode::encode(&nonce_arg, &mut __args);
+        Encode::encode(&holders, &mut __args);
+        Encode::encode(&label, &mut __args);
+        self.routed_call(nonce, 0x07, __args)
+    }
+
+    /// Decodes the result of `tag`, or `None` if the call failed
+    pub fn decode_tag_result(result: &Result) -> Option<(u64, String,)> {
+        if !result.success {
+            return None;
+        }
+        let len = (result.data_len as usize).min(result.data.len());
+        Decode::decode_exact(&result.data[..len])
+    }
+
+    fn routed_call(&self, nonce: u64, selector: u8, args: Vec<u8>) -> Option<Transaction> {
+        let len = u8::try_from(args.len()).ok()?;
+        let mut data = vec![selector, len];
+        data.extend(args);
+        Some(self.transaction(nonce, data))
+    }
+
+    fn transaction(&self, nonce: u64, data: Vec<u8>) -> Transaction {
+        Transaction {
+            tx_type: TransactionType::ProgramCall,
+            to: self.address,
+            from: self.from,
+            data,
+            value: 0,
+            nonce,
+        }
+    }
+}
+
+/// Events emitted by the TokenClient contract
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TokenClientEvent {
+    Transfer {
+        from: Address,
+        to: Address,
+        amount: u32,
+    },
+    Tagged {
+        labels: Vec<String>,
+    },
+}
+
+impl TokenClientEvent {
+    /// Decodes an event from a receipt, or `None` if it is not one of this
+    /// contract's events. The first 32 bytes hold the event name, the rest
+    /// its fields in order.
+    pub fn decode(event: &[u8]) -> Option<Self> {
+        if event.len() < 32 {
+            return None;
+        }
+        let (id, mut fields) = event.split_at(32);
+        if id == Self::id("Transfer") {
+            let decoded = Self::Transfer {
+                from: Decode::decode(&mut fields)?,
+                to: Decode::decode(&mut fields)?,
+                amount: Decode::decode(&mut fields)?,
+            };
+            return fields.is_empty().then_some(decoded);
+        }
+        if id == Self::id("Tagged") {
+            let decoded = Self::Tagged {
+                labels: Decode::decode(&mut fields)?,
+            };
+            return fields.is_empty().then_some(decoded);
+        }
+        None
+    }
+
+    /// The id `event!` gives an event: its name, zero-padded to 32 bytes
+    fn id(name: &str) -> [u8; 32] {
+        let mut id = [0u8; 32];
+        let len = name.len().min(32);
+        id[..len].copy_from_slice(&name.as_bytes()[..len]);
+        id
+    }
+}
@@ -0,0 +1,156 @@
+#[path = "common/token_client.rs"]
+mod token_client;
+
+use avm::router::{encode_router_calls, HostFuncCall};
+use avm::transaction::TransactionType;
+use compiler::{AbiCodeGenerator, CodegenTarget, ContractAbi};
+use token_client::{TokenClient, TokenClientEvent};
+use types::address::Address;
+use types::result::Result;
+use types::Encode;
+
+const TOKEN: Address = Address([0x70; 20]);
+const ALICE: Address = Address([0xa1; 20]);
+const BOB: Address = Address([0xb0; 20]);
+
+// `common/token_client.rs` is the generator's output for this ABI:
+// avm32 client --host --abi <this json> --contract TokenClient
+const TOKEN_ABI: &str = r#"{
+  "version": "1.0",
+  "functions": [
+    {
+      "name": "transfer",
+      "selector": 2,
+      "inputs": [
+        { "name": "to", "type": "address" },
+        { "name": "amount", "type": "uint32" }
+      ],
+      "outputs": [{ "type": "result" }]
+    },
+    {
+      "name": "balance_of",
+      "selector": 5,
+      "inputs": [{ "name": "owner", "type": "address" }],
+      "outputs": [{ "type": "uint32" }]
+    },
+    {
+      "name": "tag",
+      "selector": 7,
+      "inputs": [
+        { "name": "nonce", "type": "uint64" },
+        { "name": "holders", "type": "address[]" },
+        { "name": "label", "type": "string" }
+      ],
+      "outputs": [{ "type": "(uint64,string)" }]
+    }
+  ],
+  "events": [
+    {
+      "name": "Transfer",
+      "inputs": [
+        { "name": "from", "type": "address", "indexed": false },
+        { "name": "to", "type": "address", "indexed": false },
+        { "name": "amount", "type": "uint32", "indexed": false }
+      ]
+    },
+    {
+      "name": "Tagged",
+      "inputs": [
+        { "name": "labels", "type": "string[]", "indexed": false }
+      ]
+    }
+  ]
+}"#;
+
+fn event_bytes(name: &str, fields: &impl Encode) -> Vec<u8> {
+    let mut event = vec![0u8; 32];
+    event[..name.len()].copy_from_slice(name.as_bytes());
+    fields.encode(&mut event);
+    event
+}
+
+#[test]
+fn test_generated_client_is_up_to_date() {
+    let abi = ContractAbi::from_json(TOKEN_ABI).unwrap();
+    let code = AbiCodeGenerator::new(abi, "TokenClient".to_string())
+        .with_target(CodegenTarget::Host)
+        .generate_rust_code();
+    assert_eq!(code, include_str!("common/token_client.rs"));
+}
+
+#[test]
+fn test_inputs_do_not_shadow_the_argument_buffer() {
+    let abi = ContractAbi::from_json(
+        r#"{
+  "version": "1.0",
+  "functions": [
+    {
+      "name": "call",
+      "selector": 1,
+      "inputs": [{ "name": "args", "type": "uint32" }],
+      "outputs": []
+    }
+  ],
+  "events": []
+}"#,
+    )
+    .unwrap();
+    let code = AbiCodeGenerator::new(abi, "Caller".to_string())
+        .with_target(CodegenTarget::Host)
+        .generate_rust_code();
+    assert!(code.contains("Encode::encode(&args, &mut __args);"));
+}
+
+#[test]
+fn test_methods_build_router_transactions() {
+    let client = TokenClient::new(TOKEN, ALICE);
+
+    let tx = client.transfer(3, BOB, 250).unwrap();
+    assert!(matches!(tx.tx_type, TransactionType::ProgramCall));
+    assert_eq!((tx.to, tx.from, tx.value, tx.nonce), (TOKEN, ALICE, 0, 3));
+    assert_eq!(tx.data, encode_router_calls(&[HostFuncCall::new(0x02, &(BOB, 250u32))]));
+
+    // The string is length-prefixed, like it is anywhere else
+    let tx = client.tag(4, 9, &[ALICE, BOB], "vip").unwrap();
+    assert_eq!(tx.data, encode_router_calls(&[HostFuncCall::new(0x07, &(9u64, vec![ALICE, BOB], "vip"))]));
+    assert_eq!(tx.nonce, 4);
+
+    assert_eq!(client.balance_of(5, ALICE).unwrap().data[..2], [0x05, 20]);
+
+    // The router length is one byte, so longer arguments build no call
+    assert!(client.tag(6, 9, &[ALICE; 13], "").is_none());
+    assert!(client.tag(6, 9, &[ALICE; 11], &"x".repeat(19)).is_some());
+}
+
+#[test]
+fn test_result_decoders() {
+    let result = Result::new_with_data(true, 0, &1_500u32.to_le_bytes());
+    assert_eq!(TokenClient::decode_balance_of_result(&result), Some(1_500));
+    assert_eq!(TokenClient::decode_balance_of_result(&Result::new(false, 1)), None);
+    // Leftover bytes mean the result is not the declared type
+    let result = Result::new_with_data(true, 0, &[0; 5]);
+    assert_eq!(TokenClient::decode_balance_of_result(&result), None);
+
+    let data = (7u64, "gold").to_encoded_bytes();
+    let result = Result::new_with_data(true, 0, &data);
+    assert_eq!(TokenClient::decode_tag_result(&result), Some((7, "gold".to_string())));
+}
+
+#[test]
+fn test_event_decoder() {
+    let event = event_bytes("Transfer", &(ALICE, BOB, 42u32));
+    assert_eq!(
+        TokenClientEvent::decode(&event),
+        Some(TokenClientEvent::Transfer { from: ALICE, to: BOB, amount: 42 })
+    );
+
+    let event = event_bytes("Tagged", &vec!["a", "bc"]);
+    assert_eq!(
+        TokenClientEvent::decode(&event),
+        Some(TokenClientEvent::Tagged { labels: vec!["a".to_string(), "bc".to_string()] })
+    );
+
+    assert_eq!(TokenClientEvent::decode(&event_bytes("Minted", &42u32)), None);
+    assert_eq!(TokenClientEvent::decode(&event_bytes("Transfer", &(ALICE, BOB))), None);
+    assert_eq!(TokenClientEvent::decode(&[0; 8]), None);
+}
@@ -0,0 +1,89 @@
+#[path = "common/asm.rs"]
+mod asm;
+#[path = "common/chain.rs"]
+mod chain;
+
+use asm::*;
+use chain::*;
+use state::State;
+use storage::StorageKey;
+use vm::sys_call::SYSCALL_STORAGE_SET;
+use vm::trap::VmTrap;
+
+/// Stores domain "D", key "k" = [1, 2, 3, 4], then either halts or traps.
+fn storage_writer(fail: bool) -> Vec<u8> {
+    let program = Program::new()
+        .data(0x10, b"D")
+        .data(0x20, b"k")
+        .data(0x30, &[1, 2, 3, 4])
+        .li(A1, 0x10).li(A2, 1)
+        .li(A3, 0x20).li(A4, 1)
+        .li(A5, 0x30).li(A6, 4)
+        .li(A7, SYSCALL_STORAGE_SET as i32)
+        .ecall();
+    if fail {
+        program.illegal().build()
+    } else {
+        program.succeed().ebreak().build()
+    }
+}
+
+#[test]
+fn test_successful_call_commits_storage() {
+    let mut avm = new_avm(1_000);
+    avm.run_tx(deploy(CONTRACT, storage_writer(false), 0));
+
+    let receipt = avm.run_tx(call(CONTRACT, vec![], 0, 1));
+    assert!(receipt.result.success);
+    assert!(receipt.trap.is_none());
+
+    let storage = &avm.state.get_account(&CONTRACT).unwrap().storage;
+    assert_eq!(storage.get(&StorageKey::new(b"D", b"k")), Some(&vec![1, 2, 3, 4]));
+    assert_eq!(avm.state.pending_changes(), 0);
+}
+
+#[test]
+fn test_failed_call_reverts_storage() {
+    let mut avm = new_avm(1_000);
+    avm.run_tx(deploy(CONTRACT, storage_writer(true), 0));
+
+    let receipt = avm.run_tx(call(CONTRACT, vec![], 0, 1));
+    assert!(!receipt.result.success);
+    assert!(matches!(receipt.trap, Some(VmTrap::IllegalInstruction { .. })));
+
+    assert!(avm.state.get_account(&CONTRACT).unwrap().storage.is_empty());
+    assert_eq!(avm.state.pending_changes(), 0);
+}
+
+#[test]
+fn test_failed_transfer_moves_no_funds() {
+    let mut avm = new_avm(1_000);
+
+    let receipt = avm.run_tx(transfer(BOB, ALICE, 10, 0));
+    assert!(!receipt.result.success);
+    // Only the sender's nonce survives the revert
+    let bob = avm.state.get_account(&BOB).unwrap();
+    assert_eq!((bob.balance, bob.nonce), (0, 1));
+    assert_eq!(avm.state.get_account(&ALICE).unwrap().balance, 1_000);
+
+    let receipt = avm.run_tx(transfer(ALICE, BOB, 10, 0));
+    assert!(receipt.result.success);
+    assert_eq!(avm.state.get_account(&BOB).unwrap().balance, 10);
+    assert_eq!(avm.state.get_account(&ALICE).unwrap().balance, 990);
+}
+
+#[test]
+fn test_state_revert_to_checkpoint() {
+    let mut state = State::new();
+    state.set_balance(&ALICE, 50);
+    let checkpoint = state.checkpoint();
+
+    state.set_balance(&ALICE, 10);
+    state.set_nonce(&ALICE, 3);
+    state.deploy_contract(BOB, vec![0x13]);
+    state.revert_to(checkpoint);
+
+    let alice = state.get_account(&ALICE).unwrap();
+    assert_eq!((alice.balance, alice.nonce), (50, 0));
+    assert!(state.get_account(&BOB).is_none());
+}
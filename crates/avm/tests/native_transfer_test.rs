@@ -0,0 +1,79 @@
+#[path = "common/asm.rs"]
+mod asm;
+#[path = "common/chain.rs"]
+mod chain;
+
+use asm::*;
+use avm::avm::AVM;
+use avm::global::Config;
+use chain::*;
+use types::address::Address;
+use vm::sys_call::SYSCALL_TRANSFER;
+use vm::trap::VmTrap;
+
+/// Sends 10 AM to BOB and reports the syscall status as the error code.
+fn payer() -> Vec<u8> {
+    Program::new()
+        .data(0x10, &BOB.0)
+        .li(A1, 0).li(A2, 0x10)
+        .li(A3, 10).li(A4, 0)
+        .li(A7, SYSCALL_TRANSFER as i32)
+        .ecall()
+        .sb(A0, 0, Config::RESULT_ADDR as i32 + 1)
+        .succeed()
+        .ebreak()
+        .build()
+}
+
+fn with_payer() -> AVM {
+    let mut avm = new_avm(1_000);
+    avm.run_tx(deploy(CONTRACT, payer(), 0));
+    avm
+}
+
+fn balance(avm: &AVM, addr: &Address) -> Option<u128> {
+    avm.state.get_account(addr).map(|a| a.balance)
+}
+
+#[test]
+fn test_contract_pays_from_its_own_balance() {
+    let mut avm = with_payer();
+    assert!(avm.run_tx(transfer(ALICE, CONTRACT, 50, 1)).result.success);
+
+    let receipt = avm.run_tx(call(CONTRACT, vec![], 0, 2));
+    assert!(receipt.result.success);
+    assert_eq!({ receipt.result.error_code }, 0);
+
+    assert_eq!(balance(&avm, &CONTRACT), Some(40));
+    assert_eq!(balance(&avm, &BOB), Some(10));
+    assert_eq!(balance(&avm, &ALICE), Some(950));
+}
+
+#[test]
+fn test_unfunded_contract_cannot_spend_callers_balance() {
+    let mut avm = with_payer();
+
+    let receipt = avm.run_tx(call(CONTRACT, vec![], 0, 1));
+    assert!(receipt.result.success);
+    assert_eq!({ receipt.result.error_code }, 1);
+
+    assert_eq!(balance(&avm, &ALICE), Some(1_000));
+    assert_eq!(balance(&avm, &CONTRACT), Some(0));
+    assert_eq!(balance(&avm, &BOB), None);
+}
+
+#[test]
+fn test_transfers_and_deployments_pay_flat_gas() {
+    let mut avm = new_avm(1_000);
+    assert_eq!(avm.run_tx(deploy(CONTRACT, payer(), 0)).gas_used, Config::NATIVE_TX_GAS);
+    assert_eq!(avm.run_tx(transfer(ALICE, BOB, 10, 1)).gas_used, Config::NATIVE_TX_GAS);
+
+    // Without the gas nothing moves, but the nonce is still used up
+    avm.set_gas_limit(Config::NATIVE_TX_GAS - 1);
+    let receipt = avm.run_tx(transfer(ALICE, BOB, 10, 2));
+    assert!(!receipt.result.success);
+    assert!(matches!(receipt.trap, Some(VmTrap::OutOfGas(_))));
+    assert_eq!(receipt.gas_used, Config::NATIVE_TX_GAS - 1);
+    assert_eq!(balance(&avm, &BOB), Some(10));
+    assert_eq!(nonce(&avm, &ALICE), 3);
+}
@@ -0,0 +1,96 @@
+#[path = "common/asm.rs"]
+mod asm;
+#[path = "common/chain.rs"]
+mod chain;
+
+use asm::*;
+use avm::avm::AVM;
+use chain::*;
+use storage::StorageKey;
+use types::address::Address;
+use vm::sys_call::{SYSCALL_CALL_PROGRAM, SYSCALL_STORAGE_SET};
+
+const CALLER: Address = Address([0xaa; 20]);
+const CALLEE: Address = Address([0xbb; 20]);
+
+/// Stores the caller address it was invoked with under domain "B", key "k",
+/// then either halts or traps.
+fn callee(fail: bool) -> Vec<u8> {
+    let program = Program::new()
+        .data(0x10, b"B")
+        .data(0x20, b"k")
+        .addi(A5, A1, 0).li(A6, 20)
+        .li(A1, 0x10).li(A2, 1)
+        .li(A3, 0x20).li(A4, 1)
+        .li(A7, SYSCALL_STORAGE_SET as i32)
+        .ecall();
+    if fail {
+        program.illegal().build()
+    } else {
+        program.succeed().ebreak().build()
+    }
+}
+
+/// Calls `CALLEE`, then stores the first 5 bytes of the returned Result
+/// (success flag + error code) under domain "A", key "k".
+fn caller(fail: bool) -> Vec<u8> {
+    let program = Program::new()
+        .data(0x10, &CALLEE.0)
+        .data(0x50, b"A")
+        .data(0x60, b"k")
+        .li(A1, 0x10)
+        .li(A2, 0x70).li(A3, 0)
+        .li(A4, 0).li(A5, 0)
+        .li(A7, SYSCALL_CALL_PROGRAM as i32)
+        .ecall()
+        .addi(A5, A0, 0).li(A6, 5)
+        .li(A1, 0x50).li(A2, 1)
+        .li(A3, 0x60).li(A4, 1)
+        .li(A7, SYSCALL_STORAGE_SET as i32)
+        .ecall();
+    if fail {
+        program.illegal().build()
+    } else {
+        program.succeed().ebreak().build()
+    }
+}
+
+fn run(caller_fails: bool, callee_fails: bool) -> AVM {
+    let mut avm = new_avm(0);
+    avm.run_tx(deploy(CALLER, caller(caller_fails), 0));
+    avm.run_tx(deploy(CALLEE, callee(callee_fails), 1));
+    avm.run_tx(call(CALLER, vec![], 0, 2));
+    avm
+}
+
+fn stored(avm: &AVM, addr: &Address, domain: &[u8]) -> Option<Vec<u8>> {
+    avm.state.get_account(addr).unwrap().storage.get(&StorageKey::new(domain, b"k")).cloned()
+}
+
+#[test]
+fn test_failed_callee_is_reverted_alone() {
+    let avm = run(false, true);
+
+    // The callee's write is gone, the caller's survives
+    assert_eq!(stored(&avm, &CALLEE, b"B"), None);
+    // and the caller saw a failed Result carrying the trap's error code
+    assert_eq!(stored(&avm, &CALLER, b"A"), Some(vec![0, 0x02, 0x00, 0xff, 0xff]));
+}
+
+#[test]
+fn test_successful_callee_is_kept() {
+    let avm = run(false, false);
+
+    // The callee was told the calling contract, not the transaction sender, called it
+    assert_eq!(stored(&avm, &CALLEE, b"B"), Some(CALLER.0.to_vec()));
+    assert_eq!(stored(&avm, &CALLER, b"A"), Some(vec![1, 0, 0, 0, 0]));
+}
+
+#[test]
+fn test_failed_caller_reverts_successful_callee() {
+    let avm = run(true, false);
+
+    assert_eq!(stored(&avm, &CALLEE, b"B"), None);
+    assert_eq!(stored(&avm, &CALLER, b"A"), None);
+    assert_eq!(avm.state.pending_changes(), 0);
+}
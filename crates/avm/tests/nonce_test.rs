@@ -0,0 +1,43 @@
+#[path = "common/chain.rs"]
+mod chain;
+
+use avm::global::Config;
+use chain::*;
+
+#[test]
+fn test_replayed_transaction_is_rejected() {
+    let mut avm = new_avm(100);
+    assert!(avm.run_tx(transfer(ALICE, BOB, 10, 0)).result.success);
+    assert_eq!(nonce(&avm, &ALICE), 1);
+
+    let receipt = avm.run_tx(transfer(ALICE, BOB, 10, 0));
+    assert!(!receipt.result.success);
+    assert_eq!({ receipt.result.error_code }, Config::INVALID_NONCE);
+
+    // Nothing moved and the nonce was not consumed
+    assert_eq!(avm.state.get_account(&BOB).unwrap().balance, 10);
+    assert_eq!(nonce(&avm, &ALICE), 1);
+}
+
+#[test]
+fn test_future_nonce_is_rejected() {
+    let mut avm = new_avm(100);
+
+    let receipt = avm.run_tx(transfer(ALICE, BOB, 10, 5));
+    assert_eq!({ receipt.result.error_code }, Config::INVALID_NONCE);
+    assert_eq!(nonce(&avm, &ALICE), 0);
+    assert!(avm.state.get_account(&BOB).is_none());
+}
+
+#[test]
+fn test_failed_execution_still_consumes_nonce() {
+    let mut avm = new_avm(100);
+
+    let receipt = avm.run_tx(transfer(ALICE, BOB, 1_000, 0));
+    assert!(!receipt.result.success);
+    assert_ne!({ receipt.result.error_code }, Config::INVALID_NONCE);
+    assert_eq!(nonce(&avm, &ALICE), 1);
+
+    assert!(avm.run_tx(transfer(ALICE, BOB, 10, 1)).result.success);
+    assert_eq!(nonce(&avm, &ALICE), 2);
+}
@@ -0,0 +1,132 @@
+#[path = "common/chain.rs"]
+mod chain;
+
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::fs;
+use std::rc::Rc;
+
+use avm::avm::AVM;
+use chain::*;
+use state::{Account, State};
+use storage::{FileStorage, Storage, StorageBackend, StorageKey};
+
+#[test]
+fn test_state_survives_restart() {
+    let path = std::env::temp_dir().join(format!("avm-state-{}.log", std::process::id()));
+    let _ = fs::remove_file(&path);
+
+    let root = {
+        let mut state = State::new_from_storage(Rc::new(FileStorage::open(&path).unwrap())).unwrap();
+        state.set_balance(&ALICE, 1_000);
+        state.deploy_contract(CONTRACT, vec![0x13, 0, 0, 0]);
+        let mut storage = BTreeMap::new();
+        storage.insert(StorageKey::new(b"P", b"k"), vec![1, 2, 3]);
+        state.set_storage(&CONTRACT, storage);
+        state.commit();
+
+        let mut avm = AVM::new(4, 64 * 1024, state);
+        assert!(avm.run_tx(transfer(ALICE, BOB, 10, 0)).result.success);
+        avm.state.flush().unwrap();
+        avm.state.state_root()
+    };
+
+    let state = State::new_from_storage(Rc::new(FileStorage::open(&path).unwrap())).unwrap();
+    let alice = state.get_account(&ALICE).unwrap();
+    assert_eq!((alice.balance, alice.nonce), (990, 1));
+    assert_eq!(state.get_account(&BOB).unwrap().balance, 10);
+    let contract = state.get_account(&CONTRACT).unwrap();
+    assert!(contract.is_contract);
+    assert_eq!(contract.storage.get(&StorageKey::new(b"P", b"k")), Some(&vec![1, 2, 3]));
+    assert_eq!(state.state_root(), root);
+    assert_eq!(state.pending_changes(), 0);
+
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_reverted_changes_are_not_persisted() {
+    let backend = Rc::new(Storage::new());
+    let mut state = State::new_from_storage(backend.clone()).unwrap();
+    state.set_balance(&ALICE, 5);
+    state.commit();
+
+    let checkpoint = state.checkpoint();
+    state.set_balance(&ALICE, 0);
+    state.set_balance(&BOB, 5);
+    state.revert_to(checkpoint);
+    state.commit();
+
+    assert_eq!(backend.keys_in_domain(b"account").len(), 1);
+    let reloaded = State::new_from_storage(backend).unwrap();
+    assert_eq!(reloaded.get_account(&ALICE).unwrap().balance, 5);
+    assert!(reloaded.get_account(&BOB).is_none());
+}
+
+#[test]
+fn test_corrupt_account_record_is_an_error() {
+    let backend = Rc::new(Storage::new());
+    backend.write(&StorageKey::new(b"account", &ALICE.0), vec![0xff]);
+    assert!(State::new_from_storage(backend.clone()).is_err());
+
+    let account = Account { nonce: 1, balance: 2, code: vec![3], is_contract: true, storage: BTreeMap::new() };
+    backend.write(&StorageKey::new(b"account", &ALICE.0), account.encode());
+    assert!(State::new_from_storage(backend).is_ok());
+}
+
+/// Records the key of every write on its way to an in-memory store.
+#[derive(Debug, Default)]
+struct RecordingBackend {
+    inner: Storage,
+    writes: RefCell<Vec<StorageKey>>,
+}
+
+impl StorageBackend for RecordingBackend {
+    fn read(&self, key: &StorageKey) -> Option<Vec<u8>> {
+        self.inner.read(key)
+    }
+
+    fn write(&self, key: &StorageKey, value: Vec<u8>) {
+        self.writes.borrow_mut().push(key.clone());
+        self.inner.write(key, value);
+    }
+
+    fn delete(&self, key: &StorageKey) {
+        self.inner.delete(key);
+    }
+
+    fn keys_in_domain(&self, domain: &[u8]) -> Vec<Vec<u8>> {
+        self.inner.keys_in_domain(domain)
+    }
+}
+
+#[test]
+fn test_commit_writes_only_changed_slots() {
+    let backend = Rc::new(RecordingBackend::default());
+    let mut state = State::new_from_storage(backend.clone()).unwrap();
+    state.deploy_contract(CONTRACT, vec![0x13, 0, 0, 0]);
+    let mut storage = BTreeMap::new();
+    for i in 0..10u8 {
+        storage.insert(StorageKey::new(b"P", &[i]), vec![i]);
+    }
+    state.set_storage(&CONTRACT, storage.clone());
+    state.commit();
+    // Account fields, code and ten slots
+    assert_eq!(backend.writes.take().len(), 12);
+
+    storage.insert(StorageKey::new(b"P", &[3]), vec![30]);
+    storage.remove(&StorageKey::new(b"P", &[4]));
+    state.set_storage(&CONTRACT, storage);
+    state.commit();
+    // Account fields and the one changed slot; the removed one is deleted
+    let writes = backend.writes.take();
+    assert_eq!(writes.len(), 2);
+    assert!(writes.iter().all(|key| key.domain != b"code"));
+
+    let reloaded = State::new_from_storage(backend).unwrap();
+    let contract = reloaded.get_account(&CONTRACT).unwrap();
+    assert_eq!(contract.code, vec![0x13, 0, 0, 0]);
+    assert_eq!(contract.storage.len(), 9);
+    assert_eq!(contract.storage.get(&StorageKey::new(b"P", &[3])), Some(&vec![30]));
+    assert_eq!(reloaded.state_root(), state.state_root());
+}
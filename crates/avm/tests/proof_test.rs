@@ -0,0 +1,109 @@
+use std::collections::BTreeMap;
+
+use state::{verify_account_proof, verify_storage_proof, State};
+use storage::StorageKey;
+use types::address::Address;
+
+const ALICE: Address = Address([0xa1; 20]);
+const BOB: Address = Address([0xb0; 20]);
+const TOKEN: Address = Address([0xc0; 20]);
+
+/// A token contract whose `Balances` map holds 500 for ALICE.
+fn token_state() -> State {
+    let mut state = State::new();
+    state.set_balance(&ALICE, 1_000);
+    state.set_nonce(&BOB, 3);
+    state.deploy_contract(TOKEN, vec![0x13, 0, 0, 0]);
+
+    let mut storage = BTreeMap::new();
+    storage.insert(StorageKey::new(b"Balances", &ALICE.0), 500u32.to_le_bytes().to_vec());
+    storage.insert(StorageKey::new(b"P", b"total"), 500u32.to_le_bytes().to_vec());
+    state.set_storage(&TOKEN, storage);
+    state.commit();
+    state
+}
+
+#[test]
+fn test_account_proofs() {
+    let state = token_state();
+    let root = state.state_root();
+
+    let proof = state.prove_account(&ALICE).unwrap();
+    assert_eq!(proof.leaf.as_ref().map(|l| l.balance), Some(1_000));
+    assert!(verify_account_proof(&root, &proof));
+
+    // Claiming a different balance breaks the proof
+    let mut forged = proof.clone();
+    forged.leaf.as_mut().unwrap().balance = 2_000;
+    assert!(!verify_account_proof(&root, &forged));
+
+    // Absence is provable too, but an existing account cannot be denied
+    let missing = state.prove_account(&Address([0xee; 20])).unwrap();
+    assert!(missing.leaf.is_none());
+    assert!(verify_account_proof(&root, &missing));
+    let mut denied = proof;
+    denied.leaf = None;
+    assert!(!verify_account_proof(&root, &denied));
+}
+
+#[test]
+fn test_storage_proofs() {
+    let state = token_state();
+    let root = state.state_root();
+
+    let proof = state.prove_storage(&TOKEN, b"Balances", &ALICE.0).unwrap();
+    assert_eq!(proof.value, Some(500u32.to_le_bytes().to_vec()));
+    assert!(verify_storage_proof(&root, &proof));
+
+    let mut forged = proof.clone();
+    forged.value = Some(1_000_000u32.to_le_bytes().to_vec());
+    assert!(!verify_storage_proof(&root, &forged));
+
+    let mut moved = proof;
+    moved.slot.key = BOB.0.to_vec();
+    assert!(!verify_storage_proof(&root, &moved));
+
+    let empty = state.prove_storage(&TOKEN, b"Balances", &BOB.0).unwrap();
+    assert!(empty.value.is_none());
+    assert!(verify_storage_proof(&root, &empty));
+
+    let no_account = state.prove_storage(&Address([0xee; 20]), b"Balances", &ALICE.0).unwrap();
+    assert!(verify_storage_proof(&root, &no_account));
+}
+
+#[test]
+fn test_proofs_are_bound_to_a_root() {
+    let mut state = token_state();
+    let old_root = state.state_root();
+    let old_proof = state.prove_storage(&TOKEN, b"Balances", &ALICE.0).unwrap();
+
+    let mut storage = state.get_account(&TOKEN).unwrap().storage.clone();
+    storage.insert(StorageKey::new(b"Balances", &ALICE.0), 400u32.to_le_bytes().to_vec());
+    state.set_storage(&TOKEN, storage);
+    state.commit();
+    let new_root = state.state_root();
+
+    assert!(verify_storage_proof(&old_root, &old_proof));
+    assert!(!verify_storage_proof(&new_root, &old_proof));
+    assert!(verify_storage_proof(&new_root, &state.prove_storage(&TOKEN, b"Balances", &ALICE.0).unwrap()));
+
+    // Only non-empty siblings are carried
+    let proof = state.prove_account(&ALICE).unwrap();
+    assert!(proof.proof.siblings.len() < 16);
+}
+
+#[test]
+fn test_uncommitted_accounts_cannot_be_proven() {
+    let mut state = token_state();
+    state.set_balance(&ALICE, 1);
+
+    // The live balance is not in the committed tree yet
+    assert!(state.prove_account(&ALICE).is_none());
+    assert!(state.prove_storage(&ALICE, b"Balances", &BOB.0).is_none());
+    // Untouched accounts still prove against the committed root
+    assert!(verify_account_proof(&state.state_root(), &state.prove_account(&BOB).unwrap()));
+
+    state.commit();
+    let proof = state.prove_account(&ALICE).unwrap();
+    assert_eq!(proof.leaf.map(|l| l.balance), Some(1));
+}
@@ -0,0 +1,56 @@
+#[path = "common/asm.rs"]
+mod asm;
+#[path = "common/chain.rs"]
+mod chain;
+
+use asm::*;
+use avm::avm::AVM;
+use avm::global::Config;
+use chain::*;
+use state::State;
+use vm::trap::VmTrap;
+
+#[test]
+fn test_call_to_non_contract_fails_the_transaction() {
+    let mut avm = new_avm(5);
+    avm.run_tx(transfer(ALICE, BOB, 5, 0));
+
+    let receipt = avm.run_tx(call(BOB, vec![], 0, 1));
+    assert!(!receipt.result.success);
+    assert_eq!({ receipt.result.error_code }, Config::NOT_A_CONTRACT);
+    assert_eq!(nonce(&avm, &ALICE), 2);
+}
+
+#[test]
+fn test_oversized_input_fails_the_transaction() {
+    let mut avm = new_avm(0);
+    avm.run_tx(deploy(CONTRACT, Program::new().succeed().ebreak().build(), 0));
+
+    let input = vec![0; Config::MAX_INPUT_LEN + 1];
+    let receipt = avm.run_tx(call(CONTRACT, input, 0, 1));
+    assert_eq!({ receipt.result.error_code }, Config::INPUT_TOO_LARGE);
+    assert_eq!(nonce(&avm, &ALICE), 2);
+}
+
+#[test]
+fn test_deploying_over_an_existing_account_fails() {
+    let mut avm = new_avm(0);
+    let code = Program::new().succeed().ebreak().build();
+    assert!(avm.run_tx(deploy(CONTRACT, code.clone(), 0)).result.success);
+
+    let receipt = avm.run_tx(deploy(CONTRACT, vec![0xff; 8], 1));
+    assert!(!receipt.result.success);
+    assert_eq!(avm.state.get_account(&CONTRACT).unwrap().code, code);
+}
+
+#[test]
+fn test_code_larger_than_the_page_traps() {
+    // Pages of 2KB cannot hold a 4KB contract
+    let mut avm = AVM::new(4, 2048, State::new());
+    avm.run_tx(deploy(CONTRACT, vec![0; 4096], 0));
+
+    let receipt = avm.run_tx(call(CONTRACT, vec![], 0, 1));
+    assert!(!receipt.result.success);
+    assert!(matches!(receipt.trap, Some(VmTrap::OutOfBounds { .. })));
+    assert_eq!({ receipt.result.error_code }, receipt.trap.unwrap().error_code());
+}
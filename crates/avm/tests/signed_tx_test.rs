@@ -0,0 +1,64 @@
+#[path = "common/chain.rs"]
+mod chain;
+
+use avm::global::Config;
+use avm::signed_transaction::{address_from_key, SignedTransaction};
+use chain::*;
+use k256::ecdsa::Signature;
+
+#[test]
+fn test_signed_transaction_recovers_sender() {
+    let alice = address_from_key(key(1).verifying_key());
+    let signed = SignedTransaction::sign(transfer(alice, BOB, 10, 0), &key(1));
+    assert_eq!(signed.recover_sender(), Some(alice));
+
+    let mut avm = funded_avm(&alice, 100);
+    assert!(avm.run_signed_tx(signed).result.success);
+    assert_eq!(avm.state.get_account(&BOB).unwrap().balance, 10);
+}
+
+#[test]
+fn test_tampered_transaction_is_rejected() {
+    let alice = address_from_key(key(1).verifying_key());
+    let mut signed = SignedTransaction::sign(transfer(alice, BOB, 10, 0), &key(1));
+    signed.tx.value = 90;
+
+    let mut avm = funded_avm(&alice, 100);
+    let receipt = avm.run_signed_tx(signed);
+    assert_eq!({ receipt.result.error_code }, Config::INVALID_SIGNATURE);
+    assert!(avm.state.get_account(&BOB).is_none());
+    assert_eq!(avm.state.get_account(&alice).unwrap().nonce, 0);
+}
+
+#[test]
+fn test_impersonation_is_rejected() {
+    let alice = address_from_key(key(1).verifying_key());
+    // Mallory signs a transaction claiming to come from Alice
+    let signed = SignedTransaction::sign(transfer(alice, BOB, 10, 0), &key(2));
+    assert!(!signed.verify());
+
+    let mut avm = funded_avm(&alice, 100);
+    let receipt = avm.run_signed_tx(signed);
+    assert!(!receipt.result.success);
+    assert_eq!({ receipt.result.error_code }, Config::INVALID_SIGNATURE);
+    assert_eq!(avm.state.get_account(&alice).unwrap().balance, 100);
+}
+
+#[test]
+fn test_high_s_signature_is_rejected() {
+    let alice = address_from_key(key(1).verifying_key());
+    let signed = SignedTransaction::sign(transfer(alice, BOB, 10, 0), &key(1));
+
+    // (r, n - s) with the other recovery id recovers the same key
+    let (r, s) = Signature::from_slice(&signed.signature[..64]).unwrap().split_scalars();
+    let malleated = Signature::from_scalars(r, -s).unwrap();
+    let mut twin = signed.clone();
+    twin.signature[..64].copy_from_slice(&malleated.to_bytes());
+    twin.signature[64] ^= 1;
+    assert_ne!(twin.hash(), signed.hash());
+    assert_eq!(twin.recover_sender(), None);
+
+    let mut avm = funded_avm(&alice, 100);
+    assert_eq!({ avm.run_signed_tx(twin).result.error_code }, Config::INVALID_SIGNATURE);
+    assert!(avm.run_signed_tx(signed).result.success);
+}
@@ -0,0 +1,96 @@
+use std::collections::BTreeMap;
+
+use state::{empty_hashes, State};
+use storage::StorageKey;
+use types::address::Address;
+
+const ALICE: Address = Address([0xa1; 20]);
+const BOB: Address = Address([0xb0; 20]);
+const CONTRACT: Address = Address([0xc0; 20]);
+
+fn storage(entries: &[(&[u8], &[u8])]) -> BTreeMap<StorageKey, Vec<u8>> {
+    entries.iter().map(|(k, v)| (StorageKey::new(b"D", k), v.to_vec())).collect()
+}
+
+#[test]
+fn test_empty_state_root() {
+    let mut state = State::new();
+    assert_eq!(state.state_root(), empty_hashes()[0]);
+    state.commit();
+    assert_eq!(state.state_root(), empty_hashes()[0]);
+}
+
+#[test]
+fn test_root_is_independent_of_history() {
+    // Built step by step over several commits, with overwrites
+    let mut a = State::new();
+    a.set_balance(&ALICE, 5);
+    a.deploy_contract(CONTRACT, vec![0x13, 0, 0, 0]);
+    a.commit();
+    a.set_storage(&CONTRACT, storage(&[(b"1", &[1]), (b"2", &[2])]));
+    a.set_balance(&ALICE, 100);
+    a.commit();
+    a.set_storage(&CONTRACT, storage(&[(b"2", &[2]), (b"3", &[3])]));
+    a.set_nonce(&BOB, 1);
+    a.commit();
+
+    // Built in one go, in a different order
+    let mut b = State::new();
+    b.set_nonce(&BOB, 1);
+    b.set_storage(&CONTRACT, storage(&[(b"3", &[3]), (b"2", &[2])]));
+    b.deploy_contract(CONTRACT, vec![0x13, 0, 0, 0]);
+    b.set_balance(&ALICE, 100);
+    b.commit();
+
+    assert_eq!(a.state_root(), b.state_root());
+    assert_eq!(a.storage_root(&CONTRACT), b.storage_root(&CONTRACT));
+}
+
+#[test]
+fn test_root_tracks_every_field() {
+    let mut state = State::new();
+    state.set_balance(&ALICE, 1);
+    state.set_storage(&CONTRACT, storage(&[(b"1", &[1])]));
+    state.commit();
+    let base = state.state_root();
+
+    let mut roots = vec![base];
+    let mut check = |state: &mut State| {
+        state.commit();
+        let root = state.state_root();
+        assert!(!roots.contains(&root), "change did not move the root");
+        roots.push(root);
+    };
+
+    state.set_balance(&ALICE, 2);
+    check(&mut state);
+    state.set_nonce(&ALICE, 1);
+    check(&mut state);
+    state.deploy_contract(ALICE, vec![1]);
+    check(&mut state);
+    state.set_storage(&CONTRACT, storage(&[(b"1", &[2])]));
+    check(&mut state);
+    state.set_storage(&CONTRACT, storage(&[]));
+    check(&mut state);
+    state.get_account_mut(&BOB);
+    check(&mut state);
+}
+
+#[test]
+fn test_reverted_and_uncommitted_changes_leave_root_alone() {
+    let mut state = State::new();
+    state.set_balance(&ALICE, 10);
+    state.set_storage(&CONTRACT, storage(&[(b"1", &[1])]));
+    state.commit();
+    let root = state.state_root();
+
+    let checkpoint = state.checkpoint();
+    state.set_balance(&ALICE, 0);
+    state.set_balance(&BOB, 10);
+    state.set_storage(&CONTRACT, storage(&[(b"1", &[9]), (b"2", &[2])]));
+    assert_eq!(state.state_root(), root);
+
+    state.revert_to(checkpoint);
+    state.commit();
+    assert_eq!(state.state_root(), root);
+}
@@ -0,0 +1,65 @@
+#[path = "common/asm.rs"]
+mod asm;
+#[path = "common/chain.rs"]
+mod chain;
+
+use std::collections::BTreeMap;
+
+use asm::*;
+use chain::*;
+use state::State;
+use storage::StorageKey;
+use vm::sys_call::{SYSCALL_STORAGE_DELETE, SYSCALL_STORAGE_SET};
+
+/// Sets D:"k", deletes it twice and stores both delete results under D:"r".
+fn deleter() -> Vec<u8> {
+    let set = |program: Program, key: i32, value: i32, len: i32| {
+        program
+            .li(A1, 0x10).li(A2, 1)
+            .li(A3, key).li(A4, 1)
+            .li(A5, value).li(A6, len)
+            .li(A7, SYSCALL_STORAGE_SET as i32)
+            .ecall()
+    };
+    let delete = |program: Program, result: i32| {
+        program
+            .li(A1, 0x10).li(A2, 1)
+            .li(A3, 0x20).li(A4, 1)
+            .li(A7, SYSCALL_STORAGE_DELETE as i32)
+            .ecall()
+            .li(T0, result)
+            .sb(A0, T0, 0)
+    };
+
+    let program = Program::new()
+        .data(0x10, b"D")
+        .data(0x20, b"k")
+        .data(0x28, b"r")
+        .data(0x30, &[1, 2, 3, 4]);
+    let program = set(program, 0x20, 0x30, 4);
+    let program = delete(program, 0x40);
+    let program = delete(program, 0x41);
+    set(program, 0x28, 0x40, 2).succeed().ebreak().build()
+}
+
+#[test]
+fn test_delete_removes_slot() {
+    let mut avm = new_avm(1_000);
+
+    avm.run_tx(deploy(CONTRACT, deleter(), 0));
+    let receipt = avm.run_tx(call(CONTRACT, vec![], 0, 1));
+    assert!(receipt.result.success);
+
+    // Only the first delete found something
+    let storage = &avm.state.get_account(&CONTRACT).unwrap().storage;
+    assert_eq!(storage.len(), 1);
+    assert_eq!(storage.get(&StorageKey::new(b"D", b"r")), Some(&vec![1, 0]));
+
+    // A deleted slot leaves no trace in the storage root
+    let mut expected = State::new();
+    let mut slots = BTreeMap::new();
+    slots.insert(StorageKey::new(b"D", b"r"), vec![1, 0]);
+    expected.set_storage(&CONTRACT, slots);
+    expected.commit();
+    assert_eq!(avm.state.storage_root(&CONTRACT), expected.storage_root(&CONTRACT));
+}
@@ -1,3 +1,6 @@
+use types::result::{RESULT_DATA_SIZE, RESULT_SIZE};
+use types::{Address, Decode};
+
 #[derive(Debug, Clone)]
 pub struct EventAbi {
     pub name: String,
@@ -36,15 +39,36 @@ pub struct FunctionParam {
     pub kind: ParamType,
 }
 
+/// The type of a function argument, return value or event field.
+///
+/// ENCODING: every value is laid out the same way whether it is a call
+/// argument, result data or an event field, and matches `StorageValue`:
+/// - `Address`: 20 raw bytes
+/// - `Uint(bits)` / `Int(bits)`: `bits / 8` bytes, little-endian (two's
+///   complement for `Int`); `bits` is a multiple of 8 up to 256
+/// - `Bool`: one byte, 0 or 1
+/// - `FixedBytes(n)`: `n` raw bytes
+/// - `Bytes` / `String`: a u32 length, then the bytes (UTF-8 for `String`)
+/// - `FixedArray(T, n)`: `n` values of `T` back to back
+/// - `Array(T)`: a u32 count, then that many values of `T`
+/// - `Tuple(..)`: each member in order
+/// - `Result`: the VM's fixed-size `Result` struct, only used as an output
+///
+/// JSON names follow Solidity: `uint64`, `int8`, `bytes32`, `uint32[]`,
+/// `address[4]`, `(address,uint64)`.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ParamType {
     Address,
     Uint(usize), // bits
+    Int(usize),  // bits
     Bool,
     Bytes,
     String,
+    FixedBytes(usize),
+    Array(Box<ParamType>),
+    FixedArray(Box<ParamType>, usize),
+    Tuple(Vec<ParamType>),
     Result, // Represents the Result struct with success, error_code, data_len, and data fields
-    // Extend as needed
 }
 
 impl ParamType {
@@ -53,29 +77,216 @@ impl ParamType {
         match self {
             ParamType::Address => "address".to_string(),
             ParamType::Uint(bits) => format!("uint{}", bits),
+            ParamType::Int(bits) => format!("int{}", bits),
             ParamType::Bool => "bool".to_string(),
             ParamType::Bytes => "bytes".to_string(),
             ParamType::String => "string".to_string(),
+            ParamType::FixedBytes(len) => format!("bytes{}", len),
+            ParamType::Array(elem) => format!("{}[]", elem.to_json_string()),
+            ParamType::FixedArray(elem, len) => format!("{}[{}]", elem.to_json_string(), len),
+            ParamType::Tuple(members) => {
+                let members: Vec<String> = members.iter().map(|m| m.to_json_string()).collect();
+                format!("({})", members.join(","))
+            }
             ParamType::Result => "result".to_string(),
         }
     }
-    
-    /// Parse from JSON string representation
-    pub fn from_json_string(s: &str) -> Self {
+
+    /// Parse from JSON string representation, rejecting unknown types
+    pub fn from_json_string(s: &str) -> Result<Self, String> {
+        let s = s.trim();
+        let unknown = || format!("unknown ABI type `{}`", s);
+
+        // Arrays: the suffix is always the last bracket pair
+        if let Some(prefix) = s.strip_suffix(']') {
+            let open = prefix.rfind('[').ok_or_else(unknown)?;
+            let elem = Box::new(Self::from_json_string(&prefix[..open])?);
+            let len = &prefix[open + 1..];
+            if len.is_empty() {
+                return Ok(ParamType::Array(elem));
+            }
+            let len = Self::parse_size(len).ok_or_else(unknown)?;
+            return Ok(ParamType::FixedArray(elem, len));
+        }
+
+        if let Some(inner) = s.strip_prefix('(').and_then(|rest| rest.strip_suffix(')')) {
+            let mut members = Vec::new();
+            if !inner.trim().is_empty() {
+                let mut depth = 0;
+                let mut start = 0;
+                for (i, ch) in inner.char_indices() {
+                    match ch {
+                        '(' => depth += 1,
+                        ')' => depth -= 1,
+                        ',' if depth == 0 => {
+                            members.push(Self::from_json_string(&inner[start..i])?);
+                            start = i + 1;
+                        }
+                        _ => {}
+                    }
+                }
+                members.push(Self::from_json_string(&inner[start..])?);
+            }
+            return Ok(ParamType::Tuple(members));
+        }
+
         match s {
-            "address" => ParamType::Address,
-            "bool" => ParamType::Bool,
-            "bytes" => ParamType::Bytes,
-            "string" => ParamType::String,
-            "result" => ParamType::Result,
-            s if s.starts_with("uint") => {
-                let bits_str = &s[4..];
-                let bits = bits_str.parse::<usize>().unwrap_or(32);
-                ParamType::Uint(bits)
+            "address" => Ok(ParamType::Address),
+            "bool" => Ok(ParamType::Bool),
+            "bytes" => Ok(ParamType::Bytes),
+            "string" => Ok(ParamType::String),
+            "result" => Ok(ParamType::Result),
+            _ => {
+                if let Some(bits) = s.strip_prefix("uint") {
+                    Self::parse_bits(bits).map(ParamType::Uint).ok_or_else(unknown)
+                } else if let Some(bits) = s.strip_prefix("int") {
+                    Self::parse_bits(bits).map(ParamType::Int).ok_or_else(unknown)
+                } else if let Some(len) = s.strip_prefix("bytes") {
+                    Self::parse_size(len).map(ParamType::FixedBytes).ok_or_else(unknown)
+                } else {
+                    Err(unknown())
+                }
+            }
+        }
+    }
+
+    /// A plain decimal number, without sign or leading zeros
+    fn parse_size(s: &str) -> Option<usize> {
+        if s.is_empty() || !s.bytes().all(|b| b.is_ascii_digit()) || (s.len() > 1 && s.starts_with('0')) {
+            return None;
+        }
+        s.parse().ok().filter(|&n| n > 0)
+    }
+
+    fn parse_bits(s: &str) -> Option<usize> {
+        Self::parse_size(s).filter(|bits| bits % 8 == 0 && *bits <= 256)
+    }
+
+    /// Encoded size of the type, or `None` if it is variable-length
+    pub fn fixed_size(&self) -> Option<usize> {
+        match self {
+            ParamType::Address => Some(20),
+            ParamType::Uint(bits) | ParamType::Int(bits) => Some(bits / 8),
+            ParamType::Bool => Some(1),
+            ParamType::FixedBytes(len) => Some(*len),
+            ParamType::FixedArray(elem, len) => elem.fixed_size().map(|size| size * len),
+            ParamType::Tuple(members) => members.iter().map(|m| m.fixed_size()).sum(),
+            ParamType::Result => Some(RESULT_SIZE),
+            ParamType::Bytes | ParamType::String | ParamType::Array(_) => None,
+        }
+    }
+
+    /// Decodes one value from the front of `data` and formats it for display.
+    ///
+    /// Types with a Rust counterpart are read with its `types::Decode`
+    /// implementation, the same code guest programs use. Integers wider than
+    /// 128 bits print in decimal when they fit in 128 bits and as big-endian
+    /// hex otherwise.
+    pub fn format_value(&self, data: &mut &[u8]) -> Result<String, String> {
+        let decode_err = || format!("cannot decode {}", self.to_json_string());
+        match self {
+            ParamType::Address => {
+                let address: Address = Decode::decode(data).ok_or_else(decode_err)?;
+                Ok(format!("0x{}", to_hex(&address.0)))
+            }
+            ParamType::Uint(8) => u8::decode(data).map(|v| v.to_string()).ok_or_else(decode_err),
+            ParamType::Uint(16) => u16::decode(data).map(|v| v.to_string()).ok_or_else(decode_err),
+            ParamType::Uint(32) => u32::decode(data).map(|v| v.to_string()).ok_or_else(decode_err),
+            ParamType::Uint(64) => u64::decode(data).map(|v| v.to_string()).ok_or_else(decode_err),
+            ParamType::Uint(128) => u128::decode(data).map(|v| v.to_string()).ok_or_else(decode_err),
+            ParamType::Int(8) => i8::decode(data).map(|v| v.to_string()).ok_or_else(decode_err),
+            ParamType::Int(16) => i16::decode(data).map(|v| v.to_string()).ok_or_else(decode_err),
+            ParamType::Int(32) => i32::decode(data).map(|v| v.to_string()).ok_or_else(decode_err),
+            ParamType::Int(64) => i64::decode(data).map(|v| v.to_string()).ok_or_else(decode_err),
+            ParamType::Int(128) => i128::decode(data).map(|v| v.to_string()).ok_or_else(decode_err),
+            ParamType::Uint(bits) => Ok(format_uint(take(data, bits / 8).ok_or_else(decode_err)?)),
+            ParamType::Int(bits) => Ok(format_int(take(data, bits / 8).ok_or_else(decode_err)?)),
+            ParamType::Bool => bool::decode(data).map(|v| v.to_string()).ok_or_else(decode_err),
+            ParamType::FixedBytes(len) => Ok(format!("0x{}", to_hex(take(data, *len).ok_or_else(decode_err)?))),
+            ParamType::Bytes => {
+                let bytes: &[u8] = Decode::decode(data).ok_or_else(decode_err)?;
+                Ok(format!("0x{}", to_hex(bytes)))
+            }
+            ParamType::String => {
+                let string: &str = Decode::decode(data).ok_or_else(decode_err)?;
+                Ok(format!("{:?}", string))
+            }
+            ParamType::Array(elem) => {
+                let len: u32 = Decode::decode(data).ok_or_else(decode_err)?;
+                Self::format_list(std::iter::repeat_n(elem.as_ref(), len as usize), data, '[', ']')
+            }
+            ParamType::FixedArray(elem, len) => {
+                Self::format_list(std::iter::repeat_n(elem.as_ref(), *len), data, '[', ']')
+            }
+            ParamType::Tuple(members) => Self::format_list(members.iter(), data, '(', ')'),
+            ParamType::Result => {
+                let bytes = take(data, RESULT_SIZE).ok_or_else(decode_err)?;
+                let error_code = u32::from_le_bytes(bytes[1..5].try_into().unwrap());
+                let data_len = (u32::from_le_bytes(bytes[5..9].try_into().unwrap()) as usize).min(RESULT_DATA_SIZE);
+                Ok(format!(
+                    "Result {{ success: {}, error_code: {}, data: 0x{} }}",
+                    bytes[0] != 0,
+                    error_code,
+                    to_hex(&bytes[9..9 + data_len])
+                ))
             }
-            _ => ParamType::Bytes, // Default fallback
         }
     }
+
+    fn format_list<'a>(
+        types: impl Iterator<Item = &'a ParamType>,
+        data: &mut &[u8],
+        open: char,
+        close: char,
+    ) -> Result<String, String> {
+        let mut items = Vec::new();
+        for ty in types {
+            items.push(ty.format_value(data)?);
+        }
+        Ok(format!("{}{}{}", open, items.join(", "), close))
+    }
+}
+
+/// Splits `len` bytes off the front of `data`
+fn take<'a>(data: &mut &'a [u8], len: usize) -> Option<&'a [u8]> {
+    if data.len() < len {
+        return None;
+    }
+    let (head, rest) = data.split_at(len);
+    *data = rest;
+    Some(head)
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Formats a little-endian unsigned integer of any width
+fn format_uint(le: &[u8]) -> String {
+    if le.iter().skip(16).all(|&b| b == 0) {
+        let mut buf = [0u8; 16];
+        let len = le.len().min(16);
+        buf[..len].copy_from_slice(&le[..len]);
+        format!("{}", u128::from_le_bytes(buf))
+    } else {
+        let be: Vec<u8> = le.iter().rev().copied().collect();
+        format!("0x{}", to_hex(&be))
+    }
+}
+
+/// Formats a little-endian two's complement integer of any width
+fn format_int(le: &[u8]) -> String {
+    let negative = le.last().is_some_and(|b| b & 0x80 != 0);
+    let fill = if negative { 0xff } else { 0 };
+    if le.len() <= 16 || le.iter().skip(15).all(|&b| b == fill) {
+        let mut buf = [fill; 16];
+        let len = le.len().min(16);
+        buf[..len].copy_from_slice(&le[..len]);
+        format!("{}", i128::from_le_bytes(buf))
+    } else {
+        let be: Vec<u8> = le.iter().rev().copied().collect();
+        format!("0x{}", to_hex(&be))
+    }
 }
 
 /// Complete ABI for a smart contract
@@ -216,9 +427,7 @@ impl ContractAbi {
                             let obj_content = &functions_content[pos + obj_start + 1..];
                             if let Some(obj_end) = Self::find_matching_bracket(obj_content, '{', '}') {
                                 let function_json = &obj_content[..obj_end];
-                                if let Ok(function) = Self::parse_function(function_json) {
-                                    abi.functions.push(function);
-                                }
+                                abi.functions.push(Self::parse_function(function_json)?);
                                 pos = pos + obj_start + obj_end + 2;
                             } else {
                                 break;
@@ -245,9 +454,7 @@ impl ContractAbi {
                             let obj_content = &events_content[pos + obj_start + 1..];
                             if let Some(obj_end) = Self::find_matching_bracket(obj_content, '{', '}') {
                                 let event_json = &obj_content[..obj_end];
-                                if let Ok(event) = Self::parse_event(event_json) {
-                                    abi.events.push(event);
-                                }
+                                abi.events.push(Self::parse_event(event_json)?);
                                 pos = pos + obj_start + obj_end + 2;
                             } else {
                                 break;
@@ -321,7 +528,7 @@ impl ContractAbi {
                 let inputs_section = &json[inputs_start + array_start + 1..];
                 if let Some(array_end) = Self::find_matching_bracket(inputs_section, '[', ']') {
                     let inputs_content = &inputs_section[..array_end];
-                    function.inputs = Self::parse_function_params(inputs_content);
+                    function.inputs = Self::parse_function_params(inputs_content)?;
                 }
             }
         }
@@ -332,7 +539,7 @@ impl ContractAbi {
                 let outputs_section = &json[outputs_start + array_start + 1..];
                 if let Some(array_end) = Self::find_matching_bracket(outputs_section, '[', ']') {
                     let outputs_content = &outputs_section[..array_end];
-                    function.outputs = Self::parse_param_types(outputs_content);
+                    function.outputs = Self::parse_param_types(outputs_content)?;
                 }
             }
         }
@@ -357,7 +564,7 @@ impl ContractAbi {
                 let inputs_section = &json[inputs_start + array_start + 1..];
                 if let Some(array_end) = Self::find_matching_bracket(inputs_section, '[', ']') {
                     let inputs_content = &inputs_section[..array_end];
-                    event.inputs = Self::parse_event_params(inputs_content);
+                    event.inputs = Self::parse_event_params(inputs_content)?;
                 }
             }
         }
@@ -365,7 +572,7 @@ impl ContractAbi {
         Ok(event)
     }
     
-    fn parse_function_params(json: &str) -> Vec<FunctionParam> {
+    fn parse_function_params(json: &str) -> Result<Vec<FunctionParam>, String> {
         let mut params = Vec::new();
         let mut pos = 0;
         
@@ -377,17 +584,13 @@ impl ContractAbi {
                     
                     let mut param = FunctionParam {
                         name: String::new(),
-                        kind: ParamType::Bytes,
+                        kind: Self::parse_type_field(param_json)?,
                     };
                     
                     if let Some(name) = Self::extract_string_value(param_json, "name") {
                         param.name = name;
                     }
                     
-                    if let Some(type_str) = Self::extract_string_value(param_json, "type") {
-                        param.kind = ParamType::from_json_string(&type_str);
-                    }
-                    
                     params.push(param);
                     pos = pos + obj_start + obj_end + 2;
                 } else {
@@ -398,10 +601,10 @@ impl ContractAbi {
             }
         }
         
-        params
+        Ok(params)
     }
     
-    fn parse_event_params(json: &str) -> Vec<EventParam> {
+    fn parse_event_params(json: &str) -> Result<Vec<EventParam>, String> {
         let mut params = Vec::new();
         let mut pos = 0;
         
@@ -413,7 +616,7 @@ impl ContractAbi {
                     
                     let mut param = EventParam {
                         name: String::new(),
-                        kind: ParamType::Bytes,
+                        kind: Self::parse_type_field(param_json)?,
                         indexed: false,
                     };
                     
@@ -421,10 +624,6 @@ impl ContractAbi {
                         param.name = name;
                     }
                     
-                    if let Some(type_str) = Self::extract_string_value(param_json, "type") {
-                        param.kind = ParamType::from_json_string(&type_str);
-                    }
-                    
                     if let Some(indexed) = Self::extract_bool_value(param_json, "indexed") {
                         param.indexed = indexed;
                     }
@@ -439,10 +638,10 @@ impl ContractAbi {
             }
         }
         
-        params
+        Ok(params)
     }
     
-    fn parse_param_types(json: &str) -> Vec<ParamType> {
+    fn parse_param_types(json: &str) -> Result<Vec<ParamType>, String> {
         let mut types = Vec::new();
         let mut pos = 0;
         
@@ -452,9 +651,7 @@ impl ContractAbi {
                 if let Some(obj_end) = Self::find_matching_bracket(obj_content, '{', '}') {
                     let type_json = &obj_content[..obj_end];
                     
-                    if let Some(type_str) = Self::extract_string_value(type_json, "type") {
-                        types.push(ParamType::from_json_string(&type_str));
-                    }
+                    types.push(Self::parse_type_field(type_json)?);
                     
                     pos = pos + obj_start + obj_end + 2;
                 } else {
@@ -465,9 +662,16 @@ impl ContractAbi {
             }
         }
         
-        types
+        Ok(types)
     }
     
+    /// The required `"type"` of a parameter object
+    fn parse_type_field(json: &str) -> Result<ParamType, String> {
+        let type_str = Self::extract_string_value(json, "type")
+            .ok_or_else(|| format!("missing \"type\" in {{{}}}", json.trim()))?;
+        ParamType::from_json_string(&type_str)
+    }
+
     fn extract_string_value(json: &str, key: &str) -> Option<String> {
         let search_key = format!("\"{}\"", key);
         if let Some(key_pos) = json.find(&search_key) {
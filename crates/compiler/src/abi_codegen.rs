@@ -1,11 +1,25 @@
 use std::fs;
 use std::path::Path;
-use crate::abi::{ContractAbi, FunctionAbi, FunctionParam, ParamType};
+use crate::abi::{ContractAbi, EventAbi, FunctionAbi, FunctionParam, ParamType};
+
+/// Where the generated client code runs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CodegenTarget {
+    /// A `no_std` client for programs that call the contract through the
+    /// `call` syscall; meant to be `include!`d next to the imports it uses
+    #[default]
+    Guest,
+    /// A `std` module for off-chain code: one method per function building a
+    /// ready-to-submit `avm::transaction::Transaction`, plus decoders for
+    /// function results and events
+    Host,
+}
 
 /// ABI Code Generator that creates Rust client code from ABI definitions
 pub struct AbiCodeGenerator {
     abi: ContractAbi,
     contract_name: String,
+    target: CodegenTarget,
 }
 
 impl AbiCodeGenerator {
@@ -14,11 +28,22 @@ impl AbiCodeGenerator {
         Self {
             abi,
             contract_name,
+            target: CodegenTarget::Guest,
         }
     }
 
+    /// Selects the kind of client to generate (guest by default)
+    pub fn with_target(mut self, target: CodegenTarget) -> Self {
+        self.target = target;
+        self
+    }
+
     /// Generate Rust client code from the ABI
     pub fn generate_rust_code(&self) -> String {
+        if self.target == CodegenTarget::Host {
+            return self.generate_host_code();
+        }
+
         let mut code = String::new();
         
         // Add header - no attributes since this will be included
@@ -29,6 +54,7 @@ impl AbiCodeGenerator {
         code.push_str("// Note: This code assumes the following imports in the parent file:\n");
         code.push_str("// use program::types::address::Address;\n");
         code.push_str("// use program::types::result::Result;\n");
+        code.push_str("// use program::types::Encode;\n");
         code.push_str("// use program::call::call;\n\n");
         
         // Generate contract struct
@@ -51,11 +77,10 @@ impl AbiCodeGenerator {
             code.push_str("    /// Call the main entry point directly (no routing)\n");
             code.push_str("    pub fn call_main(\n");
             code.push_str("        &self,\n");
-            code.push_str("        caller: &Address,\n");
             code.push_str("        data: &[u8],\n");
             code.push_str("    ) -> Option<Result> {\n");
             code.push_str("        // Direct call without router encoding\n");
-            code.push_str("        call(caller, &self.address, data)\n");
+            code.push_str("        call(&self.address, data)\n");
             code.push_str("    }\n\n");
         } else {
             // Generate methods for each function
@@ -70,6 +95,231 @@ impl AbiCodeGenerator {
         code
     }
     
+    /// Generate a host client module from the ABI
+    ///
+    /// EDUCATIONAL: off-chain code does not make calls itself, it submits
+    /// transactions. Each method therefore returns a `Transaction` with the
+    /// router-encoded call as its data, sent from the client's `from` account
+    /// with no value attached (set `value` on the result for payable calls).
+    /// Routed methods return `None` when the encoded arguments do not fit
+    /// the router's one-byte length, like the guest client does.
+    /// Arguments are written with `types::Encode` and results and events are
+    /// read with `types::Decode`, the same code the guest uses on the other
+    /// end.
+    fn generate_host_code(&self) -> String {
+        let name = &self.contract_name;
+        let mut code = String::new();
+
+        code.push_str("// Auto-generated ABI host client code\n");
+        code.push_str("// DO NOT EDIT - Generated from ABI\n\n");
+        code.push_str("use avm::transaction::{Transaction, TransactionType};\n");
+        code.push_str("use types::address::Address;\n");
+        code.push_str("use types::result::Result;\n");
+        code.push_str("use types::{Decode, Encode};\n\n");
+
+        code.push_str(&format!("/// Builds transactions calling the {} contract\n", name));
+        code.push_str(&format!("pub struct {} {{\n", name));
+        code.push_str("    /// Address of the deployed contract\n");
+        code.push_str("    pub address: Address,\n");
+        code.push_str("    /// Account the transactions are sent from\n");
+        code.push_str("    pub from: Address,\n");
+        code.push_str("}\n\n");
+
+        code.push_str(&format!("impl {} {{\n", name));
+        code.push_str("    /// Create a new contract client\n");
+        code.push_str("    pub fn new(address: Address, from: Address) -> Self {\n");
+        code.push_str("        Self { address, from }\n");
+        code.push_str("    }\n\n");
+
+        if self.abi.functions.is_empty() {
+            code.push_str("    /// Call the main entry point directly (no routing)\n");
+            code.push_str("    pub fn call_main(&self, nonce: u64, data: Vec<u8>) -> Transaction {\n");
+            code.push_str("        self.transaction(nonce, data)\n");
+            code.push_str("    }\n\n");
+        }
+        for function in &self.abi.functions {
+            code.push_str(&self.generate_host_method(function));
+            code.push('\n');
+            if let Some(decoder) = self.generate_result_decoder(function) {
+                code.push_str(&decoder);
+                code.push('\n');
+            }
+        }
+
+        code.push_str("    fn routed_call(&self, nonce: u64, selector: u8, args: Vec<u8>) -> Option<Transaction> {\n");
+        code.push_str("        let len = u8::try_from(args.len()).ok()?;\n");
+        code.push_str("        let mut data = vec![selector, len];\n");
+        code.push_str("        data.extend(args);\n");
+        code.push_str("        Some(self.transaction(nonce, data))\n");
+        code.push_str("    }\n\n");
+        code.push_str("    fn transaction(&self, nonce: u64, data: Vec<u8>) -> Transaction {\n");
+        code.push_str("        Transaction {\n");
+        code.push_str("            tx_type: TransactionType::ProgramCall,\n");
+        code.push_str("            to: self.address,\n");
+        code.push_str("            from: self.from,\n");
+        code.push_str("            data,\n");
+        code.push_str("            value: 0,\n");
+        code.push_str("            nonce,\n");
+        code.push_str("        }\n");
+        code.push_str("    }\n");
+        code.push_str("}\n");
+
+        if !self.abi.events.is_empty() {
+            code.push('\n');
+            code.push_str(&self.generate_event_decoder(&self.abi.events));
+        }
+
+        code
+    }
+
+    /// Generate a host method building the transaction for one function
+    fn generate_host_method(&self, function: &FunctionAbi) -> String {
+        let mut method = String::new();
+
+        if function.selector > 0 {
+            method.push_str(&format!(
+                "    /// Builds a call to `{}` (selector 0x{:02x}), or `None` if the\n",
+                function.name, function.selector
+            ));
+            method.push_str("    /// arguments are too long for the router\n");
+        } else {
+            method.push_str(&format!("    /// Builds a direct call to `{}` (no routing)\n", function.name));
+        }
+        method.push_str(&format!("    pub fn {}(\n", function.name));
+        method.push_str("        &self,\n");
+        method.push_str("        nonce: u64,\n");
+        for input in &function.inputs {
+            method.push_str(&format!(
+                "        {}: {},\n",
+                host_arg_name(&input.name),
+                self.param_type_to_rust(&input.kind)
+            ));
+        }
+        if function.selector > 0 {
+            method.push_str("    ) -> Option<Transaction> {\n");
+        } else {
+            method.push_str("    ) -> Transaction {\n");
+        }
+        // `__args` cannot be an ABI input name, so no input shadows it
+        method.push_str("        let mut __args = Vec::new();\n");
+        for input in &function.inputs {
+            let arg = host_arg_name(&input.name);
+            method.push_str(&format!("        Encode::encode(&{}, &mut __args);\n", arg));
+        }
+        if function.selector > 0 {
+            method.push_str(&format!("        self.routed_call(nonce, 0x{:02x}, __args)\n", function.selector));
+        } else {
+            method.push_str("        self.transaction(nonce, __args)\n");
+        }
+        method.push_str("    }\n");
+
+        method
+    }
+
+    /// Generate a decoder for the data a function returns, if it has one
+    ///
+    /// Functions without outputs or returning a raw `Result` have nothing
+    /// to decode beyond the receipt's result itself.
+    fn generate_result_decoder(&self, function: &FunctionAbi) -> Option<String> {
+        if function.outputs.is_empty() || function.outputs.contains(&ParamType::Result) {
+            return None;
+        }
+        let output = match function.outputs.as_slice() {
+            [single] => self.param_type_to_owned_rust(single),
+            outputs => {
+                let members: Vec<String> = outputs.iter().map(|o| self.param_type_to_owned_rust(o)).collect();
+                format!("({})", members.join(", "))
+            }
+        };
+
+        let mut decoder = String::new();
+        decoder.push_str(&format!(
+            "    /// Decodes the result of `{}`, or `None` if the call failed\n",
+            function.name
+        ));
+        decoder.push_str(&format!(
+            "    pub fn decode_{}_result(result: &Result) -> Option<{}> {{\n",
+            function.name, output
+        ));
+        decoder.push_str("        if !result.success {\n");
+        decoder.push_str("            return None;\n");
+        decoder.push_str("        }\n");
+        decoder.push_str("        let len = (result.data_len as usize).min(result.data.len());\n");
+        decoder.push_str("        Decode::decode_exact(&result.data[..len])\n");
+        decoder.push_str("    }\n");
+        Some(decoder)
+    }
+
+    /// Generate an enum of the contract's events with a decoder for receipt events
+    fn generate_event_decoder(&self, events: &[EventAbi]) -> String {
+        let enum_name = format!("{}Event", self.contract_name);
+        let mut code = String::new();
+
+        code.push_str(&format!("/// Events emitted by the {} contract\n", self.contract_name));
+        code.push_str("#[derive(Debug, Clone, PartialEq, Eq)]\n");
+        code.push_str(&format!("pub enum {} {{\n", enum_name));
+        for event in events {
+            code.push_str(&format!("    {} {{\n", event.name));
+            for input in &event.inputs {
+                code.push_str(&format!(
+                    "        {}: {},\n",
+                    input.name,
+                    self.param_type_to_owned_rust(&input.kind)
+                ));
+            }
+            code.push_str("    },\n");
+        }
+        code.push_str("}\n\n");
+
+        code.push_str(&format!("impl {} {{\n", enum_name));
+        code.push_str("    /// Decodes an event from a receipt, or `None` if it is not one of this\n");
+        code.push_str("    /// contract's events. The first 32 bytes hold the event name, the rest\n");
+        code.push_str("    /// its fields in order.\n");
+        code.push_str("    pub fn decode(event: &[u8]) -> Option<Self> {\n");
+        code.push_str("        if event.len() < 32 {\n");
+        code.push_str("            return None;\n");
+        code.push_str("        }\n");
+        code.push_str("        let (id, mut fields) = event.split_at(32);\n");
+        for event in events {
+            code.push_str(&format!("        if id == Self::id(\"{}\") {{\n", event.name));
+            code.push_str(&format!("            let decoded = Self::{} {{\n", event.name));
+            for input in &event.inputs {
+                code.push_str(&format!("                {}: Decode::decode(&mut fields)?,\n", input.name));
+            }
+            code.push_str("            };\n");
+            code.push_str("            return fields.is_empty().then_some(decoded);\n");
+            code.push_str("        }\n");
+        }
+        code.push_str("        None\n");
+        code.push_str("    }\n\n");
+        code.push_str("    /// The id `event!` gives an event: its name, zero-padded to 32 bytes\n");
+        code.push_str("    fn id(name: &str) -> [u8; 32] {\n");
+        code.push_str("        let mut id = [0u8; 32];\n");
+        code.push_str("        let len = name.len().min(32);\n");
+        code.push_str("        id[..len].copy_from_slice(&name.as_bytes()[..len]);\n");
+        code.push_str("        id\n");
+        code.push_str("    }\n");
+        code.push_str("}\n");
+
+        code
+    }
+
+    /// Convert ParamType to an owned Rust type string, for decoded values
+    fn param_type_to_owned_rust(&self, param_type: &ParamType) -> String {
+        match param_type {
+            ParamType::String => "String".to_string(),
+            ParamType::Bytes => "Vec<u8>".to_string(),
+            ParamType::Array(elem) => format!("Vec<{}>", self.param_type_to_owned_rust(elem)),
+            ParamType::FixedArray(elem, len) => format!("[{}; {}]", self.param_type_to_owned_rust(elem), len),
+            ParamType::Tuple(members) => {
+                let members: Vec<String> =
+                    members.iter().map(|m| format!("{}, ", self.param_type_to_owned_rust(m))).collect();
+                format!("({})", members.concat().trim_end())
+            }
+            other => self.param_type_to_rust(other),
+        }
+    }
+
     /// Generate a method for a single function
     fn generate_function_method(&self, function: &FunctionAbi) -> String {
         let mut method = String::new();
@@ -80,7 +330,6 @@ impl AbiCodeGenerator {
         // Generate method signature
         method.push_str(&format!("    pub fn {}(\n", function.name));
         method.push_str("        &self,\n");
-        method.push_str("        caller: &Address,\n");
         
         // Add function parameters
         for input in &function.inputs {
@@ -109,15 +358,15 @@ impl AbiCodeGenerator {
             method.push_str("        let data = &encoded[..offset];\n");
             method.push_str("        \n");
             method.push_str("        // Make the call\n");
-            method.push_str("        call(caller, &self.address, data)\n");
+            method.push_str("        call(&self.address, data)\n");
         } else {
             // Direct call without router encoding
             if function.inputs.len() == 1 && matches!(function.inputs[0].kind, ParamType::Bytes) {
                 method.push_str("        // Make the call with raw data\n");
-                method.push_str(&format!("        call(caller, &self.address, {})\n", function.inputs[0].name));
+                method.push_str(&format!("        call(&self.address, {})\n", function.inputs[0].name));
             } else {
                 method.push_str("        // Make the call\n");
-                method.push_str("        call(caller, &self.address, &[])\n");
+                method.push_str("        call(&self.address, &[])\n");
             }
         }
         
@@ -130,203 +379,45 @@ impl AbiCodeGenerator {
     fn param_type_to_rust(&self, param_type: &ParamType) -> String {
         match param_type {
             ParamType::Address => "Address".to_string(),
-            ParamType::Uint(8) => "u8".to_string(),
-            ParamType::Uint(16) => "u16".to_string(),
-            ParamType::Uint(32) => "u32".to_string(),
-            ParamType::Uint(64) => "u64".to_string(),
-            ParamType::Uint(128) => "u128".to_string(),
-            ParamType::Uint(256) => "[u8; 32]".to_string(), // 256-bit as byte array
+            ParamType::Uint(bits @ (8 | 16 | 32 | 64 | 128)) => format!("u{}", bits),
+            ParamType::Int(bits @ (8 | 16 | 32 | 64 | 128)) => format!("i{}", bits),
+            // Other widths (e.g. 256-bit) as little-endian byte arrays
+            ParamType::Uint(bits) | ParamType::Int(bits) => format!("[u8; {}]", bits / 8),
             ParamType::Bool => "bool".to_string(),
             ParamType::String => "&str".to_string(),
             ParamType::Bytes => "&[u8]".to_string(),
+            ParamType::FixedBytes(len) => format!("[u8; {}]", len),
+            ParamType::Array(elem) => format!("&[{}]", self.param_type_to_rust(elem)),
+            ParamType::FixedArray(elem, len) => format!("[{}; {}]", self.param_type_to_rust(elem), len),
+            ParamType::Tuple(members) => {
+                let members: Vec<String> = members.iter().map(|m| format!("{}, ", self.param_type_to_rust(m))).collect();
+                format!("({})", members.concat().trim_end())
+            }
             ParamType::Result => "Result".to_string(),
-            _ => "Vec<u8>".to_string(),
         }
     }
 
+    /// Generates code appending `input` to `buffer` in its ABI encoding.
+    ///
+    /// Values are written with `Encode`, so the argument's Rust type decides
+    /// the layout and a client generated from a stale ABI fails to compile
+    /// instead of sending mismatched bytes. `Bytes` and `String` carry their
+    /// length wherever they appear, so the contract reads them back with the
+    /// same `Decode`.
     fn encode_argument(&self, buffer: &str, offset_var: &str, input: &FunctionParam) -> String {
         let name = &input.name;
-        match input.kind {
-            ParamType::Address => {
-                format!(
-                    "        if {offset} + 20 > {buf}.len() {{ return None; }}\n\
-         {buf}[{offset}..{offset} + 20].copy_from_slice(&{name}.0);\n\
-         {offset} += 20;\n",
-                    buf = buffer,
-                    offset = offset_var,
-                    name = name,
-                )
-            }
-            ParamType::Uint(8) => {
-                format!(
-                    "        if {offset} >= {buf}.len() {{ return None; }}\n\
-         {buf}[{offset}] = {name};\n\
-         {offset} += 1;\n",
-                    buf = buffer,
-                    offset = offset_var,
-                    name = name,
-                )
-            }
-            ParamType::Uint(16) => {
-                format!(
-                    "        if {offset} + 2 > {buf}.len() {{ return None; }}\n\
-         {buf}[{offset}..{offset} + 2].copy_from_slice(&{name}.to_le_bytes());\n\
-         {offset} += 2;\n",
-                    buf = buffer,
-                    offset = offset_var,
-                    name = name,
-                )
-            }
-            ParamType::Uint(32) => {
-                format!(
-                    "        if {offset} + 4 > {buf}.len() {{ return None; }}\n\
-         {buf}[{offset}..{offset} + 4].copy_from_slice(&{name}.to_le_bytes());\n\
-         {offset} += 4;\n",
-                    buf = buffer,
-                    offset = offset_var,
-                    name = name,
-                )
-            }
-            ParamType::Uint(64) => {
-                format!(
-                    "        if {offset} + 8 > {buf}.len() {{ return None; }}\n\
-         {buf}[{offset}..{offset} + 8].copy_from_slice(&{name}.to_le_bytes());\n\
-         {offset} += 8;\n",
-                    buf = buffer,
-                    offset = offset_var,
-                    name = name,
-                )
-            }
-            ParamType::Uint(128) => {
-                format!(
-                    "        if {offset} + 16 > {buf}.len() {{ return None; }}\n\
-         {buf}[{offset}..{offset} + 16].copy_from_slice(&{name}.to_le_bytes());\n\
-         {offset} += 16;\n",
-                    buf = buffer,
-                    offset = offset_var,
-                    name = name,
-                )
-            }
-            ParamType::Uint(256) => {
-                format!(
-                    "        if {offset} + 32 > {buf}.len() {{ return None; }}\n\
-         {buf}[{offset}..{offset} + 32].copy_from_slice(&{name});\n\
-         {offset} += 32;\n",
-                    buf = buffer,
-                    offset = offset_var,
-                    name = name,
-                )
-            }
-            ParamType::Bool => {
-                format!(
-                    "        if {offset} >= {buf}.len() {{ return None; }}\n\
-         {buf}[{offset}] = if {name} {{ 1 }} else {{ 0 }};\n\
-         {offset} += 1;\n",
-                    buf = buffer,
-                    offset = offset_var,
-                    name = name,
-                )
-            }
-            ParamType::String => {
-                format!(
-                    "        let arg_{name} = {name}.as_bytes();\n\
-         if {offset} + arg_{name}.len() > {buf}.len() {{ return None; }}\n\
-         {buf}[{offset}..{offset} + arg_{name}.len()].copy_from_slice(arg_{name});\n\
-         {offset} += arg_{name}.len();\n",
-                    buf = buffer,
-                    offset = offset_var,
-                    name = name,
-                )
-            }
-            ParamType::Bytes => {
-                format!(
-                    "        let arg_{name} = {name};\n\
-         if {offset} + arg_{name}.len() > {buf}.len() {{ return None; }}\n\
-         {buf}[{offset}..{offset} + arg_{name}.len()].copy_from_slice(arg_{name});\n\
-         {offset} += arg_{name}.len();\n",
-                    buf = buffer,
-                    offset = offset_var,
-                    name = name,
-                )
-            }
-            _ => {
-                format!("        // TODO: encode argument `{}`\n", name)
-            }
-        }
-    }
-    
-    /// Generate code to encode an argument
-    fn generate_argument_encoding(&self, name: &str, param_type: &ParamType) -> String {
-        match param_type {
-            ParamType::Address => {
-                format!("        args.extend({}.0.to_vec());\n", name)
-            }
-            ParamType::Uint(8) => {
-                format!("        args.push({});\n", name)
-            }
-            ParamType::Uint(16) => {
-                format!("        args.extend({}.to_le_bytes());\n", name)
-            }
-            ParamType::Uint(32) => {
-                format!("        args.extend({}.to_le_bytes());\n", name)
-            }
-            ParamType::Uint(64) => {
-                format!("        args.extend({}.to_le_bytes());\n", name)
-            }
-            ParamType::Uint(128) => {
-                format!("        args.extend({}.to_le_bytes());\n", name)
-            }
-            ParamType::Bool => {
-                format!("        args.push(if {} {{ 1 }} else {{ 0 }});\n", name)
-            }
-            ParamType::String => {
-                format!("        args.extend({}.as_bytes());\n", name)
-            }
-            ParamType::Bytes => {
-                format!("        args.extend({});\n", name)
-            }
-            _ => {
-                format!("        // TODO: Encode {}\n", name)
-            }
-        }
-    }
-    
-    /// Generate code to encode an argument for direct calls
-    fn generate_argument_encoding_direct(&self, name: &str, param_type: &ParamType) -> String {
-        match param_type {
-            ParamType::Address => {
-                format!("        data.extend({}.0.to_vec());\n", name)
-            }
-            ParamType::Uint(8) => {
-                format!("        data.push({});\n", name)
-            }
-            ParamType::Uint(16) => {
-                format!("        data.extend({}.to_le_bytes());\n", name)
-            }
-            ParamType::Uint(32) => {
-                format!("        data.extend({}.to_le_bytes());\n", name)
-            }
-            ParamType::Uint(64) => {
-                format!("        data.extend({}.to_le_bytes());\n", name)
-            }
-            ParamType::Uint(128) => {
-                format!("        data.extend({}.to_le_bytes());\n", name)
-            }
-            ParamType::Bool => {
-                format!("        data.push(if {} {{ 1 }} else {{ 0 }});\n", name)
-            }
-            ParamType::String => {
-                format!("        data.extend({}.as_bytes());\n", name)
-            }
-            ParamType::Bytes => {
-                format!("        data.extend({});\n", name)
-            }
-            _ => {
-                format!("        // TODO: Encode {}\n", name)
-            }
-        }
+        let mut code = String::new();
+        code.push_str(&format!(
+            "        if {} + Encode::encoded_len(&{}) > {}.len() {{ return None; }}\n",
+            offset_var, name, buffer
+        ));
+        code.push_str(&format!(
+            "        Encode::encode_into(&{}, &mut {}, &mut {});\n",
+            name, buffer, offset_var
+        ));
+        code
     }
-    
+
     /// Generate Rust client code from an ABI file
     pub fn from_abi_file<P: AsRef<Path>>(abi_path: P, contract_name: String) -> std::io::Result<String> {
         let abi_json = fs::read_to_string(abi_path)?;
@@ -343,6 +434,15 @@ impl AbiCodeGenerator {
     }
 }
 
+/// Name of a host method argument; `nonce` is taken by the method itself
+fn host_arg_name(name: &str) -> String {
+    if name == "nonce" {
+        "nonce_arg".to_string()
+    } else {
+        name.to_string()
+    }
+}
+
 /// Generate client code for all example ABIs
 pub fn generate_all_client_code() -> std::io::Result<()> {
     let bin_dir = "crates/examples/bin";
@@ -1,499 +1,776 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
 use std::fs;
 use std::path::Path;
-use crate::abi::{ContractAbi, FunctionAbi, FunctionParam, EventAbi, EventParam, ParamType};
+
+use proc_macro2::Span;
+use syn::spanned::Spanned;
+use syn::visit::{self, Visit};
+use syn::{
+    Arm, Expr, ExprCall, ExprClosure, ExprMatch, ExprMethodCall, FnArg, Item, ItemFn, Lit, Local, Pat,
+    ReturnType, Type,
+};
+
+use crate::abi::{ContractAbi, EventAbi, FunctionAbi, FunctionParam, ParamType};
+use crate::contract_abi::{analyze_contract, event_abi, last_ident, rust_param_type};
 
 /// ABI Generator that analyzes Rust source code to extract function and event definitions
+///
+/// EDUCATIONAL PURPOSE: A program's ABI is implicit in its source: the
+/// selectors live in the dispatch `match`, the argument types in the
+/// signatures of the functions each arm calls, and the events in its
+/// `event!` declarations. The generator parses the file into a syntax tree
+/// and walks it the way a reader would:
+///
+/// 1. Start from the function named in `entrypoint!` (or any function that
+///    calls `route`) and find the `match` on the selector, following calls
+///    into helper functions if the entry delegates to one
+/// 2. Resolve each arm's pattern to a selector, whether it is a literal or
+///    a `const` declared in the file
+/// 3. Take the first function of the file the arm calls and read its typed
+///    signature. Arguments the VM supplies (`caller`, the program address)
+///    are left out, and a `DataParser` argument is expanded into the
+///    `parser.read_*()` calls made on it
+///
+/// Modules written with `#[contract]` are read with `analyze_contract`, the
+/// same code the macro builds its embedded ABI with, so the ABI of such a
+/// file is exactly the one in its ELF.
+///
+/// Anything the generator cannot make sense of is reported as an `AbiError`
+/// pointing at the offending file, line and column, rather than silently
+/// producing an incomplete ABI.
 pub struct AbiGenerator {
     source_code: String,
+    file_name: String,
     abi: ContractAbi,
 }
 
+/// A source construct the generator could not turn into ABI.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AbiError {
+    pub file: String,
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl fmt::Display for AbiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}:{}: {}", self.file, self.line, self.column, self.message)
+    }
+}
+
+impl std::error::Error for AbiError {}
+
 impl AbiGenerator {
     /// Create a new ABI generator from source code
     pub fn new(source_code: String) -> Self {
         Self {
             source_code,
+            file_name: "<source>".to_string(),
             abi: ContractAbi::new(),
         }
     }
 
+    /// Sets the file name used in error locations
+    pub fn with_file_name(mut self, file_name: impl Into<String>) -> Self {
+        self.file_name = file_name.into();
+        self
+    }
+
     /// Generate ABI from source code
-    pub fn generate(&mut self) -> ContractAbi {
-        self.extract_events();
-        self.extract_functions();
-        self.abi.clone()
-    }
-
-    /// Extract event definitions from source code
-    fn extract_events(&mut self) {
-        // Look for event! macro invocations
-        let lines: Vec<&str> = self.source_code.lines().collect();
-        
-        let mut i = 0;
-        while i < lines.len() {
-            let line = lines[i].trim();
-            if line.starts_with("event!(") {
-                if let Some((event, consumed_lines)) = self.parse_event_macro_multi_line(&lines, i) {
-                    self.abi.add_event(event);
-                    i += consumed_lines;
-                } else {
-                    i += 1;
-                }
-            } else {
-                i += 1;
+    pub fn generate(&mut self) -> Result<ContractAbi, AbiError> {
+        let file = syn::parse_file(&self.source_code)
+            .map_err(|e| error_at(&self.file_name, e.span(), e.to_string()))?;
+        let analyzer = Analyzer::new(&self.file_name, &file.items);
+
+        if let Some(abi) = analyzer.contract_abi()? {
+            for event in abi.events {
+                self.abi.add_event(event);
+            }
+            for function in abi.functions {
+                self.abi.add_function(function);
             }
+            return Ok(self.abi.clone());
         }
+
+        for event in analyzer.events()? {
+            self.abi.add_event(event);
+        }
+        if let Some(dispatch) = analyzer.find_dispatch()? {
+            for function in analyzer.dispatch_functions(&dispatch)? {
+                self.abi.add_function(function);
+            }
+        }
+        Ok(self.abi.clone())
+    }
+
+    /// Parses a function signature such as `fn transfer(to: Address, amount: u32) -> Result`.
+    ///
+    /// The selector is left at 0; an implicit `caller: Address` is dropped as
+    /// it is for routed functions.
+    pub fn parse_function_signature(&self, signature: &str) -> Result<FunctionAbi, AbiError> {
+        let sig: syn::Signature = syn::parse_str(signature)
+            .map_err(|e| error_at(&self.file_name, e.span(), e.to_string()))?;
+        let func = ItemFn {
+            attrs: Vec::new(),
+            vis: syn::Visibility::Inherited,
+            sig,
+            block: Box::new(syn::parse_quote!({})),
+        };
+        Analyzer::new(&self.file_name, &[]).function_abi(&func, None)
+    }
+
+    /// Parses a type such as `u32` or `&[u8]` into its ABI type
+    pub fn parse_param_type_from_str(&self, type_str: &str) -> Option<ParamType> {
+        syn::parse_str::<Type>(type_str).ok().as_ref().and_then(rust_param_type)
+    }
+
+    /// Generate ABI from a source file
+    pub fn from_file<P: AsRef<Path>>(path: P) -> std::io::Result<ContractAbi> {
+        let source_code = fs::read_to_string(&path)?;
+        let mut generator = AbiGenerator::new(source_code).with_file_name(path.as_ref().display().to_string());
+        generator
+            .generate()
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e.to_string()))
+    }
+
+    /// Write ABI to a JSON file
+    pub fn write_abi_to_file<P: AsRef<Path>>(abi: &ContractAbi, path: P) -> std::io::Result<()> {
+        let json = abi.to_json();
+        fs::write(path, json)
+    }
+}
+
+fn error_at(file: &str, span: Span, message: impl Into<String>) -> AbiError {
+    let start = span.start();
+    AbiError {
+        file: file.to_string(),
+        line: start.line,
+        column: start.column + 1,
+        message: message.into(),
+    }
+}
+
+fn is_data_parser(ty: &Type) -> bool {
+    match ty {
+        Type::Reference(reference) => is_data_parser(&reference.elem),
+        _ => last_ident(ty).as_deref() == Some("DataParser"),
+    }
+}
+
+fn type_name(ty: &Type) -> String {
+    quote::quote!(#ty).to_string()
+}
+
+/// Name of a plain identifier expression such as `caller`
+fn expr_ident(expr: &Expr) -> Option<String> {
+    match expr {
+        Expr::Path(path) if path.qself.is_none() => path.path.get_ident().map(|i| i.to_string()),
+        _ => None,
+    }
+}
+
+fn pat_ident(pat: &Pat) -> Option<String> {
+    match pat {
+        Pat::Ident(ident) => Some(ident.ident.to_string()),
+        Pat::Type(typed) => pat_ident(&typed.pat),
+        _ => None,
+    }
+}
+
+fn has_attr(attrs: &[syn::Attribute], name: &str) -> bool {
+    attrs
+        .iter()
+        .any(|attr| attr.path().segments.last().is_some_and(|s| s.ident == name))
+}
+
+fn is_macro(mac: &syn::Macro, name: &str) -> bool {
+    mac.path.segments.last().is_some_and(|s| s.ident == name)
+}
+
+/// A dispatch `match` and the names that hold VM-supplied addresses where it is
+struct Dispatch<'a> {
+    expr: &'a ExprMatch,
+    context: HashSet<String>,
+}
+
+/// Index over the items of one parsed source file.
+struct Analyzer<'a> {
+    file_name: &'a str,
+    items: Vec<&'a Item>,
+    fns: HashMap<String, &'a ItemFn>,
+    consts: HashMap<String, u128>,
+}
+
+impl<'a> Analyzer<'a> {
+    fn new(file_name: &'a str, items: &'a [Item]) -> Self {
+        let mut analyzer = Self {
+            file_name,
+            items: Vec::new(),
+            fns: HashMap::new(),
+            consts: HashMap::new(),
+        };
+        analyzer.index(items);
+        analyzer
     }
 
-    /// Parse an event! macro invocation that may span multiple lines
-    fn parse_event_macro_multi_line(&self, lines: &[&str], start_line: usize) -> Option<(EventAbi, usize)> {
-        let mut event_content = String::new();
-        let mut brace_count = 0;
-        let mut in_event = false;
-        let mut consumed_lines = 0;
-        
-        for i in start_line..lines.len() {
-            let line = lines[i].trim();
-            consumed_lines += 1;
-            
-            if !in_event {
-                if line.starts_with("event!(") {
-                    in_event = true;
-                    event_content.push_str(line);
-                    // Count opening braces
-                    brace_count += line.chars().filter(|&c| c == '{').count();
-                    brace_count -= line.chars().filter(|&c| c == '}').count();
+    /// Flattens inline modules so helpers can be found wherever they live.
+    fn index(&mut self, items: &'a [Item]) {
+        for item in items {
+            self.items.push(item);
+            match item {
+                Item::Fn(func) => {
+                    self.fns.insert(func.sig.ident.to_string(), func);
+                }
+                Item::Const(item_const) => {
+                    if let Expr::Lit(expr) = item_const.expr.as_ref() {
+                        if let Lit::Int(lit) = &expr.lit {
+                            if let Ok(value) = lit.base10_parse::<u128>() {
+                                self.consts.insert(item_const.ident.to_string(), value);
+                            }
+                        }
+                    }
                 }
-            } else {
-                event_content.push_str(line);
-                // Count braces
-                brace_count += line.chars().filter(|&c| c == '{').count();
-                brace_count -= line.chars().filter(|&c| c == '}').count();
-                
-                // If we've closed all braces, we're done
-                if brace_count == 0 && line.ends_with(");") {
-                    break;
+                Item::Mod(module) => {
+                    if let Some((_, content)) = &module.content {
+                        self.index(content);
+                    }
                 }
+                _ => {}
             }
         }
-        
-        if !in_event {
-            return None;
-        }
-        
-        self.parse_event_macro(&event_content).map(|event| (event, consumed_lines))
     }
 
-    /// Parse an event! macro invocation
-    fn parse_event_macro(&self, line: &str) -> Option<EventAbi> {
-        // Simple parsing for event! macro
-        // Example: event!(Minted { caller => Address, amount => u32, });
-        
-        let trimmed = line.trim();
-        if !trimmed.starts_with("event!(") || !trimmed.ends_with(");") {
-            return None;
+    fn error(&self, span: Span, message: impl Into<String>) -> AbiError {
+        error_at(self.file_name, span, message)
+    }
+
+    /// Collects every `event!` declaration
+    fn events(&self) -> Result<Vec<EventAbi>, AbiError> {
+        let mut events = Vec::new();
+        for item in &self.items {
+            if let Item::Macro(item) = item {
+                if is_macro(&item.mac, "event") {
+                    events.push(self.event(&item.mac)?);
+                }
+            }
         }
+        Ok(events)
+    }
 
-        // Extract the content between event!( and );
-        let content = &trimmed[7..trimmed.len()-2];
-        
-        // Find the event name (first word before {)
-        let brace_pos = content.find('{')?;
-        let event_name = content[..brace_pos].trim();
-        
-        // Extract field definitions
-        let fields_content = &content[brace_pos+1..];
-        let fields_content = fields_content.trim_end_matches('}');
-        
-        let mut inputs = Vec::new();
-        let field_lines: Vec<&str> = fields_content.split(',').collect();
-        
-        for field_line in field_lines {
-            let field_line = field_line.trim();
-            if field_line.is_empty() {
+    fn event(&self, mac: &syn::Macro) -> Result<EventAbi, AbiError> {
+        event_abi(mac).map_err(|e| self.error(e.span(), e.to_string()))
+    }
+
+    /// The ABI of the file's `#[contract]` modules, if it has any
+    fn contract_abi(&self) -> Result<Option<ContractAbi>, AbiError> {
+        let mut abi: Option<ContractAbi> = None;
+        for item in &self.items {
+            let Item::Mod(module) = item else { continue };
+            let Some((_, content)) = &module.content else { continue };
+            if !has_attr(&module.attrs, "contract") {
                 continue;
             }
-            
-            // Parse field: name => type
-            if let Some((name, param_type)) = self.parse_event_field(field_line) {
-                inputs.push(EventParam {
-                    name: name.to_string(),
-                    kind: param_type,
-                    indexed: false, // Default to false for now
-                });
+            let contract = analyze_contract(content).map_err(|e| self.error(e.span(), e.to_string()))?;
+            let abi = abi.get_or_insert_with(ContractAbi::new);
+            for event in contract.abi.events {
+                abi.add_event(event);
+            }
+            for (function, decl) in contract.abi.functions.into_iter().zip(&contract.functions) {
+                self.push_unique(&mut abi.functions, function, decl.ident.span())?;
             }
         }
-        
-        Some(EventAbi {
-            name: event_name.to_string(),
-            inputs,
-        })
+        Ok(abi)
     }
 
-    /// Parse an event field definition
-    fn parse_event_field<'a>(&self, field: &'a str) -> Option<(&'a str, ParamType)> {
-        // Format: field_name => type
-        let parts: Vec<&str> = field.split("=>").collect();
-        if parts.len() != 2 {
-            return None;
-        }
-        
-        let name = parts[0].trim();
-        let type_str = parts[1].trim();
-        
-        let param_type = self.parse_param_type(type_str)?;
-        Some((name, param_type))
-    }
-
-    /// Parse a parameter type string
-    fn parse_param_type(&self, type_str: &str) -> Option<ParamType> {
-        match type_str {
-            "Address" => Some(ParamType::Address),
-            "u32" => Some(ParamType::Uint(32)),
-            "u64" => Some(ParamType::Uint(64)),
-            "u8" => Some(ParamType::Uint(8)),
-            "bool" => Some(ParamType::Bool),
-            "String" => Some(ParamType::String),
-            "&[u8]" | "[u8]" => Some(ParamType::Bytes),
-            _ => None,
+    /// Finds the selector `match`, starting at the entrypoint
+    fn find_dispatch(&self) -> Result<Option<Dispatch<'a>>, AbiError> {
+        let roots: Vec<&'a ItemFn> = match self.entrypoint()? {
+            Some(entry) => vec![entry],
+            // No entrypoint! in this file: fall back to whatever calls the router
+            None => self.items.iter().filter_map(|item| match item {
+                Item::Fn(func) if calls_route(func) => Some(func),
+                _ => None,
+            }).collect(),
+        };
+
+        let mut visited = HashSet::new();
+        for root in roots {
+            if let Some(dispatch) = self.find_dispatch_from(root, &mut visited) {
+                return Ok(Some(dispatch));
+            }
         }
+        Ok(None)
     }
 
-    /// Extract function definitions from source code
-    fn extract_functions(&mut self) {
-        // Look for router patterns and function selectors
-        let lines: Vec<&str> = self.source_code.lines().collect();
-        
-        let functions = self.collect_functions_from_router(&lines);
-        
-        // Add all collected functions
-        for function in functions {
-            self.abi.add_function(function);
+    fn entrypoint(&self) -> Result<Option<&'a ItemFn>, AbiError> {
+        for item in &self.items {
+            let Item::Macro(item) = item else { continue };
+            if !is_macro(&item.mac, "entrypoint") {
+                continue;
+            }
+            let path: syn::Path = item
+                .mac
+                .parse_body()
+                .map_err(|e| self.error(e.span(), format!("malformed entrypoint!: {}", e)))?;
+            let name = path.segments.last().map(|s| s.ident.to_string()).unwrap_or_default();
+            return match self.fns.get(&name) {
+                Some(func) => Ok(Some(func)),
+                None => Err(self.error(
+                    path.span(),
+                    format!("entrypoint `{}` is not defined in this file", name),
+                )),
+            };
         }
+        Ok(None)
     }
 
-    /// Collect functions from router match patterns
-    fn collect_functions_from_router(&self, lines: &[&str]) -> Vec<FunctionAbi> {
-        let mut functions = vec![];
-        
-        for (i, line) in lines.iter().enumerate() {
-            let line = line.trim();
-            
-            // Look for selector patterns like "0x01 => {"
-            if line.starts_with("0x") && line.contains("=>") {
-                if let Some(selector) = self.parse_selector_from_line(line) {
-                    // Check if this line has a direct function call (e.g., "0x01 => compare(call.args),")
-                    if let Some(function_name) = self.extract_direct_function_call(line) {
-                        if let Some(function) = self.find_function_definition(lines, i, &function_name) {
-                            let mut function = function;
-                            function.selector = selector;
-                            functions.push(function);
-                        }
-                        continue;
-                    }
-                    
-                    // Check for multi-line pattern with braces
-                    if line.contains('{') {
-                        // Look for function calls in subsequent lines within braces
-                        let mut brace_count = 0;
-                        let mut in_braces = false;
-                        
-                        for j in i..lines.len() {
-                            let next_line = lines[j].trim();
-                            
-                            // Count braces to track scope
-                            for ch in next_line.chars() {
-                                match ch {
-                                    '{' => {
-                                        brace_count += 1;
-                                        in_braces = true;
-                                    }
-                                    '}' => {
-                                        brace_count -= 1;
-                                        if brace_count == 0 {
-                                            in_braces = false;
-                                        }
-                                    }
-                                    _ => {}
-                                }
-                            }
-                            
-                            if in_braces && brace_count > 0 {
-                                // Look for function calls in this line
-                                if let Some(function_name) = self.extract_function_call_from_line(next_line) {
-                                    if let Some(function) = self.find_function_definition(lines, j, &function_name) {
-                                        let mut function = function;
-                                        function.selector = selector;
-                                        functions.push(function);
-                                    }
-                                }
-                            }
-                            
-                            if !in_braces && brace_count == 0 {
-                                break;
-                            }
-                        }
+    /// Looks for the dispatch in `func`, then in the local functions it calls
+    fn find_dispatch_from(&self, func: &'a ItemFn, visited: &mut HashSet<String>) -> Option<Dispatch<'a>> {
+        if !visited.insert(func.sig.ident.to_string()) {
+            return None;
+        }
+
+        let mut finder = DispatchFinder {
+            analyzer: self,
+            closures: Vec::new(),
+            found: None,
+        };
+        finder.visit_block(&func.block);
+        if let Some(mut dispatch) = finder.found {
+            for input in &func.sig.inputs {
+                if let FnArg::Typed(typed) = input {
+                    if rust_param_type(&typed.ty) == Some(ParamType::Address) {
+                        dispatch.context.extend(pat_ident(&typed.pat));
                     }
                 }
             }
+            return Some(dispatch);
         }
-        
-        functions
+
+        let mut calls = LocalCalls { analyzer: self, calls: Vec::new() };
+        calls.visit_block(&func.block);
+        calls
+            .calls
+            .into_iter()
+            .find_map(|call| self.find_dispatch_from(self.fns[&call_name(call)?], visited))
     }
 
-    /// Parse a selector pattern like "0x01 => function_name(call.args)" or "0x01 => { function_name(caller, call.args); }"
-    fn parse_selector_pattern<'a>(&self, line: &'a str) -> Option<(u8, &'a str)> {
-        let parts: Vec<&str> = line.split("=>").collect();
-        if parts.len() != 2 {
-            return None;
+    /// A `match` on `<call>.selector`, or with arms that are selector literals or consts
+    fn is_dispatch(&self, expr: &ExprMatch) -> bool {
+        if let Expr::Field(field) = expr.expr.as_ref() {
+            if let syn::Member::Named(member) = &field.member {
+                if member == "selector" {
+                    return true;
+                }
+            }
         }
-        
-        let selector_str = parts[0].trim();
-        let function_part = parts[1].trim();
-        
-        // Extract selector
-        if !selector_str.starts_with("0x") {
-            return None;
+        expr.arms.iter().any(|arm| self.is_selector_pattern(&arm.pat))
+    }
+
+    fn is_selector_pattern(&self, pat: &Pat) -> bool {
+        match pat {
+            Pat::Lit(lit) => matches!(lit.lit, Lit::Int(_)),
+            Pat::Ident(ident) => self.consts.contains_key(&ident.ident.to_string()),
+            Pat::Path(path) => path
+                .path
+                .get_ident()
+                .is_some_and(|ident| self.consts.contains_key(&ident.to_string())),
+            Pat::Or(or) => or.cases.iter().any(|case| self.is_selector_pattern(case)),
+            _ => false,
         }
-        
-        let selector = u8::from_str_radix(&selector_str[2..], 16).ok()?;
-        
-        // Extract function name - handle both patterns:
-        // 1. "function_name(call.args)" 
-        // 2. "{ function_name(caller, call.args); }"
-        let function_name = if function_part.starts_with('{') {
-            // Pattern 2: extract from inside braces
-            let inner = function_part.trim_start_matches('{').trim_end_matches('}');
-            // Look for function call pattern: function_name(...)
-            if let Some(start) = inner.find('(') {
-                inner[..start].trim()
-            } else {
-                return None;
-            }
-        } else {
-            // Pattern 1: extract directly
-            if let Some(start) = function_part.find('(') {
-                function_part[..start].trim()
-            } else {
-                return None;
+    }
+
+    fn dispatch_functions(&self, dispatch: &Dispatch<'a>) -> Result<Vec<FunctionAbi>, AbiError> {
+        let mut functions = Vec::new();
+        for arm in &dispatch.expr.arms {
+            for selector in self.selectors(&arm.pat)? {
+                let mut function = self.arm_function(arm, &dispatch.context)?;
+                function.selector = selector;
+                self.push_unique(&mut functions, function, arm.pat.span())?;
             }
-        };
-        
-        // Skip empty function names
-        if function_name.is_empty() {
-            return None;
         }
-        
-        Some((selector, function_name))
-    }
-
-    /// Find function definition and create FunctionAbi
-    fn find_function_definition(&self, lines: &[&str], start_line: usize, function_name: &str) -> Option<FunctionAbi> {
-        // Look for function definition pattern: fn function_name(...)
-        for i in 0..lines.len() {
-            let trimmed = lines[i].trim();
-            if trimmed.starts_with("fn ") && trimmed.contains(function_name) {
-                // Extract function signature
-                if let Some(signature) = self.extract_function_signature(lines, i) {
-                    return Some(self.parse_function_signature(function_name, signature));
-                }
-            }
+        Ok(functions)
+    }
+
+    fn push_unique(&self, functions: &mut Vec<FunctionAbi>, function: FunctionAbi, span: Span) -> Result<(), AbiError> {
+        if let Some(previous) = functions.iter().find(|f| f.selector == function.selector) {
+            return Err(self.error(
+                span,
+                format!("selector {:#04x} is already used by `{}`", function.selector, previous.name),
+            ));
         }
-        
-        None
-    }
-    
-    pub fn extract_function_signature(&self, lines: &[&str], start_line: usize) -> Option<String> {
-        let mut signature = String::new();
-        
-        for i in start_line..lines.len() {
-            let line = lines[i].trim();
-            
-            if line.starts_with("fn ") {
-                // Start collecting the signature
-                signature.push_str(line);
-                
-                // Check if this line contains the opening brace
-                if line.contains('{') {
-                    // Remove everything from the opening brace onwards
-                    if let Some(brace_pos) = line.find('{') {
-                        signature = line[..brace_pos].trim().to_string();
-                    }
-                    return Some(signature);
+        functions.push(function);
+        Ok(())
+    }
+
+    /// Resolves an arm pattern to the selectors it matches; `_` matches none
+    fn selectors(&self, pat: &Pat) -> Result<Vec<u8>, AbiError> {
+        let (value, span) = match pat {
+            Pat::Wild(_) => return Ok(Vec::new()),
+            Pat::Or(or) => {
+                let mut selectors = Vec::new();
+                for case in &or.cases {
+                    selectors.extend(self.selectors(case)?);
                 }
-                
-                // Continue to next line if no opening brace
-                continue;
+                return Ok(selectors);
             }
-            
-            // If we're already collecting a signature, add this line
-            if !signature.is_empty() {
-                signature.push_str(line);
-                
-                // Check if this line contains the opening brace
-                if line.contains('{') {
-                    // Remove everything from the opening brace onwards
-                    if let Some(brace_pos) = line.find('{') {
-                        signature = signature[..signature.len() - (line.len() - brace_pos)].trim().to_string();
+            Pat::Lit(lit) => match &lit.lit {
+                Lit::Int(int) => (
+                    int.base10_parse::<u128>().map_err(|e| self.error(int.span(), e.to_string()))?,
+                    int.span(),
+                ),
+                other => return Err(self.error(other.span(), "selector must be an integer")),
+            },
+            Pat::Ident(ident) if ident.subpat.is_none() => {
+                let name = ident.ident.to_string();
+                match self.consts.get(&name) {
+                    Some(value) => (*value, ident.span()),
+                    // A lowercase name is a catch-all binding, like `_`
+                    None if name.starts_with(|c: char| c.is_lowercase() || c == '_') => return Ok(Vec::new()),
+                    None => {
+                        return Err(self.error(
+                            ident.span(),
+                            format!("cannot resolve selector `{}`: no integer const of that name in this file", name),
+                        ))
                     }
-                    return Some(signature);
                 }
             }
-        }
-        
-        None
-    }
-    
-    pub fn parse_function_signature(&self, function_name: &str, signature: String) -> FunctionAbi {
-        let mut inputs = vec![];
-        let mut outputs = vec![]; // Start with empty outputs for void functions
-        
-        // Parse function signature like: fn init(caller: Address, args: &[u8])
-        if let Some(params_start) = signature.find('(') {
-            if let Some(params_end) = signature.find(')') {
-                let params_str = &signature[params_start + 1..params_end];
-                
-                // Parse parameters
-                for param in params_str.split(',') {
-                    let param = param.trim();
-                    if !param.is_empty() {
-                        if let Some((name, param_type)) = self.parse_parameter(param) {
-                            // The VM passes `caller: Address` implicitly; keep it out of the ABI so generated
-                            // clients encode only the real, routed arguments.
-                            if name == "caller" && param_type == ParamType::Address {
-                                continue;
-                            }
-                            inputs.push(FunctionParam {
-                                name: name.to_string(),
-                                kind: param_type,
-                            });
-                        }
+            Pat::Path(path) => {
+                let name = type_name_of_path(&path.path);
+                match path.path.get_ident().and_then(|ident| self.consts.get(&ident.to_string())) {
+                    Some(value) => (*value, path.span()),
+                    None => {
+                        return Err(self.error(
+                            path.span(),
+                            format!("cannot resolve selector `{}`: no integer const of that name in this file", name),
+                        ))
                     }
                 }
             }
-        }
-        
-        // Parse return type if present
-        if let Some(arrow_pos) = signature.find("->") {
-            let after_arrow = &signature[arrow_pos + 2..].trim();
-            // Find the end of the return type (first space or brace)
-            let return_type_end = after_arrow.find(' ').unwrap_or(after_arrow.find('{').unwrap_or(after_arrow.len()));
-            let return_type_str = &after_arrow[..return_type_end];
-            if let Some(param_type) = self.parse_param_type_from_str(return_type_str) {
-                outputs = vec![param_type];
+            other => return Err(self.error(other.span(), "unsupported selector pattern")),
+        };
+        let selector = u8::try_from(value)
+            .map_err(|_| self.error(span, format!("selector {} does not fit in a u8", value)))?;
+        Ok(vec![selector])
+    }
+
+    /// The ABI of the function a selector arm calls
+    fn arm_function(&self, arm: &Arm, context: &HashSet<String>) -> Result<FunctionAbi, AbiError> {
+        let mut calls = LocalCalls { analyzer: self, calls: Vec::new() };
+        calls.visit_expr(&arm.body);
+        let call = calls.calls.first().ok_or_else(|| {
+            self.error(arm.pat.span(), "selector arm does not call a function defined in this file")
+        })?;
+        let func = self.fns[&call_name(call).unwrap_or_default()];
+
+        // A name bound inside the arm shadows the VM-supplied one
+        let mut locals = LetBindings::default();
+        locals.visit_expr(&arm.body);
+        let context: HashSet<String> = context.difference(&locals.names).cloned().collect();
+
+        self.function_abi(func, Some((call, &context)))
+    }
+
+    /// The ABI of `func`'s signature, dropping VM-supplied addresses
+    fn function_abi(&self, func: &ItemFn, call_site: Option<(&ExprCall, &HashSet<String>)>) -> Result<FunctionAbi, AbiError> {
+        let mut inputs = Vec::new();
+        for (index, input) in func.sig.inputs.iter().enumerate() {
+            let FnArg::Typed(typed) = input else {
+                return Err(self.error(input.span(), "contract functions cannot take `self`"));
+            };
+            let name = pat_ident(&typed.pat).unwrap_or_else(|| format!("arg{}", index));
+            let ty = typed.ty.as_ref();
+
+            if last_ident(ty).as_deref() == Some("CallContext") {
+                continue;
+            }
+            if is_data_parser(ty) {
+                inputs.extend(self.parser_reads(func, &name)?);
+                continue;
+            }
+            let kind = rust_param_type(ty).ok_or_else(|| {
+                self.error(
+                    ty.span(),
+                    format!("unsupported type `{}` for parameter `{}` of `{}`", type_name(ty), name, func.sig.ident),
+                )
+            })?;
+
+            // The VM passes `caller: Address` implicitly; keep it out of the ABI so generated
+            // clients encode only the real, routed arguments.
+            let implicit = kind == ParamType::Address
+                && (name == "caller"
+                    || call_site.is_some_and(|(call, context)| {
+                        call.args
+                            .iter()
+                            .nth(index)
+                            .and_then(expr_ident)
+                            .is_some_and(|arg| context.contains(&arg))
+                    }));
+            if !implicit {
+                inputs.push(FunctionParam { name, kind });
             }
         }
-        
-        FunctionAbi {
-            name: function_name.to_string(),
+
+        let outputs = match &func.sig.output {
+            ReturnType::Default => Vec::new(),
+            ReturnType::Type(_, ty) => match ty.as_ref() {
+                Type::Tuple(tuple) if tuple.elems.is_empty() => Vec::new(),
+                ty => vec![rust_param_type(ty).ok_or_else(|| {
+                    self.error(
+                        ty.span(),
+                        format!("unsupported return type `{}` of `{}`", type_name(ty), func.sig.ident),
+                    )
+                })?],
+            },
+        };
+
+        Ok(FunctionAbi {
+            name: func.sig.ident.to_string(),
             selector: 0, // Will be set by caller
             inputs,
             outputs,
+        })
+    }
+
+    /// The arguments `func` reads from its `parser` parameter, in order
+    fn parser_reads(&self, func: &ItemFn, parser: &str) -> Result<Vec<FunctionParam>, AbiError> {
+        let mut reads = ParserReads {
+            parser,
+            reads: Vec::new(),
+        };
+        reads.visit_block(&func.block);
+
+        let mut inputs = Vec::new();
+        for (index, read) in reads.reads.into_iter().enumerate() {
+            inputs.push(FunctionParam {
+                kind: self.read_kind(parser, &read)?,
+                name: read.name.unwrap_or_else(|| format!("arg{}", index)),
+            });
         }
+        Ok(inputs)
     }
-    
-    fn parse_parameter(&self, param_str: &str) -> Option<(String, ParamType)> {
-        // Parse parameter like: "caller: Address" or "args: &[u8]"
-        if let Some(colon_pos) = param_str.find(':') {
-            let name = param_str[..colon_pos].trim();
-            let type_str = param_str[colon_pos + 1..].trim();
-            
-            if let Some(param_type) = self.parse_param_type_from_str(type_str) {
-                return Some((name.to_string(), param_type));
+
+    /// The ABI type of the value one `parser.read*()` call takes off the input
+    fn read_kind(&self, parser: &str, read: &ParserRead) -> Result<ParamType, AbiError> {
+        let call = read.call;
+        let method = call.method.to_string();
+        let unsupported = |detail: &str| {
+            self.error(
+                call.method.span(),
+                format!("cannot derive an ABI type from `{}.{}()`{}", parser, method, detail),
+            )
+        };
+        match method.as_str() {
+            "read_u32" => Ok(ParamType::Uint(32)),
+            "read_u64" => Ok(ParamType::Uint(64)),
+            "read_bool" => Ok(ParamType::Bool),
+            "read_address" => Ok(ParamType::Address),
+            // The contract reads exactly `len` raw bytes, with no length prefix
+            "read_bytes" => match call.args.first().and_then(literal_usize) {
+                Some(1) if read.first_byte => Ok(ParamType::Uint(8)),
+                Some(len) => Ok(ParamType::FixedBytes(len)),
+                None => Err(unsupported(": the length must be an integer literal")),
+            },
+            // `read::<T>()`, or `let x: T = parser.read();`
+            "read" => {
+                let turbofish = call.turbofish.as_ref().and_then(|generics| match generics.args.first()? {
+                    syn::GenericArgument::Type(ty) => Some(ty),
+                    _ => None,
+                });
+                let ty = turbofish
+                    .or(read.ty)
+                    .ok_or_else(|| unsupported(": name the type with `read::<T>()` or a `let` annotation"))?;
+                match rust_param_type(ty) {
+                    Some(ParamType::Result) | None => Err(self.error(
+                        ty.span(),
+                        format!("unsupported type `{}` read from `{}`", type_name(ty), parser),
+                    )),
+                    Some(kind) => Ok(kind),
+                }
             }
+            _ => Err(unsupported("")),
         }
-        None
     }
-    
-    pub fn parse_param_type_from_str(&self, type_str: &str) -> Option<ParamType> {
-        match type_str.trim() {
-            "Address" => Some(ParamType::Address),
-            "u8" => Some(ParamType::Uint(8)),
-            "u16" => Some(ParamType::Uint(16)),
-            "u32" => Some(ParamType::Uint(32)),
-            "u64" => Some(ParamType::Uint(64)),
-            "u128" => Some(ParamType::Uint(128)),
-            "bool" => Some(ParamType::Bool),
-            "&[u8]" | "[u8]" => Some(ParamType::Bytes),
-            "String" | "&str" => Some(ParamType::String),
-            "Result" => Some(ParamType::Result), // Proper Result type
+}
+
+/// The value of an integer literal expression such as `32`
+fn literal_usize(expr: &Expr) -> Option<usize> {
+    match expr {
+        Expr::Lit(lit) => match &lit.lit {
+            Lit::Int(int) => int.base10_parse().ok(),
             _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn type_name_of_path(path: &syn::Path) -> String {
+    quote::quote!(#path).to_string().replace(' ', "")
+}
+
+fn call_name(call: &ExprCall) -> Option<String> {
+    expr_ident(&call.func)
+}
+
+fn calls_route(func: &ItemFn) -> bool {
+    struct RouteCalls(bool);
+    impl<'ast> Visit<'ast> for RouteCalls {
+        fn visit_expr_call(&mut self, call: &'ast ExprCall) {
+            if let Expr::Path(path) = call.func.as_ref() {
+                if path.path.segments.last().is_some_and(|s| s.ident == "route") {
+                    self.0 = true;
+                }
+            }
+            visit::visit_expr_call(self, call);
         }
     }
+    let mut visitor = RouteCalls(false);
+    visitor.visit_block(&func.block);
+    visitor.0
+}
 
-    fn parse_selector_from_line(&self, line: &str) -> Option<u8> {
-        let parts: Vec<&str> = line.split("=>").collect();
-        if parts.len() != 2 {
-            return None;
+/// Finds the first dispatch `match`, tracking enclosing closure parameters
+struct DispatchFinder<'a, 'b> {
+    analyzer: &'b Analyzer<'a>,
+    closures: Vec<String>,
+    found: Option<Dispatch<'a>>,
+}
+
+impl<'a> Visit<'a> for DispatchFinder<'a, '_> {
+    fn visit_expr_closure(&mut self, closure: &'a ExprClosure) {
+        let depth = self.closures.len();
+        self.closures.extend(closure.inputs.iter().filter_map(pat_ident));
+        visit::visit_expr_closure(self, closure);
+        self.closures.truncate(depth);
+    }
+
+    fn visit_expr_match(&mut self, expr: &'a ExprMatch) {
+        if self.found.is_some() {
+            return;
         }
-        
-        let selector_str = parts[0].trim();
-        if !selector_str.starts_with("0x") {
-            return None;
+        if self.analyzer.is_dispatch(expr) {
+            self.found = Some(Dispatch {
+                expr,
+                context: self.closures.iter().cloned().collect(),
+            });
+            return;
+        }
+        visit::visit_expr_match(self, expr);
+    }
+}
+
+/// Calls to functions defined in the file, outermost first
+struct LocalCalls<'a, 'b> {
+    analyzer: &'b Analyzer<'a>,
+    calls: Vec<&'b ExprCall>,
+}
+
+impl<'b> Visit<'b> for LocalCalls<'_, 'b> {
+    fn visit_expr_call(&mut self, call: &'b ExprCall) {
+        if call_name(call).is_some_and(|name| self.analyzer.fns.contains_key(&name)) {
+            self.calls.push(call);
         }
-        
-        u8::from_str_radix(&selector_str[2..], 16).ok()
-    }
-    
-    fn extract_direct_function_call(&self, line: &str) -> Option<String> {
-        // Look for pattern like "0x01 => compare(call.args),"
-        if let Some(arrow_pos) = line.find("=>") {
-            let after_arrow = &line[arrow_pos + 2..].trim();
-            if let Some(start) = after_arrow.find('(') {
-                let function_name = after_arrow[..start].trim();
-                if !function_name.is_empty() && 
-                   !function_name.contains(' ') && 
-                   !function_name.contains("::") && 
-                   !function_name.contains('.') {
-                    return Some(function_name.to_string());
+        visit::visit_expr_call(self, call);
+    }
+}
+
+#[derive(Default)]
+struct LetBindings {
+    names: HashSet<String>,
+}
+
+impl<'ast> Visit<'ast> for LetBindings {
+    fn visit_local(&mut self, local: &'ast Local) {
+        self.names.extend(pat_ident(&local.pat));
+        visit::visit_local(self, local);
+    }
+}
+
+/// One `parser.read*()` call
+struct ParserRead<'ast> {
+    /// The `let` name the value is bound to
+    name: Option<String>,
+    /// The `let` type annotation, which fixes the type of a bare `read()`
+    ty: Option<&'ast Type>,
+    call: &'ast ExprMethodCall,
+    /// Only the first byte is used, as in `parser.read_bytes(1)[0]`
+    first_byte: bool,
+}
+
+/// `parser.read*()` calls, in order
+struct ParserReads<'p, 'ast> {
+    parser: &'p str,
+    reads: Vec<ParserRead<'ast>>,
+}
+
+impl<'ast> ParserReads<'_, 'ast> {
+    /// The read at the root of `let x = parser.read_bytes(1)[0] as u128;`,
+    /// and whether only its first byte is kept
+    fn direct_read(&self, mut expr: &'ast Expr) -> Option<(&'ast ExprMethodCall, bool)> {
+        let mut first_byte = false;
+        loop {
+            expr = match expr {
+                Expr::Cast(cast) => &cast.expr,
+                Expr::Paren(paren) => &paren.expr,
+                Expr::Index(index) => {
+                    first_byte = literal_usize(&index.index) == Some(0);
+                    &index.expr
                 }
-            }
+                Expr::MethodCall(call) if self.is_read(call) => return Some((call, first_byte)),
+                _ => return None,
+            };
         }
-        None
-    }
-    
-    fn extract_function_call_from_line(&self, line: &str) -> Option<String> {
-        // Look for function call pattern: function_name(...)
-        if let Some(start) = line.find('(') {
-            let potential_name = line[..start].trim();
-            if !potential_name.is_empty() && 
-               !potential_name.contains(' ') && 
-               !potential_name.contains("::") && 
-               !potential_name.contains('.') {
-                return Some(potential_name.to_string());
-            }
+    }
+
+    fn is_read(&self, call: &ExprMethodCall) -> bool {
+        let method = call.method.to_string();
+        (method == "read" || method.starts_with("read_"))
+            && expr_ident(&call.receiver).is_some_and(|receiver| receiver == self.parser)
+    }
+
+    fn push(&mut self, name: Option<String>, ty: Option<&'ast Type>, call: &'ast ExprMethodCall, first_byte: bool) {
+        self.reads.push(ParserRead { name, ty, call, first_byte });
+        // The read's own arguments are still visited
+        for arg in &call.args {
+            self.visit_expr(arg);
         }
-        
-        // Also look for patterns like "let b = balance_of(call.args)"
-        if line.contains("let") && line.contains('=') {
-            if let Some(equals_pos) = line.find('=') {
-                let after_equals = &line[equals_pos + 1..];
-                if let Some(start) = after_equals.find('(') {
-                    let potential_name = after_equals[..start].trim();
-                    if !potential_name.is_empty() && 
-                       !potential_name.contains(' ') && 
-                       !potential_name.contains("::") && 
-                       !potential_name.contains('.') {
-                        return Some(potential_name.to_string());
-                    }
-                }
+    }
+}
+
+impl<'ast> Visit<'ast> for ParserReads<'_, 'ast> {
+    fn visit_local(&mut self, local: &'ast Local) {
+        if let Some(init) = &local.init {
+            if let Some((call, first_byte)) = self.direct_read(&init.expr) {
+                let ty = match &local.pat {
+                    Pat::Type(typed) => Some(typed.ty.as_ref()),
+                    _ => None,
+                };
+                self.push(pat_ident(&local.pat), ty, call, first_byte);
+                return;
             }
         }
-        
-        None
+        visit::visit_local(self, local);
     }
 
-    /// Generate ABI from a source file
-    pub fn from_file<P: AsRef<Path>>(path: P) -> std::io::Result<ContractAbi> {
-        let source_code = fs::read_to_string(path)?;
-        let mut generator = AbiGenerator::new(source_code);
-        Ok(generator.generate())
+    fn visit_expr_index(&mut self, index: &'ast syn::ExprIndex) {
+        if let Expr::MethodCall(call) = index.expr.as_ref() {
+            if self.is_read(call) {
+                self.push(None, None, call, literal_usize(&index.index) == Some(0));
+                self.visit_expr(&index.index);
+                return;
+            }
+        }
+        visit::visit_expr_index(self, index);
     }
 
-    /// Write ABI to a JSON file
-    pub fn write_abi_to_file<P: AsRef<Path>>(abi: &ContractAbi, path: P) -> std::io::Result<()> {
-        let json = abi.to_json();
-        fs::write(path, json)
+    fn visit_expr_method_call(&mut self, call: &'ast ExprMethodCall) {
+        if self.is_read(call) {
+            self.push(None, None, call, false);
+            return;
+        }
+        visit::visit_expr_method_call(self, call);
     }
 }
 
@@ -501,24 +778,24 @@ impl AbiGenerator {
 pub fn generate_all_example_abis() -> std::io::Result<()> {
     let examples_dir = "crates/examples/src";
     let bin_dir = "crates/examples/bin";
-    
+
     // Ensure bin directory exists
     fs::create_dir_all(bin_dir)?;
-    
+
     let source_files = vec![
         "simple.rs",
-        "storage.rs", 
+        "storage.rs",
         "erc20.rs",
         "multi_func.rs",
         "call_program.rs",
     ];
-    
+
     for source_file in source_files {
         let source_path = format!("{}/{}", examples_dir, source_file);
         let abi_path = format!("{}/{}.abi.json", bin_dir, source_file.replace(".rs", ""));
-        
+
         println!("Generating ABI for {}", source_file);
-        
+
         match AbiGenerator::from_file(&source_path) {
             Ok(abi) => {
                 AbiGenerator::write_abi_to_file(&abi, &abi_path)?;
@@ -529,6 +806,6 @@ pub fn generate_all_example_abis() -> std::io::Result<()> {
             }
         }
     }
-    
+
     Ok(())
 }
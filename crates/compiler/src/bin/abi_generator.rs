@@ -16,9 +16,15 @@ fn main() {
     
     match fs::read_to_string(input_file) {
         Ok(source_code) => {
-            let mut generator = AbiGenerator::new(source_code);
-            let abi = generator.generate();
-            
+            let mut generator = AbiGenerator::new(source_code).with_file_name(input_file.as_str());
+            let abi = match generator.generate() {
+                Ok(abi) => abi,
+                Err(e) => {
+                    eprintln!("✗ Failed to generate ABI: {}", e);
+                    std::process::exit(1);
+                }
+            };
+
             match fs::write(output_file, abi.to_json()) {
                 Ok(_) => println!("✓ Generated ABI: {}", output_file),
                 Err(e) => {
@@ -3,8 +3,10 @@ use std::fs;
 use std::path::{Path, PathBuf};
 use std::process::Command;
 
-use compiler::abi_codegen::AbiCodeGenerator;
+use compiler::abi_codegen::{AbiCodeGenerator, CodegenTarget};
 use compiler::abi_generator::AbiGenerator;
+use compiler::ContractAbi;
+use compiler::parse_elf_from_bytes;
 
 #[derive(Debug, Clone)]
 struct Paths {
@@ -216,6 +218,7 @@ fn cmd_abi(mut args: Vec<String>, paths: &Paths) -> Result<(), String> {
     let mut src: Option<PathBuf> = None;
     let mut out: Option<PathBuf> = None;
     let mut manifest_path: Option<PathBuf> = None;
+    let mut elf: Option<PathBuf> = None;
 
     let mut i = 0;
     while i < args.len() {
@@ -224,6 +227,11 @@ fn cmd_abi(mut args: Vec<String>, paths: &Paths) -> Result<(), String> {
                 i += 1;
                 bin = Some(args.get(i).cloned().ok_or("missing value for --bin")?);
             }
+            "--elf" => {
+                i += 1;
+                let val = args.get(i).cloned().ok_or("missing value for --elf")?;
+                elf = Some(PathBuf::from(val));
+            }
             "--src" => {
                 i += 1;
                 let val = args.get(i).cloned().ok_or("missing value for --src")?;
@@ -266,11 +274,29 @@ fn cmd_abi(mut args: Vec<String>, paths: &Paths) -> Result<(), String> {
     };
 
     let output = out.unwrap_or_else(|| manifest_dir.join("bin").join(format!("{}.abi.json", bin_name)));
-    let source =
-        fs::read_to_string(&src_path).map_err(|e| format!("failed to read {}: {}", src_path.display(), e))?;
 
-    let mut generator = AbiGenerator::new(source);
-    let abi = generator.generate();
+    // Prefer the ABI a #[contract] program embeds in its ELF; otherwise scrape the source
+    let embedded = match &elf {
+        Some(elf_path) => {
+            let bytes = fs::read(elf_path).map_err(|e| format!("failed to read {}: {}", elf_path.display(), e))?;
+            let info = parse_elf_from_bytes(&bytes)
+                .map_err(|e| format!("failed to parse {}: {}", elf_path.display(), e))?;
+            info.embedded_abi()
+                .map_err(|e| format!("invalid ABI in {}: {}", elf_path.display(), e))?
+        }
+        None => None,
+    };
+    let abi = match embedded {
+        Some(abi) => abi,
+        None => {
+            let source = fs::read_to_string(&src_path)
+                .map_err(|e| format!("failed to read {}: {}", src_path.display(), e))?;
+            AbiGenerator::new(source)
+                .with_file_name(src_path.display().to_string())
+                .generate()
+                .map_err(|e| format!("failed to generate ABI: {}", e))?
+        }
+    };
     fs::create_dir_all(
         output
             .parent()
@@ -288,10 +314,12 @@ fn cmd_client(mut args: Vec<String>, _paths: &Paths) -> Result<(), String> {
     let mut abi_path: Option<PathBuf> = None;
     let mut out: Option<PathBuf> = None;
     let mut contract: Option<String> = None;
+    let mut target = CodegenTarget::Guest;
 
     let mut i = 0;
     while i < args.len() {
         match args[i].as_str() {
+            "--host" => target = CodegenTarget::Host,
             "--abi" => {
                 i += 1;
                 let val = args.get(i).cloned().ok_or("missing value for --abi")?;
@@ -325,13 +353,13 @@ fn cmd_client(mut args: Vec<String>, _paths: &Paths) -> Result<(), String> {
 
     let contract_name = contract.unwrap_or_else(|| derive_contract_name(&abi_path));
 
-    let code = AbiCodeGenerator::from_abi_file(
-        abi_path
-            .to_str()
-            .ok_or("invalid abi path")?,
-        contract_name,
-    )
-    .map_err(|e| format!("failed to generate client: {}", e))?;
+    let abi_json = fs::read_to_string(&abi_path)
+        .map_err(|e| format!("failed to read {}: {}", abi_path.display(), e))?;
+    let abi = ContractAbi::from_json(&abi_json)
+        .map_err(|e| format!("failed to generate client: {}", e))?;
+    let code = AbiCodeGenerator::new(abi, contract_name)
+        .with_target(target)
+        .generate_rust_code();
 
     fs::create_dir_all(out.parent().ok_or("invalid output path for client")?)
         .map_err(|e| e.to_string())?;
@@ -439,6 +467,8 @@ fn cmd_all(mut args: Vec<String>, paths: &Paths) -> Result<(), String> {
             src.display().to_string(),
             "--out".into(),
             abi_out.display().to_string(),
+            "--elf".into(),
+            out_dir.join(format!("{}.elf", bin)).display().to_string(),
             "--manifest-path".into(),
             manifest_path.display().to_string(),
         ],
@@ -485,8 +515,8 @@ fn print_usage() {
     eprintln!(
         "Usage:
   avm32 build --bin <name> [--manifest-path <cargo_toml>] [--out-dir <dir>] [--linker-script <file>] [--cargo <cmd>] [--features <feat>] [--debug|--release]
-  avm32 abi --bin <name> [--src <path>] [--out <file>] [--manifest-path <cargo_toml>]
-  avm32 client --abi <file> [--out <file>] [--contract <name>]
+  avm32 abi --bin <name> [--src <path>] [--elf <file>] [--out <file>] [--manifest-path <cargo_toml>]
+  avm32 client --abi <file> [--out <file>] [--contract <name>] [--host]
   avm32 all --bin <name> [--manifest-path <cargo_toml>] [--src <path>] [--out-dir <dir>] [--linker-script <file>] [--cargo <cmd>] [--features <feat>]"
     );
 }
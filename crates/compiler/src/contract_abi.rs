@@ -0,0 +1,272 @@
+//! Reads the ABI of a `#[contract]` module from its syntax tree.
+//!
+//! EDUCATIONAL PURPOSE: a `#[contract]` program's ABI is produced twice. The
+//! macro embeds one in the ELF when the program is compiled, and `avm32 abi`
+//! derives one from the source file without compiling it. If the two walked
+//! the module separately, each with its own idea of which arguments count
+//! and how a Rust type maps to an ABI type, they would drift apart. Both
+//! call `analyze_contract` instead, and every Rust type in an ABI, routed or
+//! `#[contract]`, goes through `rust_param_type`.
+
+use std::collections::BTreeMap;
+
+use syn::spanned::Spanned;
+use syn::{Attribute, Expr, FnArg, Ident, Item, ItemFn, Lit, Pat, ReturnType, Type};
+
+use crate::abi::{ContractAbi, EventAbi, EventParam, FunctionAbi, FunctionParam, ParamType};
+
+/// A `#[contract]` module: its callable functions and the ABI they form.
+pub struct ContractModule {
+    pub functions: Vec<ContractFunction>,
+    pub abi: ContractAbi,
+}
+
+/// A `#[function(selector = N)]` of a contract module.
+pub struct ContractFunction {
+    pub ident: Ident,
+    pub selector: u8,
+    pub inputs: Vec<ContractInput>,
+}
+
+/// An argument of a contract function, as the dispatcher fills it in.
+pub enum ContractInput {
+    /// `CallContext`, filled in from the call rather than the input
+    Context,
+    /// A value decoded from the call input
+    Value { name: Ident, ty: Box<Type> },
+}
+
+/// Maps a Rust type to its ABI type, or `None` if it has no ABI encoding
+pub fn rust_param_type(ty: &Type) -> Option<ParamType> {
+    match ty {
+        Type::Reference(reference) => match reference.elem.as_ref() {
+            elem if last_ident(elem).as_deref() == Some("str") => Some(ParamType::String),
+            elem => rust_param_type(elem),
+        },
+        Type::Slice(slice) => match rust_param_type(&slice.elem)? {
+            ParamType::Uint(8) => Some(ParamType::Bytes),
+            elem => Some(ParamType::Array(Box::new(elem))),
+        },
+        Type::Array(array) => {
+            let len = match &array.len {
+                Expr::Lit(expr) => match &expr.lit {
+                    Lit::Int(int) => int.base10_parse::<usize>().ok()?,
+                    _ => return None,
+                },
+                _ => return None,
+            };
+            match rust_param_type(&array.elem)? {
+                ParamType::Uint(8) => Some(ParamType::FixedBytes(len)),
+                elem => Some(ParamType::FixedArray(Box::new(elem), len)),
+            }
+        }
+        Type::Tuple(tuple) if !tuple.elems.is_empty() => {
+            tuple.elems.iter().map(rust_param_type).collect::<Option<_>>().map(ParamType::Tuple)
+        }
+        Type::Paren(paren) => rust_param_type(&paren.elem),
+        Type::Path(path) if last_ident(ty).as_deref() == Some("Vec") => {
+            let syn::PathArguments::AngleBracketed(args) = &path.path.segments.last()?.arguments else {
+                return None;
+            };
+            match args.args.first()? {
+                syn::GenericArgument::Type(elem) => match rust_param_type(elem)? {
+                    ParamType::Uint(8) => Some(ParamType::Bytes),
+                    elem => Some(ParamType::Array(Box::new(elem))),
+                },
+                _ => None,
+            }
+        }
+        _ => match last_ident(ty)?.as_str() {
+            "Address" => Some(ParamType::Address),
+            "u8" => Some(ParamType::Uint(8)),
+            "u16" => Some(ParamType::Uint(16)),
+            "u32" => Some(ParamType::Uint(32)),
+            "u64" => Some(ParamType::Uint(64)),
+            "u128" => Some(ParamType::Uint(128)),
+            "i8" => Some(ParamType::Int(8)),
+            "i16" => Some(ParamType::Int(16)),
+            "i32" => Some(ParamType::Int(32)),
+            "i64" => Some(ParamType::Int(64)),
+            "i128" => Some(ParamType::Int(128)),
+            "bool" => Some(ParamType::Bool),
+            "String" => Some(ParamType::String),
+            "Result" => Some(ParamType::Result),
+            _ => None,
+        },
+    }
+}
+
+pub(crate) fn last_ident(ty: &Type) -> Option<String> {
+    match ty {
+        Type::Path(path) => path.path.segments.last().map(|s| s.ident.to_string()),
+        _ => None,
+    }
+}
+
+fn type_name(ty: &Type) -> String {
+    quote::quote!(#ty).to_string()
+}
+
+/// Whether `attr` is `#[function(...)]`
+pub fn is_function_attr(attr: &Attribute) -> bool {
+    attr.path().is_ident("function")
+}
+
+/// Reads the functions and events of a `#[contract]` module's items.
+///
+/// Only `CallContext` is left out of a function's inputs: any other
+/// argument, even an `Address` named `caller`, is read from the call input
+/// and so is part of the ABI.
+pub fn analyze_contract(items: &[Item]) -> syn::Result<ContractModule> {
+    let mut abi = ContractAbi::new();
+    let mut functions = Vec::new();
+    let mut selectors: BTreeMap<u8, Ident> = BTreeMap::new();
+
+    for item in items {
+        match item {
+            Item::Fn(func) => {
+                let Some(selector) = function_selector(&func.attrs)? else { continue };
+                if let Some(previous) = selectors.insert(selector, func.sig.ident.clone()) {
+                    return Err(syn::Error::new(
+                        func.sig.ident.span(),
+                        format!("selector {:#04x} is already used by `{}`", selector, previous),
+                    ));
+                }
+                let (function, function_abi) = analyze_function(func, selector)?;
+                functions.push(function);
+                abi.add_function(function_abi);
+            }
+            Item::Macro(mac) if mac.mac.path.segments.last().is_some_and(|s| s.ident == "event") => {
+                abi.add_event(event_abi(&mac.mac)?);
+            }
+            _ => {}
+        }
+    }
+    Ok(ContractModule { functions, abi })
+}
+
+/// The `N` of a `#[function(selector = N)]` attribute, if there is one
+fn function_selector(attrs: &[Attribute]) -> syn::Result<Option<u8>> {
+    let Some(attr) = attrs.iter().find(|attr| is_function_attr(attr)) else {
+        return Ok(None);
+    };
+    let mut selector = None;
+    attr.parse_nested_meta(|meta| {
+        if meta.path.is_ident("selector") {
+            let lit: syn::LitInt = meta.value()?.parse()?;
+            selector = Some(lit.base10_parse::<u8>()?);
+            Ok(())
+        } else {
+            Err(meta.error("expected `selector = <u8>`"))
+        }
+    })?;
+    selector
+        .map(Some)
+        .ok_or_else(|| syn::Error::new(attr.span(), "missing `selector = <u8>`"))
+}
+
+fn analyze_function(func: &ItemFn, selector: u8) -> syn::Result<(ContractFunction, FunctionAbi)> {
+    let sig = &func.sig;
+    if !sig.generics.params.is_empty() || sig.asyncness.is_some() {
+        return Err(syn::Error::new(sig.span(), "contract functions cannot be generic or async"));
+    }
+
+    let mut inputs = Vec::new();
+    let mut params = Vec::new();
+    for (i, input) in sig.inputs.iter().enumerate() {
+        let FnArg::Typed(typed) = input else {
+            return Err(syn::Error::new(input.span(), "contract functions cannot take `self`"));
+        };
+        let Pat::Ident(pat) = typed.pat.as_ref() else {
+            return Err(syn::Error::new(typed.pat.span(), "expected a plain argument name"));
+        };
+        let ty = typed.ty.as_ref();
+
+        if last_ident(ty).as_deref() == Some("CallContext") {
+            if i != 0 {
+                return Err(syn::Error::new(ty.span(), "`CallContext` must be the first argument"));
+            }
+            inputs.push(ContractInput::Context);
+            continue;
+        }
+        let kind = match rust_param_type(ty) {
+            Some(ParamType::Result) | None => {
+                return Err(syn::Error::new(
+                    ty.span(),
+                    format!("unsupported type `{}` for argument `{}`", type_name(ty), pat.ident),
+                ))
+            }
+            Some(kind) => kind,
+        };
+        params.push(FunctionParam { name: pat.ident.to_string(), kind });
+        inputs.push(ContractInput::Value { name: pat.ident.clone(), ty: typed.ty.clone() });
+    }
+
+    let outputs = match &sig.output {
+        ReturnType::Default => Vec::new(),
+        ReturnType::Type(_, ty) => match ty.as_ref() {
+            Type::Tuple(tuple) if tuple.elems.is_empty() => Vec::new(),
+            ty => vec![rust_param_type(ty).ok_or_else(|| {
+                syn::Error::new(ty.span(), format!("unsupported return type `{}`", type_name(ty)))
+            })?],
+        },
+    };
+
+    let function = ContractFunction { ident: sig.ident.clone(), selector, inputs };
+    let abi = FunctionAbi { name: sig.ident.to_string(), selector, inputs: params, outputs };
+    Ok((function, abi))
+}
+
+/// The ABI of an `event!(Name { field => Type, ... })` invocation
+pub(crate) fn event_abi(mac: &syn::Macro) -> syn::Result<EventAbi> {
+    let decl: EventDecl = mac
+        .parse_body()
+        .map_err(|e| syn::Error::new(e.span(), format!("malformed event!: {}", e)))?;
+    let mut inputs = Vec::new();
+    for (name, ty) in decl.fields {
+        let kind = match rust_param_type(&ty) {
+            Some(ParamType::Result) | None => {
+                return Err(syn::Error::new(
+                    ty.span(),
+                    format!("unsupported type `{}` for event field `{}`", type_name(&ty), name),
+                ))
+            }
+            Some(kind) => kind,
+        };
+        inputs.push(EventParam {
+            name: name.to_string(),
+            kind,
+            indexed: false, // Default to false for now
+        });
+    }
+    Ok(EventAbi {
+        name: decl.name.to_string(),
+        inputs,
+    })
+}
+
+/// `event!(Name { field => Type, ... })`
+struct EventDecl {
+    name: Ident,
+    fields: Vec<(Ident, Type)>,
+}
+
+impl syn::parse::Parse for EventDecl {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let name = input.parse()?;
+        let content;
+        syn::braced!(content in input);
+        let mut fields = Vec::new();
+        while !content.is_empty() {
+            let field: Ident = content.parse()?;
+            content.parse::<syn::Token![=>]>()?;
+            let ty: Type = content.parse()?;
+            fields.push((field, ty));
+            if content.is_empty() {
+                break;
+            }
+            content.parse::<syn::Token![,]>()?;
+        }
+        Ok(Self { name, fields })
+    }
+}
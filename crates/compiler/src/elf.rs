@@ -1,5 +1,10 @@
 use goblin::elf::Elf;
 
+use crate::abi::ContractAbi;
+
+/// Section `#[contract]` programs embed their ABI JSON in.
+pub const ABI_SECTION: &str = ".abi";
+
 pub struct ElfInfo<'a> {
     pub code: &'a [u8],
     pub sections: Vec<ElfSection<'a>>,
@@ -68,6 +73,21 @@ impl<'a> ElfInfo<'a> {
     pub fn get_section_by_name(&self, name: &str) -> Option<&ElfSection<'a>> {
         self.sections.iter().find(|s| s.name == name)
     }
+
+    /// Returns the ABI embedded in the `.abi` section, if the program has one.
+    ///
+    /// Programs written with `#[contract]` carry their ABI inside the ELF, so
+    /// tooling can read it back from the binary instead of the source.
+    pub fn embedded_abi(&self) -> Result<Option<ContractAbi>, String> {
+        let Some(section) = self.get_section_by_name(ABI_SECTION) else {
+            return Ok(None);
+        };
+        // The linker may pad the section; the JSON itself never contains NUL
+        let end = section.data.iter().position(|&b| b == 0).unwrap_or(section.data.len());
+        let json = std::str::from_utf8(&section.data[..end])
+            .map_err(|e| format!("{} section is not UTF-8: {}", ABI_SECTION, e))?;
+        ContractAbi::from_json(json).map(Some)
+    }
 }
 
 
@@ -8,4 +8,6 @@ pub mod abi_generator;
 pub use abi_generator::*;
 
 pub mod abi_codegen;
-pub use abi_codegen::*;
\ No newline at end of file
+pub use abi_codegen::*;
+pub mod contract_abi;
+pub use contract_abi::*;
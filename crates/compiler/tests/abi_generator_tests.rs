@@ -17,7 +17,7 @@ fn test_abi_generation() {
     "#;
 
     let mut generator = AbiGenerator::new(source_code.to_string());
-    let abi = generator.generate();
+    let abi = generator.generate().unwrap();
 
     assert_eq!(abi.events.len(), 2);
     assert_eq!(abi.events[0].name, "Minted");
@@ -68,7 +68,7 @@ fn test_function_extraction() {
     "#;
     
     let mut generator = AbiGenerator::new(source_code.to_string());
-    let abi = generator.generate();
+    let abi = generator.generate().unwrap();
     
     assert_eq!(abi.functions.len(), 3);
     
@@ -101,7 +101,7 @@ fn test_multi_line_event_parsing() {
     "#;
     
     let mut generator = AbiGenerator::new(source_code.to_string());
-    let abi = generator.generate();
+    let abi = generator.generate().unwrap();
     
     assert_eq!(abi.events.len(), 1);
     let event = &abi.events[0];
@@ -129,7 +129,7 @@ fn test_empty_program() {
     "#;
     
     let mut generator = AbiGenerator::new(source_code.to_string());
-    let abi = generator.generate();
+    let abi = generator.generate().unwrap();
     
     assert_eq!(abi.events.len(), 0);
     assert_eq!(abi.functions.len(), 0);
@@ -236,7 +236,7 @@ fn test_real_erc20_like_program() {
     "#;
     
     let mut generator = AbiGenerator::new(source_code.to_string());
-    let abi = generator.generate();
+    let abi = generator.generate().unwrap();
     
     // Check events
     assert_eq!(abi.events.len(), 2);
@@ -315,7 +315,7 @@ fn test_function_input_extraction() {
     "#;
     
     let mut generator = AbiGenerator::new(source_code.to_string());
-    let abi = generator.generate();
+    let abi = generator.generate().unwrap();
     
     // Check that all functions have the correct inputs
     assert_eq!(abi.functions.len(), 3);
@@ -373,48 +373,40 @@ fn test_function_signature_parsing() {
         }
     "#;
     
-    let mut generator = AbiGenerator::new(source_code.to_string());
-    let _abi = generator.generate();
-    
+    let generator = AbiGenerator::new(source_code.to_string());
+
     // Since these functions aren't called in the router, they won't be included
     // But we can test the signature parsing directly
-    let lines: Vec<&str> = source_code.lines().collect();
-    
-    // Test simple_function
-    if let Some(signature) = generator.extract_function_signature(&lines, 1) {
-        let func = generator.parse_function_signature("simple_function", signature);
-        assert_eq!(func.inputs.len(), 0);
-    }
-    
-    // Test single_param
-    if let Some(signature) = generator.extract_function_signature(&lines, 5) {
-        let func = generator.parse_function_signature("single_param", signature);
-        assert_eq!(func.inputs.len(), 1);
-        assert_eq!(func.inputs[0].name, "param");
-        assert!(matches!(func.inputs[0].kind, ParamType::Uint(32)));
-    }
-    
-    // Test multiple_params
-    if let Some(signature) = generator.extract_function_signature(&lines, 9) {
-        let func = generator.parse_function_signature("multiple_params", signature);
-        assert_eq!(func.inputs.len(), 3);
-        assert_eq!(func.inputs[0].name, "a");
-        assert!(matches!(func.inputs[0].kind, ParamType::Address));
-        assert_eq!(func.inputs[1].name, "b");
-        assert!(matches!(func.inputs[1].kind, ParamType::Uint(64)));
-        assert_eq!(func.inputs[2].name, "c");
-        assert!(matches!(func.inputs[2].kind, ParamType::Bool));
-    }
-    
-    // Test with_return_type
-    if let Some(signature) = generator.extract_function_signature(&lines, 13) {
-        let func = generator.parse_function_signature("with_return_type", signature);
-        assert_eq!(func.inputs.len(), 1);
-        assert_eq!(func.inputs[0].name, "input");
-        assert!(matches!(func.inputs[0].kind, ParamType::String));
-        assert_eq!(func.outputs.len(), 1);
-        assert!(matches!(func.outputs[0], ParamType::Uint(32)));
-    }
+    let func = generator.parse_function_signature("fn simple_function()").unwrap();
+    assert_eq!(func.name, "simple_function");
+    assert_eq!(func.inputs.len(), 0);
+
+    let func = generator.parse_function_signature("fn single_param(param: u32)").unwrap();
+    assert_eq!(func.inputs.len(), 1);
+    assert_eq!(func.inputs[0].name, "param");
+    assert!(matches!(func.inputs[0].kind, ParamType::Uint(32)));
+
+    let func = generator.parse_function_signature("fn multiple_params(a: Address, b: u64, c: bool)").unwrap();
+    assert_eq!(func.inputs.len(), 3);
+    assert_eq!(func.inputs[0].name, "a");
+    assert!(matches!(func.inputs[0].kind, ParamType::Address));
+    assert_eq!(func.inputs[1].name, "b");
+    assert!(matches!(func.inputs[1].kind, ParamType::Uint(64)));
+    assert_eq!(func.inputs[2].name, "c");
+    assert!(matches!(func.inputs[2].kind, ParamType::Bool));
+
+    let func = generator.parse_function_signature("fn with_return_type(input: String) -> u32").unwrap();
+    assert_eq!(func.inputs.len(), 1);
+    assert_eq!(func.inputs[0].name, "input");
+    assert!(matches!(func.inputs[0].kind, ParamType::String));
+    assert_eq!(func.outputs.len(), 1);
+    assert!(matches!(func.outputs[0], ParamType::Uint(32)));
+
+    let func = generator
+        .parse_function_signature("fn complex_signature(\n    caller: Address,\n    args: &[u8],\n    flag: bool\n) -> u32")
+        .unwrap();
+    assert_eq!(func.inputs.len(), 2);
+    assert!(matches!(func.inputs[0].kind, ParamType::Bytes));
 }
 
 #[test]
@@ -462,7 +454,7 @@ fn test_edge_case_router_patterns() {
     "#;
     
     let mut generator = AbiGenerator::new(source_code.to_string());
-    let abi = generator.generate();
+    let abi = generator.generate().unwrap();
     
     assert_eq!(abi.functions.len(), 3);
     
@@ -493,7 +485,7 @@ fn test_malformed_function_signatures() {
     "#;
     
     let mut generator = AbiGenerator::new(source_code.to_string());
-    let abi = generator.generate();
+    let abi = generator.generate().unwrap();
     
     // Should find all functions regardless of signature complexity
     assert_eq!(abi.functions.len(), 4);
@@ -548,7 +540,7 @@ fn test_multiline_function_signatures() {
     "#;
     
     let mut generator = AbiGenerator::new(source_code.to_string());
-    let abi = generator.generate();
+    let abi = generator.generate().unwrap();
     
     assert_eq!(abi.functions.len(), 2);
     
@@ -602,7 +594,7 @@ fn test_different_router_patterns() {
     "#;
     
     let mut generator = AbiGenerator::new(source_code.to_string());
-    let abi = generator.generate();
+    let abi = generator.generate().unwrap();
     
     // The router pattern with nested calls might find more functions than expected
     // Let's check that at least the main functions were found
@@ -643,7 +635,7 @@ fn test_result_type_handling() {
     "#;
     
     let mut generator = AbiGenerator::new(source_code.to_string());
-    let abi = generator.generate();
+    let abi = generator.generate().unwrap();
     
     assert_eq!(abi.functions.len(), 4);
     
@@ -689,7 +681,7 @@ fn test_complex_parameter_types() {
     "#;
     
     let mut generator = AbiGenerator::new(source_code.to_string());
-    let abi = generator.generate();
+    let abi = generator.generate().unwrap();
     
     assert_eq!(abi.functions.len(), 1);
     
@@ -720,7 +712,7 @@ fn test_complex_parameter_types() {
 fn test_empty_and_malformed_programs() {
     // Test empty program
     let mut empty_generator = AbiGenerator::new("".to_string());
-    let empty_abi = empty_generator.generate();
+    let empty_abi = empty_generator.generate().unwrap();
     assert_eq!(empty_abi.functions.len(), 0);
     assert_eq!(empty_abi.events.len(), 0);
     
@@ -730,7 +722,7 @@ fn test_empty_and_malformed_programs() {
         fn another_function() { }
     "#;
     let mut no_router_generator = AbiGenerator::new(no_router_source.to_string());
-    let no_router_abi = no_router_generator.generate();
+    let no_router_abi = no_router_generator.generate().unwrap();
     assert_eq!(no_router_abi.functions.len(), 0); // No functions without router
     
     // Test malformed router
@@ -743,7 +735,7 @@ fn test_empty_and_malformed_programs() {
         }
     "#;
     let mut malformed_generator = AbiGenerator::new(malformed_source.to_string());
-    let malformed_abi = malformed_generator.generate();
+    let malformed_abi = malformed_generator.generate().unwrap();
     assert_eq!(malformed_abi.functions.len(), 0); // No functions without router
 }
 
@@ -777,7 +769,7 @@ fn test_event_edge_cases() {
     "#;
     
     let mut generator = AbiGenerator::new(source_code.to_string());
-    let abi = generator.generate();
+    let abi = generator.generate().unwrap();
     
 
     
@@ -817,7 +809,7 @@ fn test_erc20_example_file_generates_typed_abi() {
     // Ensure the real ERC20 example stays in sync with the typed ABI we expect
     let source_code = include_str!("../../examples/src/erc20.rs");
     let mut generator = AbiGenerator::new(source_code.to_string());
-    let abi = generator.generate();
+    let abi = generator.generate().unwrap();
 
     let function_names: Vec<&str> = abi.functions.iter().map(|f| f.name.as_str()).collect();
     assert!(function_names.contains(&"init"));
@@ -867,13 +859,13 @@ fn test_router_preparsed_args_keep_typed_signature() {
                     routed_transfer(caller, to, amount, flag);
                     Result::new(true, 0)
                 }
-                _ => vm_panic(b\"unknown selector\"),
+                _ => vm_panic(b"unknown selector"),
             })
         }
     "#;
 
     let mut generator = AbiGenerator::new(source_code.to_string());
-    let abi = generator.generate();
+    let abi = generator.generate().unwrap();
 
     assert_eq!(abi.functions.len(), 1);
     let func = &abi.functions[0];
@@ -890,3 +882,207 @@ fn test_router_preparsed_args_keep_typed_signature() {
     assert_eq!(func.outputs.len(), 1);
     assert!(matches!(func.outputs[0], ParamType::Result));
 }
+
+#[test]
+fn test_dex_example_resolves_const_selectors() {
+    // dex.rs matches on `const` selectors and hands a DataParser to each handler
+    let source_code = include_str!("../../examples/src/dex.rs");
+    let mut generator = AbiGenerator::new(source_code.to_string());
+    let abi = generator.generate().unwrap();
+
+    let selectors: Vec<(u8, &str)> = abi.functions.iter().map(|f| (f.selector, f.name.as_str())).collect();
+    assert_eq!(selectors, vec![(1, "add_liquidity"), (2, "remove_liquidity"), (3, "swap")]);
+
+    // Inputs come from the handler's parser reads; the program and caller addresses are implicit
+    let add = &abi.functions[0];
+    assert_eq!(add.inputs.len(), 2);
+    assert_eq!(add.inputs[0].name, "am_in");
+    assert!(matches!(add.inputs[0].kind, ParamType::Uint(64)));
+    assert_eq!(add.inputs[1].name, "token_in");
+    assert!(matches!(add.inputs[1].kind, ParamType::Uint(64)));
+    assert!(matches!(add.outputs[0], ParamType::Result));
+
+    // `parser.read_bytes(1)[0]` is a single byte on the wire
+    let swap = &abi.functions[2];
+    assert_eq!(swap.inputs[0].name, "direction");
+    assert_eq!(swap.inputs[0].kind, ParamType::Uint(8));
+    assert_eq!(swap.inputs[1].kind, ParamType::Uint(64));
+
+    assert_eq!(abi.events.len(), 3);
+}
+
+#[test]
+fn test_parser_reads_map_to_their_wire_types() {
+    let source_code = r#"
+        fn entry(program: Address, caller: Address, data: &[u8]) -> Result {
+            let mut parser = DataParser::new(data);
+            match parser.read_bytes(1)[0] {
+                0x01 => handle(caller, parser),
+                _ => vm_panic(b"unknown selector"),
+            }
+        }
+
+        fn handle(caller: Address, mut parser: DataParser) -> Result {
+            let tag = parser.read_bytes(4);
+            let id = parser.read::<u16>();
+            let owners: Vec<Address> = parser.read();
+            let first = parser.read_bytes(2)[0];
+            Result::new(true, 0)
+        }
+
+        entrypoint!(entry);
+    "#;
+
+    let abi = AbiGenerator::new(source_code.to_string()).generate().unwrap();
+    let kinds: Vec<(&str, ParamType)> =
+        abi.functions[0].inputs.iter().map(|i| (i.name.as_str(), i.kind.clone())).collect();
+    assert_eq!(
+        kinds,
+        vec![
+            ("tag", ParamType::FixedBytes(4)),
+            ("id", ParamType::Uint(16)),
+            ("owners", ParamType::Array(Box::new(ParamType::Address))),
+            // Two bytes are read even though only the first is kept
+            ("first", ParamType::FixedBytes(2)),
+        ]
+    );
+
+    // Neither the length nor the type can be known here
+    for read in ["parser.read_bytes(parser.remaining())", "parser.read()"] {
+        let source = source_code.replace("parser.read_bytes(4)", read);
+        let err = AbiGenerator::new(source).generate().unwrap_err();
+        assert!(err.message.starts_with("cannot derive an ABI type from `parser."), "{}", err);
+    }
+}
+
+#[test]
+fn test_router_found_through_helper_function() {
+    let source_code = r#"
+        const MINT: u8 = 0x07;
+
+        fn entry(program: Address, caller: Address, data: &[u8]) -> Result {
+            dispatch(program, caller, data)
+        }
+
+        fn dispatch(program: Address, caller: Address, data: &[u8]) -> Result {
+            let mut parser = DataParser::new(data);
+            let op = parser.read_bytes(1)[0];
+            match op {
+                MINT | 0x08 => mint(caller, parser),
+                _ => vm_panic(b"unknown selector"),
+            }
+        }
+
+        fn mint(caller: Address, mut parser: DataParser) -> Result {
+            let to = parser.read_address();
+            let amount = parser.read_u32() as u64;
+            Result::new(true, 0)
+        }
+
+        entrypoint!(entry);
+    "#;
+
+    let mut generator = AbiGenerator::new(source_code.to_string());
+    let abi = generator.generate().unwrap();
+
+    assert_eq!(abi.functions.len(), 2);
+    assert_eq!(abi.functions[0].selector, 0x07);
+    assert_eq!(abi.functions[1].selector, 0x08);
+    let mint = &abi.functions[0];
+    assert_eq!(mint.name, "mint");
+    assert_eq!(mint.inputs.len(), 2);
+    assert_eq!(mint.inputs[0].name, "to");
+    assert!(matches!(mint.inputs[0].kind, ParamType::Address));
+    assert_eq!(mint.inputs[1].name, "amount");
+    assert!(matches!(mint.inputs[1].kind, ParamType::Uint(32)));
+}
+
+#[test]
+fn test_contract_module_uses_function_attributes() {
+    let source_code = r#"
+        #[contract]
+        mod token {
+            event!(Burned { amount => u64 });
+
+            #[function(selector = 0x02)]
+            pub fn transfer(ctx: CallContext, to: Address, amount: u32) {}
+
+            #[function(selector = 0x05)]
+            pub fn balance_of(owner: Address) -> u32 { 0 }
+
+            #[function(selector = 0x06)]
+            pub fn allowance(caller: Address, spender: Address) -> u32 { 0 }
+
+            fn helper() {}
+        }
+    "#;
+
+    let mut generator = AbiGenerator::new(source_code.to_string());
+    let abi = generator.generate().unwrap();
+
+    assert_eq!(abi.functions.len(), 3);
+    assert_eq!(abi.functions[0].name, "transfer");
+    assert_eq!(abi.functions[0].selector, 2);
+    assert_eq!(abi.functions[0].inputs.len(), 2);
+    assert_eq!(abi.functions[1].outputs, vec![ParamType::Uint(32)]);
+    // The macro reads `caller` from the input, so it stays in the ABI
+    assert_eq!(abi.functions[2].inputs.len(), 2);
+    assert_eq!(abi.functions[2].inputs[0].name, "caller");
+    assert_eq!(abi.events[0].name, "Burned");
+}
+
+#[test]
+fn test_errors_report_file_and_line() {
+    let unresolved = r#"
+fn entry(program: Address, caller: Address, data: &[u8]) -> Result {
+    route(data, program, caller, |to, from, call| match call.selector {
+        0x01 => init(call.args),
+        MISSING => init(call.args),
+        _ => vm_panic(b"unknown selector"),
+    })
+}
+
+fn init(args: &[u8]) {}
+"#;
+    let err = AbiGenerator::new(unresolved.to_string())
+        .with_file_name("src/token.rs")
+        .generate()
+        .unwrap_err();
+    assert_eq!((err.file.as_str(), err.line, err.column), ("src/token.rs", 5, 9));
+    assert!(err.to_string().starts_with("src/token.rs:5:9: cannot resolve selector `MISSING`"));
+
+    let duplicate = unresolved.replace("MISSING", "0x01");
+    let err = AbiGenerator::new(duplicate).generate().unwrap_err();
+    assert_eq!(err.line, 5);
+    assert!(err.message.contains("already used by `init`"));
+
+    let bad_type = "event!(Moved { by => f64 });";
+    let err = AbiGenerator::new(bad_type.to_string()).generate().unwrap_err();
+    assert_eq!((err.line, err.column), (1, 22));
+    assert!(err.message.contains("event field `by`"));
+
+    let no_call = unresolved.replace("MISSING => init(call.args)", "0x02 => Result::new(true, 0)");
+    let err = AbiGenerator::new(no_call).generate().unwrap_err();
+    assert_eq!(err.line, 5);
+
+    let syntax = "fn broken( {";
+    assert!(AbiGenerator::new(syntax.to_string()).generate().is_err());
+}
+
+#[test]
+fn test_all_examples_generate() {
+    for (name, source) in [
+        ("allocator_demo.rs", include_str!("../../examples/src/allocator_demo.rs")),
+        ("call_program.rs", include_str!("../../examples/src/call_program.rs")),
+        ("ecdsa_verify.rs", include_str!("../../examples/src/ecdsa_verify.rs")),
+        ("lib_import.rs", include_str!("../../examples/src/lib_import.rs")),
+        ("logging.rs", include_str!("../../examples/src/logging.rs")),
+        ("multi_func.rs", include_str!("../../examples/src/multi_func.rs")),
+        ("native_transfer.rs", include_str!("../../examples/src/native_transfer.rs")),
+        ("simple.rs", include_str!("../../examples/src/simple.rs")),
+        ("storage.rs", include_str!("../../examples/src/storage.rs")),
+    ] {
+        let result = AbiGenerator::new(source.to_string()).with_file_name(name).generate();
+        assert!(result.is_ok(), "{}", result.unwrap_err());
+    }
+}
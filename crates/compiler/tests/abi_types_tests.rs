@@ -0,0 +1,161 @@
+use compiler::{AbiCodeGenerator, AbiGenerator, ContractAbi, FunctionAbi, FunctionParam, ParamType};
+
+fn array(elem: ParamType) -> ParamType {
+    ParamType::Array(Box::new(elem))
+}
+
+fn fixed_array(elem: ParamType, len: usize) -> ParamType {
+    ParamType::FixedArray(Box::new(elem), len)
+}
+
+#[test]
+fn test_type_names_round_trip() {
+    let cases = vec![
+        (ParamType::Uint(256), "uint256"),
+        (ParamType::Int(8), "int8"),
+        (ParamType::FixedBytes(32), "bytes32"),
+        (array(ParamType::Uint(32)), "uint32[]"),
+        (fixed_array(ParamType::Address, 4), "address[4]"),
+        (fixed_array(array(ParamType::Bool), 2), "bool[][2]"),
+        (ParamType::Tuple(vec![ParamType::Address, ParamType::Uint(64)]), "(address,uint64)"),
+        (
+            array(ParamType::Tuple(vec![ParamType::String, ParamType::Tuple(vec![ParamType::Int(16)])])),
+            "(string,(int16))[]",
+        ),
+    ];
+    for (kind, name) in cases {
+        assert_eq!(kind.to_json_string(), name);
+        assert_eq!(ParamType::from_json_string(name), Ok(kind));
+    }
+}
+
+#[test]
+fn test_unknown_types_are_rejected() {
+    for name in ["uint", "uint7", "uint512", "int0", "bytes0", "bytes01", "address[0]", "float", "(uint8", "u32"] {
+        let err = ParamType::from_json_string(name).unwrap_err();
+        assert!(err.contains("unknown ABI type"), "{}: {}", name, err);
+    }
+
+    let json = r#"{
+  "version": "1.0",
+  "functions": [
+    {
+      "name": "f",
+      "selector": 1,
+      "inputs": [{ "name": "x", "type": "uint33" }],
+      "outputs": []
+    }
+  ],
+  "events": []
+}"#;
+    assert!(ContractAbi::from_json(json).unwrap_err().contains("uint33"));
+}
+
+#[test]
+fn test_outputs_round_trip_through_json() {
+    let mut abi = ContractAbi::new();
+    abi.add_function(FunctionAbi {
+        name: "positions".to_string(),
+        selector: 7,
+        inputs: vec![FunctionParam { name: "owners".to_string(), kind: array(ParamType::Address) }],
+        outputs: vec![ParamType::Tuple(vec![ParamType::Int(64), ParamType::FixedBytes(4)])],
+    });
+    let parsed = ContractAbi::from_json(&abi.to_json()).unwrap();
+    let function = &parsed.functions[0];
+    assert_eq!((function.name.as_str(), function.selector), ("positions", 7));
+    assert_eq!(function.inputs[0].kind, abi.functions[0].inputs[0].kind);
+    assert_eq!(function.outputs, abi.functions[0].outputs);
+}
+
+#[test]
+fn test_fixed_size() {
+    assert_eq!(ParamType::Uint(256).fixed_size(), Some(32));
+    assert_eq!(fixed_array(ParamType::Address, 3).fixed_size(), Some(60));
+    assert_eq!(ParamType::Tuple(vec![ParamType::Bool, ParamType::Int(32)]).fixed_size(), Some(5));
+    assert_eq!(ParamType::Tuple(vec![ParamType::Bool, ParamType::Bytes]).fixed_size(), None);
+    assert_eq!(array(ParamType::Uint(8)).fixed_size(), None);
+}
+
+#[test]
+fn test_format_value_decodes_each_type() {
+    let mut data: Vec<u8> = Vec::new();
+    data.extend((-5i16).to_le_bytes());
+    data.extend([0xab, 0xcd]);
+    data.extend(2u32.to_le_bytes());
+    data.extend(10u32.to_le_bytes());
+    data.extend(20u32.to_le_bytes());
+    data.push(1);
+    data.extend(2u32.to_le_bytes());
+    data.extend(b"hi");
+    let mut wide = [0u8; 32];
+    wide[31] = 0x01;
+    data.extend(wide);
+
+    let mut cursor = data.as_slice();
+    let format = |kind: ParamType, cursor: &mut &[u8]| kind.format_value(cursor).unwrap();
+    assert_eq!(format(ParamType::Int(16), &mut cursor), "-5");
+    assert_eq!(format(ParamType::FixedBytes(2), &mut cursor), "0xabcd");
+    assert_eq!(format(array(ParamType::Uint(32)), &mut cursor), "[10, 20]");
+    assert_eq!(
+        format(ParamType::Tuple(vec![ParamType::Bool, ParamType::String]), &mut cursor),
+        "(true, \"hi\")"
+    );
+    assert_eq!(format(ParamType::Uint(256), &mut cursor), format!("0x01{}", "00".repeat(31)));
+    assert!(cursor.is_empty());
+
+    let mut short: &[u8] = &[1, 2, 3];
+    assert_eq!(ParamType::Uint(32).format_value(&mut short), Err("cannot decode uint32".to_string()));
+    let mut bad_bool: &[u8] = &[2];
+    assert!(ParamType::Bool.format_value(&mut bad_bool).is_err());
+}
+
+#[test]
+fn test_codegen_encodes_composite_inputs() {
+    let mut abi = ContractAbi::new();
+    abi.add_function(FunctionAbi {
+        name: "batch".to_string(),
+        selector: 3,
+        inputs: vec![
+            FunctionParam { name: "delta".to_string(), kind: ParamType::Int(8) },
+            FunctionParam { name: "recipients".to_string(), kind: array(ParamType::Address) },
+            FunctionParam {
+                name: "pair".to_string(),
+                kind: ParamType::Tuple(vec![ParamType::Uint(64), ParamType::FixedBytes(4)]),
+            },
+            FunctionParam { name: "memo".to_string(), kind: ParamType::Bytes },
+        ],
+        outputs: vec![],
+    });
+    let code = AbiCodeGenerator::new(abi, "Batch".to_string()).generate_rust_code();
+
+    assert!(code.contains("delta: i8"));
+    assert!(code.contains("recipients: &[Address]"));
+    assert!(code.contains("pair: (u64, [u8; 4],)"));
+    // Values are written with `Encode`, so their Rust types fix the layout
+    // Bytes carry their length in the last position too
+    for name in ["delta", "recipients", "pair", "memo"] {
+        assert!(code.contains(&format!("Encode::encode_into(&{}, &mut encoded, &mut offset);", name)));
+    }
+    assert!(!code.contains("copy_from_slice"));
+}
+
+#[test]
+fn test_generator_maps_composite_rust_types() {
+    let generator = AbiGenerator::new(String::new());
+    let parse = |ty: &str| generator.parse_param_type_from_str(ty);
+    assert_eq!(parse("i64"), Some(ParamType::Int(64)));
+    assert_eq!(parse("[u8; 32]"), Some(ParamType::FixedBytes(32)));
+    assert_eq!(parse("[Address; 2]"), Some(fixed_array(ParamType::Address, 2)));
+    assert_eq!(parse("Vec<u8>"), Some(ParamType::Bytes));
+    assert_eq!(parse("Vec<u32>"), Some(array(ParamType::Uint(32))));
+    assert_eq!(parse("&[bool]"), Some(array(ParamType::Bool)));
+    assert_eq!(parse("(u8, Address)"), Some(ParamType::Tuple(vec![ParamType::Uint(8), ParamType::Address])));
+    assert_eq!(parse("[u8; N]"), None);
+    assert_eq!(parse("f32"), None);
+
+    let func = generator
+        .parse_function_signature("fn quote(pool: [u8; 4]) -> (u64, i32)")
+        .unwrap();
+    assert_eq!(func.inputs[0].kind, ParamType::FixedBytes(4));
+    assert_eq!(func.outputs, vec![ParamType::Tuple(vec![ParamType::Uint(64), ParamType::Int(32)])]);
+}
@@ -37,7 +37,7 @@ fn my_vm_entry(_self_address: Address, _caller: Address, data: &[u8]) -> Result
     call_data[4..8].copy_from_slice(&second.to_le_bytes());
     
     // Call the simple contract using the generated client's call_main method
-    let ret = match simple_client.call_main(&_caller, &call_data) {
+    let ret = match simple_client.call_main(&call_data) {
         Some(result) => result,
         None => vm_panic(b"program call failed"),
     };
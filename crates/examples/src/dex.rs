@@ -5,9 +5,9 @@ extern crate program;
 
 use program::{
     call::call,
-    entrypoint, event, fire_event, persist_struct, DataParser, require, vm_panic, transfer,
+    entrypoint, event, fire_event, msg_value, persist_struct, DataParser, require, require_no_value, vm_panic, transfer,
     hex_address,
-    types::{address::Address, o::O, result::Result},
+    types::{address::Address, o::O, result::Result, Encode},
     Map,
 };
 
@@ -70,9 +70,20 @@ fn get_liquidity(owner: Address) -> u128 {
     }
 }
 
+// Pulls `amount` ERC20 from `owner` into the pool.
+// Cross-contract calls run as the DEX itself, so the owner must first
+// `approve` the DEX for at least `amount`; tokens are then taken from the
+// caller's own balance, never from whatever else the pool happens to hold.
+fn pull_tokens(erc20: &Erc20Contract, owner: Address, self_addr: Address, amount: u128) -> bool {
+    erc20
+        .transfer_from(owner, self_addr, amount as u32)
+        .map(|r| r.success)
+        .unwrap_or(false)
+}
+
 fn add_liquidity(self_addr: Address, caller: Address, mut parser: DataParser) -> Result {
-    // Adds liquidity by pulling both legs (native AM and ERC20) from the caller,
-    // mints LP shares proportional to the existing reserves, and emits an event.
+    // Adds liquidity from the AM attached to the call and the ERC20 the caller approved
+    // for the pool, mints LP shares proportional to the existing reserves, and emits an event.
     let erc20 = Erc20Contract::new(erc20_address());
     require(parser.remaining() >= 16, b"add: missing args");
     let am_in = parser.read_u64();
@@ -81,18 +92,12 @@ fn add_liquidity(self_addr: Address, caller: Address, mut parser: DataParser) ->
     require(token_in > 0, b"add: zero token");
     require(token_in <= u32::MAX as u128, b"add: token overflow");
 
-    // Collect AM from caller into the pool address (native balance increases).
-    require(transfer!(&self_addr, am_in), b"add: am transfer failed");
-
-    // Pull ERC20 from caller into the pool address.
-    let ok = erc20
-        .transfer(&caller, self_addr, token_in as u32)
-        .map(|r| r.success)
-        .unwrap_or(false);
-    require(ok, b"add: token transfer failed");
-
     let mut pool = load_pool();
 
+    // The AM leg arrives as the call's value, the ERC20 leg is pulled from the caller.
+    require(msg_value() == am_in, b"add: am value mismatch");
+    require(pull_tokens(&erc20, caller, self_addr, token_in), b"add: token transfer failed");
+
     let minted = if pool.total_liquidity == 0 {
         am_in as u128
     } else {
@@ -123,6 +128,7 @@ fn add_liquidity(self_addr: Address, caller: Address, mut parser: DataParser) ->
 
 fn remove_liquidity(self_addr: Address, caller: Address, mut parser: DataParser) -> Result {
     // Burns LP shares for AM + ERC20 payouts, updates reserves, and emits LiquidityRemoved.
+    require_no_value();
     let erc20 = Erc20Contract::new(erc20_address());
     require(parser.remaining() >= 8, b"remove: missing args");
     let shares = parser.read_u64() as u128;
@@ -148,13 +154,12 @@ fn remove_liquidity(self_addr: Address, caller: Address, mut parser: DataParser)
     // Pay out ERC20 tokens from pool balance.
     require(token_out <= u32::MAX as u128, b"remove: token overflow");
     let ok = erc20
-        .transfer(&self_addr, caller, token_out as u32)
+        .transfer(caller, token_out as u32)
         .map(|r| r.success)
         .unwrap_or(false);
     require(ok, b"remove: token transfer failed");
 
-    // Pay native AM out to the provider. Note: with the current host interface,
-    // native transfers debit the caller context.
+    // Pay native AM out to the provider from the pool's own balance.
     require(transfer!(&caller, am_out as u64), b"remove: am transfer failed");
 
     fire_event!(LiquidityRemoved::new(caller, am_out as u64, token_out as u64));
@@ -181,9 +186,8 @@ fn swap(self_addr: Address, caller: Address, mut parser: DataParser) -> Result {
 
     if direction == 0 {
         let am_in = amount;
-        // Collect AM into the pool.
-        let ok = transfer!(&self_addr, am_in);
-        require(ok, b"swap: am transfer failed");
+        // The AM being swapped is attached to the call.
+        require(msg_value() == am_in, b"swap: am value mismatch");
 
         let token_out = (am_in as u128 * pool.reserve_token) / (pool.reserve_am + am_in as u128);
         require(token_out > 0, b"swap: zero output");
@@ -195,7 +199,7 @@ fn swap(self_addr: Address, caller: Address, mut parser: DataParser) -> Result {
         pool.store();
 
         let ok = erc20
-            .transfer(&self_addr, caller, token_out as u32)
+            .transfer(caller, token_out as u32)
             .map(|r| r.success)
             .unwrap_or(false);
         require(ok, b"swap: token transfer failed");
@@ -207,15 +211,12 @@ fn swap(self_addr: Address, caller: Address, mut parser: DataParser) -> Result {
         res
     } else {
         // ERC20 -> AM
+        require_no_value();
         let token_in = amount as u128;
         require(token_in <= u32::MAX as u128, b"swap: token overflow");
 
-        // Pull ERC20 into the pool.
-        let ok = erc20
-            .transfer(&caller, self_addr, token_in as u32)
-            .map(|r| r.success)
-            .unwrap_or(false);
-        require(ok, b"swap: token transfer failed");
+        // The caller must have approved the pool for the tokens being swapped.
+        require(pull_tokens(&erc20, caller, self_addr, token_in), b"swap: token transfer failed");
 
         let am_out = (token_in * pool.reserve_am) / (pool.reserve_token + token_in);
         require(am_out > 0, b"swap: zero output");
@@ -25,7 +25,15 @@ event!(Transfer {
     value => u32,
 });
 
+event!(Approval {
+    owner => Address,
+    spender => Address,
+    value => u32,
+});
+
 Map!(Balances);
+// owner => spender => remaining allowance
+Map!(Allowances);
 
 unsafe fn main_entry(program: Address, caller: Address, data: &[u8]) -> Result {   
     route(data, program, caller, 
@@ -41,12 +49,34 @@ unsafe fn main_entry(program: Address, caller: Address, data: &[u8]) -> Result {
             transfer(caller, to, amount);
             Result::new(true, 0)
         },
+        0x03 => {
+            let mut parser = DataParser::new(call.args);
+            let spender = parser.read_address();
+            let amount = parser.read_u32();
+            approve(caller, spender, amount);
+            Result::new(true, 0)
+        },
+        0x04 => {
+            let mut parser = DataParser::new(call.args);
+            let from = parser.read_address();
+            let to = parser.read_address();
+            let amount = parser.read_u32();
+            transfer_from(caller, from, to, amount);
+            Result::new(true, 0)
+        },
         0x05 => {
             let mut parser = DataParser::new(call.args);
             let owner = parser.read_address();
             let b = balance_of(owner);
             Result::with_u32(b)
         },
+        0x06 => {
+            let mut parser = DataParser::new(call.args);
+            let owner = parser.read_address();
+            let spender = parser.read_address();
+            let a = allowance(owner, spender);
+            Result::with_u32(a)
+        },
         _ => vm_panic(b"unknown selector"),
     })
 }
@@ -96,12 +126,43 @@ fn transfer(caller: Address, to: Address, amount: u32) {
         O::None => 0,
     };
     
-    Balances::set(caller, from_bal - amount);
+    // An emptied balance is deleted rather than stored as zero
+    if from_bal == amount {
+        Balances::remove(caller);
+    } else {
+        Balances::set(caller, from_bal - amount);
+    }
     Balances::set(to, to_bal + amount);
     
     fire_event!(Transfer::new(caller, to, amount));
 }
 
+fn approve(caller: Address, spender: Address, amount: u32) {
+    Allowances::at(caller).set(spender, amount);
+    fire_event!(Approval::new(caller, spender, amount));
+}
+
+fn transfer_from(caller: Address, from: Address, to: Address, amount: u32) {
+    let allowed = allowance(from, caller);
+    if allowed < amount {
+        vm_panic(b"allowance exceeded");
+    }
+
+    if allowed == amount {
+        Allowances::at(from).remove(caller);
+    } else {
+        Allowances::at(from).set(caller, allowed - amount);
+    }
+    transfer(from, to, amount);
+}
+
+fn allowance(owner: Address, spender: Address) -> u32 {
+    match Allowances::at(owner).get(spender) {
+        O::Some(a) => a,
+        O::None => 0,
+    }
+}
+
 fn balance_of(owner: Address) -> u32 {
     match Balances::get(owner) {
         O::Some(bal) => bal,
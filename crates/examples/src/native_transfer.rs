@@ -7,8 +7,8 @@ use program::{entrypoint, require, DataParser};
 use program::types::address::Address;
 use program::types::result::Result;
 
-/// Demonstrates transferring the native AM token from the caller to a target
-/// address using the VM's transfer syscall. The input payload is:
+/// Demonstrates transferring the native AM token held by this contract to a
+/// target address using the VM's transfer syscall. The input payload is:
 /// - 20 bytes: destination address
 /// - 8 bytes: amount (little-endian u64)
 fn transfer_entry(_self_address: Address, _caller: Address, data: &[u8]) -> Result {
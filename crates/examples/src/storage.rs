@@ -2,9 +2,10 @@
 #![no_main]
 
 extern crate program;
-use program::{entrypoint, types::result::Result, require};
+use program::{entrypoint, types::result::Result, require, StorageMap};
 use program::types::address::Address; 
 use program::persist_struct;
+use program::{StorageSet, StorageVec};
 
 // Struct 1: User profile
 persist_struct!(User {
@@ -19,7 +20,17 @@ persist_struct!(Config {
     timeout_ms: u64,
 });
 
-fn my_vm_entry(_self_address: Address, _caller: Address, _data: &[u8]) -> Result {
+// Collections
+static SCORES: StorageVec<u64> = StorageVec::new("Scores");
+static MEMBERS: StorageSet<Address> = StorageSet::new("Members");
+
+fn my_vm_entry(self_address: Address, caller: Address, data: &[u8]) -> Result {
+    // Writing past the end of a vector must fail the call
+    if data == [0x01] {
+        SCORES.set(SCORES.len(), &0);
+        return Result::new(true, 0);
+    }
+
     // --- User ---
     require(User::load().is_none() == true, b"user already exists");
     let mut user = User{id: 1000, active: false, level: 3};
@@ -54,6 +65,36 @@ fn my_vm_entry(_self_address: Address, _caller: Address, _data: &[u8]) -> Result
     require(reloaded_config.retries == 13, b"config retries must be 13");
     require(reloaded_config.timeout_ms == 100000, b"config timeout_ms must be 100000");
 
+    // --- StorageVec ---
+    require(SCORES.is_empty(), b"scores already exist");
+    SCORES.push(&10);
+    SCORES.push(&20);
+    SCORES.push(&30);
+    SCORES.set(1, &25);
+    require(SCORES.len() == 3, b"scores len must be 3");
+    require(SCORES.pop().expect("scores empty") == 30, b"popped score must be 30");
+    require(SCORES.get(1).expect("score not found") == 25, b"score 1 must be 25");
+    require(SCORES.get(2).is_none(), b"score 2 must be gone");
+    require(SCORES.iter().sum::<u64>() == 35, b"scores must sum to 35");
+    SCORES.pop();
+    SCORES.pop();
+    require(SCORES.pop().is_none(), b"pop on empty must return none");
+    // Popping the last element removes the length header too
+    require(SCORES.is_empty() && StorageMap::iter(b"Scores").count() == 0, b"scores must be gone");
+
+    // --- StorageSet ---
+    require(MEMBERS.insert(caller), b"caller must be new");
+    require(!MEMBERS.insert(caller), b"caller must not be added twice");
+    require(MEMBERS.insert(self_address), b"self must be new");
+    require(MEMBERS.contains(caller), b"caller must be a member");
+    require(MEMBERS.remove(self_address), b"self must be removed");
+    require(!MEMBERS.contains(self_address), b"self must not be a member");
+    require(MEMBERS.len() == 1 && MEMBERS.iter().count() == 1, b"one member must remain");
+    require(!MEMBERS.remove(self_address), b"self must not be removed twice");
+    require(MEMBERS.len() == 1, b"failed remove must keep len");
+    require(MEMBERS.remove(caller), b"caller must be removed");
+    require(MEMBERS.is_empty() && StorageMap::iter(b"Members").count() == 0, b"members must be gone");
+
     Result::new(true, 0)
 }
 
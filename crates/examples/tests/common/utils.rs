@@ -50,17 +50,8 @@ pub fn load_abi_from_file<P: AsRef<Path>>(path: P) -> Option<Vec<EventAbi>> {
             let param_type_str = input.get("type")?.as_str()?;
             let indexed = input.get("indexed").and_then(|v| v.as_bool()).unwrap_or(false);
 
-            let param_type = match param_type_str {
-                "address" => ParamType::Address,
-                "uint32" => ParamType::Uint(32),
-                "uint64" => ParamType::Uint(64),
-                "uint128" => ParamType::Uint(128),
-                "uint256" => ParamType::Uint(256),
-                "bool" => ParamType::Bool,
-                "string" => ParamType::String,
-                "bytes" => ParamType::Bytes,
-                _ => panic!("❌ Unsupported parameter type: {}", param_type_str),
-            };
+            let param_type = ParamType::from_json_string(param_type_str)
+                .unwrap_or_else(|e| panic!("❌ Unsupported parameter type: {}", e));
 
             params.push(EventParam {
                 name: param_name,
@@ -13,6 +13,7 @@ mod ecdsa;
 use avm::transaction::{TransactionType, TransactionBundle, Transaction};
 use avm::router::{encode_router_calls, HostFuncCall};
 use once_cell::sync::Lazy;
+use types::Encode;
 use compiler::EventAbi;
 pub use ecdsa::{build_ecdsa_payload, ECDSA_HASH, ECDSA_SK_BYTES};
 pub use test_runner::TestRunner;
@@ -126,21 +127,87 @@ pub static TEST_CASES: Lazy<Vec<TestCase<'static>>> = Lazy::new(|| {
                     to: to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d1"),
                     from: to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d0"),
                     data: encode_router_calls(&[
-                        HostFuncCall {
-                            selector: 0x01, // initialize
-                            args: (|| {
-                                // max supply
-                                let max_supply: u32 = 100000000; // 100 million
-                                let mut max_supply_bytes: Vec<u8> = max_supply.to_le_bytes().to_vec();
-
-                                // decimals
-                                let decimals: u8 = 18;
+                        // initialize(max_supply: 100 million, decimals: 18)
+                        HostFuncCall::new(0x01, &(100_000_000u32, 18u8)),
+                    ]),
+                    value: 0,
+                    nonce: 1,
+                },
+                Transaction {
+                    tx_type: TransactionType::ProgramCall,
+                    to: to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d1"),
+                    from: to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d0"),
+                    data: encode_router_calls(&[
+                        // transfer 50 million tokens
+                        HostFuncCall::new(
+                            0x02,
+                            &(to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d2"), 50_000_000u32),
+                        ),
+                    ]),
+                    value: 0,
+                    nonce: 2,
+                },
+                Transaction {
+                    tx_type: TransactionType::ProgramCall,
+                    to: to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d1"),
+                    from: to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d0"),
+                    data: encode_router_calls(&[
+                        // balance_of the original caller
+                        HostFuncCall::new(0x05, &to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d0")),
+                    ]),
+                    value: 0,
+                    nonce: 3,
+                },
+            ]),
+        },
 
-                                // combine
-                                max_supply_bytes.extend(vec![decimals]);
-                                max_supply_bytes
-                            })(),
-                        }
+        TestCase {
+            name: "erc20 allowance",
+            expected_success: true,
+            expected_error_code: 0,
+            expected_data: Some(100u32.to_encoded_bytes()), // 300 approved, 200 spent
+            abi: load_abi_from_file("bin/erc20.abi.json"),
+            address_mappings: vec![
+                ("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d1", "erc20"),
+            ],
+            bundle: TransactionBundle::new(vec![
+                Transaction {
+                    tx_type: TransactionType::CreateAccount,
+                    to: to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d1"),
+                    from: to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d0"),
+                    data: get_program_code("erc20"),
+                    value: 0,
+                    nonce: 0,
+                },
+                Transaction {
+                    tx_type: TransactionType::ProgramCall,
+                    to: to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d1"),
+                    from: to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d0"),
+                    data: encode_router_calls(&[
+                        // init(supply, decimals)
+                        HostFuncCall::new(0x01, &(1_000u32, 0u8)),
+                    ]),
+                    value: 0,
+                    nonce: 1,
+                },
+                Transaction {
+                    tx_type: TransactionType::ProgramCall,
+                    to: to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d1"),
+                    from: to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d0"),
+                    data: encode_router_calls(&[
+                        // approve(spender, 300)
+                        HostFuncCall::new(0x03, &(to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d2"), 300u32)),
+                    ]),
+                    value: 0,
+                    nonce: 2,
+                },
+                Transaction {
+                    tx_type: TransactionType::ProgramCall,
+                    to: to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d1"),
+                    from: to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d2"),
+                    data: encode_router_calls(&[
+                        // spender moves 200 from owner to recipient
+                        HostFuncCall::new(0x04, &(to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d0"), to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d3"), 200u32)),
                     ]),
                     value: 0,
                     nonce: 0,
@@ -150,22 +217,183 @@ pub static TEST_CASES: Lazy<Vec<TestCase<'static>>> = Lazy::new(|| {
                     to: to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d1"),
                     from: to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d0"),
                     data: encode_router_calls(&[
-                        HostFuncCall {
-                            selector: 0x02, // transfer
-                            args: (|| {
-                                // to address (20 bytes)
-                                let to_addr = to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d2");
-                                let mut args = to_addr.0.to_vec();
+                        // allowance(owner, spender)
+                        HostFuncCall::new(0x06, &(to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d0"), to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d2"))),
+                    ]),
+                    value: 0,
+                    nonce: 3,
+                },
+            ]),
+        },
 
-                                // amount (4 bytes)
-                                let amount: u32 = 50000000; // 50 million tokens
-                                args.extend(amount.to_le_bytes());
+        TestCase {
+            name: "erc20 allowance spent",
+            expected_success: true,
+            expected_error_code: 0,
+            expected_data: Some(0u32.to_encoded_bytes()), // the exhausted allowance is removed
+            abi: load_abi_from_file("bin/erc20.abi.json"),
+            address_mappings: vec![
+                ("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d1", "erc20"),
+            ],
+            bundle: TransactionBundle::new(vec![
+                Transaction {
+                    tx_type: TransactionType::CreateAccount,
+                    to: to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d1"),
+                    from: to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d0"),
+                    data: get_program_code("erc20"),
+                    value: 0,
+                    nonce: 0,
+                },
+                Transaction {
+                    tx_type: TransactionType::ProgramCall,
+                    to: to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d1"),
+                    from: to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d0"),
+                    data: encode_router_calls(&[
+                        // init(supply, decimals)
+                        HostFuncCall::new(0x01, &(1_000u32, 0u8)),
+                    ]),
+                    value: 0,
+                    nonce: 1,
+                },
+                Transaction {
+                    tx_type: TransactionType::ProgramCall,
+                    to: to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d1"),
+                    from: to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d0"),
+                    data: encode_router_calls(&[
+                        // approve(spender, 300)
+                        HostFuncCall::new(0x03, &(to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d2"), 300u32)),
+                    ]),
+                    value: 0,
+                    nonce: 2,
+                },
+                Transaction {
+                    tx_type: TransactionType::ProgramCall,
+                    to: to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d1"),
+                    from: to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d2"),
+                    data: encode_router_calls(&[
+                        // spender moves 200 from owner to recipient
+                        HostFuncCall::new(0x04, &(to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d0"), to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d3"), 200u32)),
+                    ]),
+                    value: 0,
+                    nonce: 0,
+                },
+                Transaction {
+                    tx_type: TransactionType::ProgramCall,
+                    to: to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d1"),
+                    from: to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d2"),
+                    data: encode_router_calls(&[
+                        // spender moves 100 from owner to recipient
+                        HostFuncCall::new(0x04, &(to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d0"), to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d3"), 100u32)),
+                    ]),
+                    value: 0,
+                    nonce: 1,
+                },
+                Transaction {
+                    tx_type: TransactionType::ProgramCall,
+                    to: to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d1"),
+                    from: to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d0"),
+                    data: encode_router_calls(&[
+                        // allowance(owner, spender)
+                        HostFuncCall::new(0x06, &(to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d0"), to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d2"))),
+                    ]),
+                    value: 0,
+                    nonce: 3,
+                },
+            ]),
+        },
 
-                                args
-                            })(),
-                        }
+        TestCase {
+            name: "erc20 allowance recipient",
+            expected_success: true,
+            expected_error_code: 0,
+            expected_data: Some(300u32.to_encoded_bytes()), // both spends reached the recipient
+            abi: load_abi_from_file("bin/erc20.abi.json"),
+            address_mappings: vec![
+                ("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d1", "erc20"),
+            ],
+            bundle: TransactionBundle::new(vec![
+                Transaction {
+                    tx_type: TransactionType::CreateAccount,
+                    to: to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d1"),
+                    from: to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d0"),
+                    data: get_program_code("erc20"),
+                    value: 0,
+                    nonce: 0,
+                },
+                Transaction {
+                    tx_type: TransactionType::ProgramCall,
+                    to: to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d1"),
+                    from: to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d0"),
+                    data: encode_router_calls(&[
+                        // init(supply, decimals)
+                        HostFuncCall::new(0x01, &(1_000u32, 0u8)),
+                    ]),
+                    value: 0,
+                    nonce: 1,
+                },
+                Transaction {
+                    tx_type: TransactionType::ProgramCall,
+                    to: to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d1"),
+                    from: to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d0"),
+                    data: encode_router_calls(&[
+                        // approve(spender, 300)
+                        HostFuncCall::new(0x03, &(to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d2"), 300u32)),
+                    ]),
+                    value: 0,
+                    nonce: 2,
+                },
+                Transaction {
+                    tx_type: TransactionType::ProgramCall,
+                    to: to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d1"),
+                    from: to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d2"),
+                    data: encode_router_calls(&[
+                        // spender moves 200 from owner to recipient
+                        HostFuncCall::new(0x04, &(to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d0"), to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d3"), 200u32)),
+                    ]),
+                    value: 0,
+                    nonce: 0,
+                },
+                Transaction {
+                    tx_type: TransactionType::ProgramCall,
+                    to: to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d1"),
+                    from: to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d2"),
+                    data: encode_router_calls(&[
+                        // spender moves 100 from owner to recipient
+                        HostFuncCall::new(0x04, &(to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d0"), to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d3"), 100u32)),
+                    ]),
+                    value: 0,
+                    nonce: 1,
+                },
+                Transaction {
+                    tx_type: TransactionType::ProgramCall,
+                    to: to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d1"),
+                    from: to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d0"),
+                    data: encode_router_calls(&[
+                        // balance_of(recipient)
+                        HostFuncCall::new(0x05, &to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d3")),
                     ]),
                     value: 0,
+                    nonce: 3,
+                },
+            ]),
+        },
+
+        TestCase {
+            name: "erc20 allowance exceeded",
+            expected_success: false,
+            expected_error_code: 0xFFFF_0006, // guest panic: "allowance exceeded"
+            expected_data: None,
+            abi: load_abi_from_file("bin/erc20.abi.json"),
+            address_mappings: vec![
+                ("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d1", "erc20"),
+            ],
+            bundle: TransactionBundle::new(vec![
+                Transaction {
+                    tx_type: TransactionType::CreateAccount,
+                    to: to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d1"),
+                    from: to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d0"),
+                    data: get_program_code("erc20"),
+                    value: 0,
                     nonce: 0,
                 },
                 Transaction {
@@ -173,14 +401,30 @@ pub static TEST_CASES: Lazy<Vec<TestCase<'static>>> = Lazy::new(|| {
                     to: to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d1"),
                     from: to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d0"),
                     data: encode_router_calls(&[
-                        HostFuncCall {
-                            selector: 0x05, // balance_of
-                            args: (|| {
-                                // check balance of the original caller (d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d0)
-                                let owner_addr = to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d0");
-                                owner_addr.0.to_vec()
-                            })(),
-                        }
+                        // init(supply, decimals)
+                        HostFuncCall::new(0x01, &(1_000u32, 0u8)),
+                    ]),
+                    value: 0,
+                    nonce: 1,
+                },
+                Transaction {
+                    tx_type: TransactionType::ProgramCall,
+                    to: to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d1"),
+                    from: to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d0"),
+                    data: encode_router_calls(&[
+                        // approve(spender, 100)
+                        HostFuncCall::new(0x03, &(to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d2"), 100u32)),
+                    ]),
+                    value: 0,
+                    nonce: 2,
+                },
+                Transaction {
+                    tx_type: TransactionType::ProgramCall,
+                    to: to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d1"),
+                    from: to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d2"),
+                    data: encode_router_calls(&[
+                        // spender moves 101 from owner to recipient
+                        HostFuncCall::new(0x04, &(to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d0"), to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d3"), 101u32)),
                     ]),
                     value: 0,
                     nonce: 0,
@@ -213,19 +457,15 @@ pub static TEST_CASES: Lazy<Vec<TestCase<'static>>> = Lazy::new(|| {
                     to: to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d1"),
                     data: get_program_code("simple"),
                     value: 0,
-                    nonce: 0,
+                    nonce: 1,
                 },
                 Transaction {
                     tx_type: TransactionType::ProgramCall,
                     to: to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d0"),
                     from: to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d0"),
-                    data: (|| {
-                        let mut data = to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d1").0.to_vec();
-                        data.extend(vec![100, 0, 0, 0, 42, 0, 0, 0]);
-                        data
-                    })(),
+                    data: (to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d1"), 100u32, 42u32).to_encoded_bytes(),
                     value: 0,
-                    nonce: 0,
+                    nonce: 2,
                 },
             ]),
         },
@@ -254,8 +494,37 @@ pub static TEST_CASES: Lazy<Vec<TestCase<'static>>> = Lazy::new(|| {
                     from: to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d0"),
                     data: vec![],
                     value: 0,
+                    nonce: 1,
+                },
+            ]),
+        },
+
+        TestCase {
+            name: "storage vec set out of bounds",
+            expected_success: false,
+            expected_error_code: 0xFFFF_0006, // guest panic: "index out of bounds"
+            expected_data: None,
+            abi: None,
+            address_mappings: vec![
+                ("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d0", "storage"),
+            ],
+            bundle: TransactionBundle::new(vec![
+                Transaction {
+                    tx_type: TransactionType::CreateAccount,
+                    to: to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d0"),
+                    from: to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d0"),
+                    data: get_program_code("storage"),
+                    value: 0,
                     nonce: 0,
                 },
+                Transaction {
+                    tx_type: TransactionType::ProgramCall,
+                    to: to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d0"),
+                    from: to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d0"),
+                    data: vec![0x01],
+                    value: 0,
+                    nonce: 1,
+                },
             ]),
         },
 
@@ -286,7 +555,7 @@ pub static TEST_CASES: Lazy<Vec<TestCase<'static>>> = Lazy::new(|| {
                         42, 0, 0, 0,      // second u64 = 42
                     ],
                     value: 0,
-                    nonce: 0,
+                    nonce: 1,
                 },
             ]),
         },
@@ -314,16 +583,10 @@ pub static TEST_CASES: Lazy<Vec<TestCase<'static>>> = Lazy::new(|| {
                     to: to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d0"),
                     from: to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d0"),
                     data: encode_router_calls(&[
-                        HostFuncCall {
-                            selector: 0x01,
-                            args: vec![
-                                100, 0, 0, 0, // first = 100
-                                42, 0, 0, 0,  // second = 42
-                            ],
-                        }
+                        HostFuncCall::new(0x01, &(100u32, 42u32)),
                     ]),
                     value: 0,
-                    nonce: 0,
+                    nonce: 1,
                 },
             ]),
         },
@@ -361,7 +624,7 @@ pub static TEST_CASES: Lazy<Vec<TestCase<'static>>> = Lazy::new(|| {
                         92, 0, 0, 0,
                     ],
                     value: 0,
-                    nonce: 0,
+                    nonce: 1,
                 },
             ]),
         },
@@ -389,10 +652,7 @@ pub static TEST_CASES: Lazy<Vec<TestCase<'static>>> = Lazy::new(|| {
             name: "guest transfer syscall",
             expected_success: true,
             expected_error_code: 0,
-            expected_data: Some({
-                let mut v = 42u128.to_le_bytes().to_vec();
-                v
-            }),
+            expected_data: Some(42u128.to_encoded_bytes()),
             abi: None,
             address_mappings: vec![
                 ("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d4", "native_transfer"),
@@ -406,17 +666,22 @@ pub static TEST_CASES: Lazy<Vec<TestCase<'static>>> = Lazy::new(|| {
                     value: 0,
                     nonce: 0,
                 },
+                // Fund the contract; guest transfers pay from its own balance
+                Transaction {
+                    tx_type: TransactionType::Transfer,
+                    to: to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d4"),
+                    from: to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d3"),
+                    data: vec![],
+                    value: 100,
+                    nonce: 1,
+                },
                 Transaction {
                     tx_type: TransactionType::ProgramCall,
                     to: to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d4"),
                     from: to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d3"),
-                    data: (|| {
-                        let mut data = to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d0").0.to_vec();
-                        data.extend_from_slice(&42u64.to_le_bytes());
-                        data
-                    })(),
+                    data: (to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d0"), 42u64).to_encoded_bytes(),
                     value: 0,
-                    nonce: 1,
+                    nonce: 2,
                 },
             ]),
         },
@@ -425,12 +690,7 @@ pub static TEST_CASES: Lazy<Vec<TestCase<'static>>> = Lazy::new(|| {
             name: "dex amm",
             expected_success: true,
             expected_error_code: 0,
-            expected_data: Some({
-                let mut buf = Vec::new();
-                buf.extend_from_slice(&101000u128.to_le_bytes());
-                buf.extend_from_slice(&495050u128.to_le_bytes());
-                buf
-            }),
+            expected_data: Some((101000u128, 495050u128).to_encoded_bytes()),
             abi: load_abis_from_files(&["bin/erc20.abi.json", "bin/dex.abi.json"]),
             address_mappings: vec![
                 ("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d1", "erc20"),
@@ -450,16 +710,8 @@ pub static TEST_CASES: Lazy<Vec<TestCase<'static>>> = Lazy::new(|| {
                     to: to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d1"),
                     from: to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d3"),
                     data: encode_router_calls(&[
-                        HostFuncCall {
-                            selector: 0x01, // init
-                            args: (|| {
-                                let mut args = Vec::new();
-                                let supply: u32 = 1_000_000;
-                                args.extend_from_slice(&supply.to_le_bytes());
-                                args.push(0); // decimals
-                                args
-                            })(),
-                        }
+                        // init(supply, decimals)
+                        HostFuncCall::new(0x01, &(1_000_000u32, 0u8)),
                     ]),
                     value: 0,
                     nonce: 1,
@@ -469,15 +721,8 @@ pub static TEST_CASES: Lazy<Vec<TestCase<'static>>> = Lazy::new(|| {
                     to: to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d1"),
                     from: to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d3"),
                     data: encode_router_calls(&[
-                        HostFuncCall {
-                            selector: 0x02, // transfer
-                            args: (|| {
-                                let mut args = to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d5").0.to_vec();
-                                let amount: u32 = 500_000;
-                                args.extend_from_slice(&amount.to_le_bytes());
-                                args
-                            })(),
-                        }
+                        // approve(dex, 500_000) so add_liquidity can pull the ERC20 leg
+                        HostFuncCall::new(0x03, &(to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d5"), 500_000u32)),
                     ]),
                     value: 0,
                     nonce: 2,
@@ -494,40 +739,26 @@ pub static TEST_CASES: Lazy<Vec<TestCase<'static>>> = Lazy::new(|| {
                     tx_type: TransactionType::ProgramCall,
                     to: to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d5"),
                     from: to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d3"),
-                    data: {
-                        let mut data = Vec::new();
-                        data.push(0x01); // add liquidity
-                        data.extend_from_slice(&100_000u64.to_le_bytes()); // AM in
-                        data.extend_from_slice(&500_000u64.to_le_bytes()); // token target
-                        data
-                    },
-                    value: 0,
+                    // add liquidity(AM in, token target)
+                    data: (0x01u8, 100_000u64, 500_000u64).to_encoded_bytes(),
+                    value: 100_000, // AM leg
                     nonce: 4,
                 },
                 Transaction {
                     tx_type: TransactionType::ProgramCall,
                     to: to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d5"),
                     from: to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d2"),
-                    data: {
-                        let mut data = Vec::new();
-                        data.push(0x03); // swap
-                        data.push(0x00); // am -> token
-                        data.extend_from_slice(&1_000u64.to_le_bytes());
-                        data
-                    },
-                    value: 0,
+                    // swap(am -> token, amount)
+                    data: (0x03u8, 0x00u8, 1_000u64).to_encoded_bytes(),
+                    value: 1_000, // AM being swapped
                     nonce: 0,
                 },
                 Transaction {
                     tx_type: TransactionType::ProgramCall,
                     to: to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d5"),
                     from: to_address("d5a3c7f85d2b6e91fa78cd3210b45f6ae913d0d3"),
-                    data: {
-                        let mut data = Vec::new();
-                        data.push(0x02); // remove liquidity
-                        data.extend_from_slice(&100_000u64.to_le_bytes());
-                        data
-                    },
+                    // remove liquidity(shares)
+                    data: (0x02u8, 100_000u64).to_encoded_bytes(),
                     value: 0,
                     nonce: 5,
                 },
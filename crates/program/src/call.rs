@@ -1,7 +1,19 @@
 use types::address::Address;
 use types::result::Result;
 
-pub fn call(from: &Address, to: &Address, input_data: &[u8]) -> Option<Result> {
+/// Calls the program at `to` with `input_data`.
+///
+/// The callee sees the currently executing program as its caller; the host
+/// fills that in, so there is no way to call on behalf of another address.
+pub fn call(to: &Address, input_data: &[u8]) -> Option<Result> {
+    call_with_value(to, input_data, 0)
+}
+
+/// Calls the program at `to` with `input_data`, attaching `value` native
+/// tokens paid from the executing program's own balance.
+///
+/// The value is handed back if the callee fails or the balance cannot cover it.
+pub fn call_with_value(to: &Address, input_data: &[u8], value: u64) -> Option<Result> {
     #[cfg(target_arch = "riscv32")]
     unsafe {
         let mut result_ptr: u32;
@@ -9,9 +21,10 @@ pub fn call(from: &Address, to: &Address, input_data: &[u8]) -> Option<Result> {
             "li a7, 5",        // syscall ID for call_contract
             "ecall",
             in("x11") to.0.as_ptr(), // a1
-            in("x12") from.0.as_ptr(), // a2
-            in("x13") input_data.as_ptr(), // a3
-            in("x14") input_data.len(), // a4
+            in("x12") input_data.as_ptr(), // a2
+            in("x13") input_data.len(), // a3
+            in("x14") value as u32, // a4
+            in("x15") (value >> 32) as u32, // a5
             out("x10") result_ptr, // a0
         );
 
@@ -25,6 +38,7 @@ pub fn call(from: &Address, to: &Address, input_data: &[u8]) -> Option<Result> {
     #[cfg(not(target_arch = "riscv32"))]
     {
         // For non-RISC-V targets, return None
+        let _ = (to, input_data, value);
         None
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,125 @@
+//! Runtime support for the code generated by `#[contract]`.
+//!
+//! The macro only emits calls into this module: `ContractArg` decodes each
+//! argument from the call input, `IntoResult` packs each return value into a
+//! `Result`, and `CallContext` hands the call's addresses to functions that
+//! ask for them.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::{require, DataParser};
+use types::address::Address;
+use types::result::{Result, RESULT_DATA_SIZE};
+use types::{Decode, Encode};
+
+/// Addresses of the current call, for contract functions that need them.
+///
+/// Declare it as the first argument of a `#[function]`; it is filled in by
+/// the dispatcher and is not part of the function's ABI.
+#[derive(Clone, Copy, Debug)]
+pub struct CallContext {
+    /// Address of the contract being called
+    pub program: Address,
+    /// Address of the account that made the call
+    pub caller: Address,
+}
+
+/// A type that can be read as a contract function argument.
+///
+/// ENCODING: every argument is read with its `types::Decode` encoding, the
+/// same one clients write with `types::Encode`. `&[u8]` and `&str` carry a
+/// u32 length like any other slice, so they can appear in any position.
+pub trait ContractArg<'a>: Sized {
+    fn read(parser: &mut DataParser<'a>) -> Self;
+}
+
+impl<'a, T: Decode<'a>> ContractArg<'a> for T {
+    fn read(parser: &mut DataParser<'a>) -> Self {
+        parser.read()
+    }
+}
+
+/// Rejects input left over after the last argument.
+pub fn finish_args(parser: &DataParser<'_>) {
+    require(parser.remaining() == 0, b"too many arguments");
+}
+
+/// A contract function's return value, converted to the call `Result`.
+///
+/// `()` is a plain success and a `Result` is passed through untouched. Other
+/// values succeed with their `types::Encode` encoding as the result data, so
+/// a `u32` reads back with `Result::get_u32_data`.
+pub trait IntoResult {
+    fn into_result(self) -> Result;
+}
+
+impl IntoResult for () {
+    fn into_result(self) -> Result {
+        Result::new(true, 0)
+    }
+}
+
+impl IntoResult for Result {
+    fn into_result(self) -> Result {
+        self
+    }
+}
+
+/// A successful `Result` carrying the encoding of `value`.
+fn encoded_result<T: Encode>(value: &T) -> Result {
+    require(value.encoded_len() <= RESULT_DATA_SIZE, b"result data too large");
+    let mut data = [0u8; RESULT_DATA_SIZE];
+    let mut len = 0;
+    value.encode_into(&mut data, &mut len);
+    Result::new_with_data(true, 0, &data[..len])
+}
+
+macro_rules! impl_into_result_by_encode {
+    ($($ty:ty),*) => {
+        $(
+            impl IntoResult for $ty {
+                fn into_result(self) -> Result {
+                    encoded_result(&self)
+                }
+            }
+        )*
+    };
+}
+
+impl_into_result_by_encode!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128, bool, Address, String);
+
+impl<T: Encode, const N: usize> IntoResult for [T; N] {
+    fn into_result(self) -> Result {
+        encoded_result(&self)
+    }
+}
+
+impl<T: Encode> IntoResult for Vec<T> {
+    fn into_result(self) -> Result {
+        encoded_result(&self)
+    }
+}
+
+impl<T: Encode + ?Sized> IntoResult for &T {
+    fn into_result(self) -> Result {
+        encoded_result(&self)
+    }
+}
+
+macro_rules! impl_into_result_for_tuple {
+    ($($name:ident),+) => {
+        impl<$($name: Encode),+> IntoResult for ($($name,)+) {
+            fn into_result(self) -> Result {
+                encoded_result(&self)
+            }
+        }
+    };
+}
+
+impl_into_result_for_tuple!(A);
+impl_into_result_for_tuple!(A, B);
+impl_into_result_for_tuple!(A, B, C);
+impl_into_result_for_tuple!(A, B, C, D);
+impl_into_result_for_tuple!(A, B, C, D, E);
+impl_into_result_for_tuple!(A, B, C, D, E, F);
@@ -1,20 +1,3 @@
-// use types::SerializeField;
-// use crate::vm_panic;
-
-// ——— Blanket fallback: any `T: BytesSerialization` —————
-
-// impl<T: BytesSerialization> SerializeField for T {
-//     fn serialize_field(&self, buf: &mut [u8], offset: &mut usize) {
-//         // let bytes = self.as_bytes();
-//         // let len = bytes.len();
-//         // if *offset + len <= buf.len() {
-//         //     buf[*offset..*offset + len].copy_from_slice(bytes);
-//         //     *offset += len;
-//         // }
-//         vm_panic(b"SerializeField not implemented for this type");
-//     }
-// }
-
 // ——— The `event!` macro —————————————————————————————
 
 #[macro_export]
@@ -49,7 +32,8 @@ macro_rules! event {
         }
 
         impl $name {
-            /// Serialize all fields (including the 32-byte id) into `buf`.
+            /// Serialize all fields (including the 32-byte id) into `buf`,
+            /// each in its ABI encoding (see `types::Encode`).
             pub fn write_bytes(&self, buf: &mut [u8]) -> usize {
                 let mut offset = 0;
                 // first serialize the id
@@ -57,7 +41,7 @@ macro_rules! event {
                 offset += 32;
                 // then each declared field
                 $(
-                    <$ftype as $crate::types::SerializeField>::serialize_field(
+                    <$ftype as $crate::types::Encode>::encode_into(
                         &self.$fname,
                         buf,
                         &mut offset,
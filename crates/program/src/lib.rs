@@ -3,7 +3,7 @@
 #[cfg(test)]
 extern crate std;
 
-extern crate alloc;
+pub extern crate alloc; // Allow `$crate::alloc` in macros
 
 /* --------------------------------- Imports --------------------------------- */
 
@@ -19,11 +19,24 @@ pub use integers::*;
 pub mod transfer;
 pub use transfer::transfer;
 pub use transfer::balance;
+pub use transfer::{msg_value, require_no_value};
 
 // StorageMap
 pub mod storage_map;
 pub use storage_map::StorageMap;
 pub use storage_map::StorageKey;
+pub use storage_map::{KeyBuf, StorageEntry, StorageIter, SubMap};
+
+// Encoded storage values
+#[macro_use]
+pub mod storage_value;
+pub use storage_value::StorageValue;
+
+// Persistent collections
+pub mod storage_vec;
+pub use storage_vec::StorageVec;
+pub mod storage_set;
+pub use storage_set::StorageSet;
 
 // Events
 pub mod event;
@@ -54,6 +67,11 @@ pub use storage::PERSISTENT_DOMAIN; // Allow `$crate::PERSISTENT_DOMAIN` in macr
 pub mod router;
 pub use router::{decode_calls, route, FuncCall};
 
+// Contract macro support
+pub mod contract;
+pub use contract::CallContext;
+pub use program_macros::{contract, function};
+
 // Panic handling
 mod panic;
 pub use panic::vm_panic;
@@ -1,6 +1,6 @@
 //! Simple parser for reading typed values from a byte slice.
-use core::convert::TryInto;
 use types::address::Address;
+use types::Decode;
 use crate::vm_panic;
 
 pub struct DataParser<'a> {
@@ -117,24 +117,32 @@ impl<'a> DataParser<'a> {
         Address(buf)
     }
 
+    /// Reads one ABI-encoded value (see `types::Encode`).
+    pub fn read<T: Decode<'a>>(&mut self) -> T {
+        let mut rest = &self.data[self.offset.min(self.data.len())..];
+        let before = rest.len();
+        match T::decode(&mut rest) {
+            Some(value) => {
+                self.offset += before - rest.len();
+                value
+            }
+            None => vm_panic(b"invalid input data"),
+        }
+    }
+
     pub fn read_u32(&mut self) -> u32 {
-        let bytes: [u8; 4] = self.read_bytes(4).try_into().unwrap();
-        u32::from_le_bytes(bytes)
+        self.read()
     }
 
     pub fn read_u64(&mut self) -> u64 {
-        let bytes: [u8; 8] = self.read_bytes(8).try_into().unwrap();
-        u64::from_le_bytes(bytes)
+        self.read()
     }
 
     pub fn read_bool(&mut self) -> bool {
-        self.read_bytes(1)[0] != 0
+        self.read()
     }
 
     pub fn read_address(&mut self) -> Address {
-        let bytes = self.read_bytes(20);
-        let mut arr = [0u8; 20];
-        arr.copy_from_slice(bytes);
-        Address(arr)
+        self.read()
     }
 }
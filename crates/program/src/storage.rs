@@ -10,6 +10,11 @@ pub trait Persistent {
         Self: Sized;
 
     fn store(&self);
+
+    /// Deletes the stored value, returning true if there was one.
+    fn remove() -> bool
+    where
+        Self: Sized;
 }
 
 /// Macro that defines persistent structs with embedded static key
@@ -65,6 +70,10 @@ macro_rules! persist_struct {
             pub fn store(&self) {
                 <$name as $crate::Persistent>::store(self)
             }
+
+            pub fn remove() -> bool {
+                <$name as $crate::Persistent>::remove()
+            }
         }
 
         impl $crate::Persistent for $name {
@@ -160,6 +169,10 @@ macro_rules! persist_struct {
                     // For non-RISC-V targets, do nothing
                 }
             }
+
+            fn remove() -> bool {
+                $crate::StorageMap::remove($crate::PERSISTENT_DOMAIN.as_bytes(), $name::PERSIST_KEY)
+            }
         }
     };
 }
@@ -1,20 +1,145 @@
 use core::mem::{size_of, MaybeUninit};
 use crate::{require, types::O, types::address::Address};
+use crate::storage_value::StorageValue;
 use crate::logf;
 
+/// Longest key, in bytes, a storage entry can have.
+pub const MAX_KEY_LEN: usize = 64;
+
+/// Fixed-capacity buffer that a `StorageKey` writes its bytes into.
+///
+/// Keys are built on the stack; running past `MAX_KEY_LEN` aborts the call
+/// with "key too long" rather than silently truncating the key.
+#[derive(Clone, Copy)]
+pub struct KeyBuf {
+    bytes: [u8; MAX_KEY_LEN],
+    len: usize,
+}
+
+impl KeyBuf {
+    pub const fn new() -> Self {
+        Self { bytes: [0u8; MAX_KEY_LEN], len: 0 }
+    }
+
+    /// Builds the key for `key` on its own.
+    pub fn of<K: StorageKey>(key: K) -> Self {
+        let mut buf = Self::new();
+        key.write_key(&mut buf);
+        buf
+    }
+
+    /// Appends raw bytes to the key.
+    pub fn push(&mut self, bytes: &[u8]) {
+        require(self.len + bytes.len() <= MAX_KEY_LEN, b"key too long");
+        self.bytes[self.len..self.len + bytes.len()].copy_from_slice(bytes);
+        self.len += bytes.len();
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes[..self.len]
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+impl Default for KeyBuf {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 /// Trait for types that can be used as storage keys in `StorageMap`.
+///
+/// The bytes written identify the entry within its map's domain and reach
+/// the host's storage unchanged.
+///
+/// COMPOSITE KEYS: Tuples of keys are written component by component. Every
+/// encoding below is prefix-free (fixed width, or length-prefixed for byte
+/// strings), so `(a, b)` can never produce the same bytes as a different
+/// `(a', b')`:
+/// - `Address` and `[u8; N]`: the raw bytes
+/// - Integers: big-endian, so iteration visits them in numeric order
+/// - `bool`: one byte
+/// - `&[u8]` and `&str`: a one-byte length, then the bytes
 pub trait StorageKey {
-    /// Fills the provided buffer with the hex-encoded key.
-    /// Returns the length of the encoded key.
-    fn as_storage_key(&self) -> &[u8];
+    /// Appends the key's bytes to `out`.
+    fn write_key(&self, out: &mut KeyBuf);
+}
+
+impl<K: StorageKey + ?Sized> StorageKey for &K {
+    fn write_key(&self, out: &mut KeyBuf) {
+        (**self).write_key(out)
+    }
 }
 
 impl StorageKey for Address {
-    fn as_storage_key(&self) -> &[u8] {
-        &self.0
+    fn write_key(&self, out: &mut KeyBuf) {
+        out.push(&self.0);
+    }
+}
+
+impl<const N: usize> StorageKey for [u8; N] {
+    fn write_key(&self, out: &mut KeyBuf) {
+        out.push(self);
+    }
+}
+
+impl StorageKey for [u8] {
+    fn write_key(&self, out: &mut KeyBuf) {
+        require(self.len() <= u8::MAX as usize, b"key too long");
+        out.push(&[self.len() as u8]);
+        out.push(self);
+    }
+}
+
+impl StorageKey for str {
+    fn write_key(&self, out: &mut KeyBuf) {
+        self.as_bytes().write_key(out)
+    }
+}
+
+impl StorageKey for bool {
+    fn write_key(&self, out: &mut KeyBuf) {
+        out.push(&[*self as u8]);
     }
 }
 
+macro_rules! impl_storage_key_for_int {
+    ($($ty:ty),*) => {
+        $(
+            impl StorageKey for $ty {
+                fn write_key(&self, out: &mut KeyBuf) {
+                    out.push(&self.to_be_bytes());
+                }
+            }
+        )*
+    };
+}
+
+impl_storage_key_for_int!(u8, u16, u32, u64, u128);
+
+macro_rules! impl_storage_key_for_tuple {
+    ($($name:ident),+) => {
+        impl<$($name: StorageKey),+> StorageKey for ($($name,)+) {
+            #[allow(non_snake_case)]
+            fn write_key(&self, out: &mut KeyBuf) {
+                let ($($name,)+) = self;
+                $($name.write_key(out);)+
+            }
+        }
+    };
+}
+
+impl_storage_key_for_tuple!(A, B);
+impl_storage_key_for_tuple!(A, B, C);
+impl_storage_key_for_tuple!(A, B, C, D);
+
 
 pub struct StorageMap;
 
@@ -102,8 +227,381 @@ impl StorageMap {
             // For non-RISC-V targets, do nothing
         }
     }
+
+    /// Reads the raw bytes stored under the entry.
+    ///
+    /// The bytes live in the guest heap, which is never reclaimed during a
+    /// call, hence the `'static` slice.
+    pub fn get_bytes(domain: &[u8], key: &[u8]) -> O<&'static [u8]> {
+        require(key.len() <= 64, b"key too long");
+        require(domain.len() <= 64, b"domain too long");
+
+        let mut full_key = [0u8; 64];
+        full_key[..key.len()].copy_from_slice(key);
+
+        #[cfg(target_arch = "riscv32")]
+        unsafe {
+            let value_ptr: u32;
+            core::arch::asm!(
+                "li a7, 1", // syscall_storage_read
+                "ecall",
+                in("a1") domain.as_ptr(), // a1 - domain ptr
+                in("a2") domain.len(), // a2 - domain len
+                in("a3") full_key.as_ptr(), // a3 - key ptr
+                in("a4") key.len(), // a4 - key len
+                out("a0") value_ptr, // a0
+            );
+
+            if value_ptr == 0 {
+                return O::None;
+            }
+
+            let len_bytes = core::slice::from_raw_parts(value_ptr as *const u8, 4);
+            let value_len = u32::from_le_bytes(len_bytes.try_into().unwrap()) as usize;
+            O::Some(core::slice::from_raw_parts((value_ptr + 4) as *const u8, value_len))
+        }
+
+        #[cfg(not(target_arch = "riscv32"))]
+        {
+            // For non-RISC-V targets, return None
+            O::None
+        }
+    }
+
+    /// Stores raw bytes under the entry.
+    pub fn set_bytes(domain: &[u8], key: &[u8], value: &[u8]) {
+        require(key.len() <= 64, b"key too long");
+        require(domain.len() <= 64, b"domain too long");
+
+        let mut full_key = [0u8; 64];
+        full_key[..key.len()].copy_from_slice(key);
+
+        #[cfg(target_arch = "riscv32")]
+        unsafe {
+            core::arch::asm!(
+                "li a7, 2", // syscall_storage_write
+                "ecall",
+                in("a1") domain.as_ptr(), // a1 - domain ptr
+                in("a2") domain.len(), // a2 - domain len
+                in("a3") full_key.as_ptr(), // a3 - key ptr
+                in("a4") key.len(), // a4 - key len
+                in("a5") value.as_ptr(), // a5 - value ptr
+                in("a6") value.len(), // a6 - value len
+                options(readonly, nostack, preserves_flags)
+            );
+        }
+
+        #[cfg(not(target_arch = "riscv32"))]
+        {
+            // For non-RISC-V targets, do nothing
+            let _ = value;
+        }
+    }
+
+    /// Reads an encoded value, see `StorageValue`.
+    ///
+    /// Returns `None` if the entry is missing or does not decode as `V`.
+    pub fn get_value<V: StorageValue>(domain: &[u8], key: &[u8]) -> O<V> {
+        match Self::get_bytes(domain, key) {
+            O::Some(bytes) => match V::from_storage_bytes(bytes) {
+                Some(value) => O::Some(value),
+                None => O::None,
+            },
+            O::None => O::None,
+        }
+    }
+
+    /// Stores `val` in its `StorageValue` encoding.
+    pub fn set_value<V: StorageValue>(domain: &[u8], key: &[u8], val: &V) {
+        Self::set_bytes(domain, key, &val.to_storage_bytes());
+    }
+
+    /// Deletes the entry, returning true if it existed.
+    pub fn remove(domain: &[u8], key: &[u8]) -> bool {
+        require(key.len() <= 64, b"key too long");
+        require(domain.len() <= 64, b"domain too long");
+
+        let mut full_key = [0u8; 64];
+        full_key[..key.len()].copy_from_slice(key);
+
+        #[cfg(target_arch = "riscv32")]
+        unsafe {
+            let removed: u32;
+            core::arch::asm!(
+                "li a7, 12", // syscall_storage_delete
+                "ecall",
+                in("a1") domain.as_ptr(), // a1 - domain ptr
+                in("a2") domain.len(), // a2 - domain len
+                in("a3") full_key.as_ptr(), // a3 - key ptr
+                in("a4") key.len(), // a4 - key len
+                lateout("a0") removed, // a0
+            );
+            removed != 0
+        }
+
+        #[cfg(not(target_arch = "riscv32"))]
+        {
+            // For non-RISC-V targets, nothing is stored
+            false
+        }
+    }
+
+    /// Fetches up to `limit` entries of `domain` that come after `cursor`
+    /// (or from the first key when `cursor` is `None`), in ascending key order.
+    ///
+    /// The host caps how many entries a single batch holds, so a batch may
+    /// be shorter than `limit` while entries remain; only an empty batch
+    /// means the domain is exhausted.
+    pub fn next(domain: &[u8], cursor: Option<&[u8]>, limit: u32) -> StorageBatch {
+        require(domain.len() <= 64, b"domain too long");
+
+        let (cursor_ptr, cursor_len) = match cursor {
+            Some(key) => (key.as_ptr(), key.len()),
+            None => (core::ptr::null(), 0),
+        };
+
+        #[cfg(target_arch = "riscv32")]
+        unsafe {
+            let batch_ptr: u32;
+            core::arch::asm!(
+                "li a7, 13", // syscall_storage_next
+                "ecall",
+                in("a1") domain.as_ptr(), // a1 - domain ptr
+                in("a2") domain.len(), // a2 - domain len
+                in("a3") cursor_ptr, // a3 - cursor ptr
+                in("a4") cursor_len, // a4 - cursor len
+                in("a5") cursor.is_some() as u32, // a5 - cursor set
+                in("a6") limit, // a6 - max entries
+                lateout("a0") batch_ptr, // a0
+            );
+
+            if batch_ptr == 0 {
+                return StorageBatch::EMPTY;
+            }
+
+            let count = u32::from_le_bytes(core::slice::from_raw_parts(batch_ptr as *const u8, 4).try_into().unwrap());
+            StorageBatch { ptr: (batch_ptr + 4) as *const u8, remaining: count, len: count }
+        }
+
+        #[cfg(not(target_arch = "riscv32"))]
+        {
+            // For non-RISC-V targets, nothing is stored
+            let _ = (cursor_ptr, cursor_len, limit);
+            StorageBatch::EMPTY
+        }
+    }
+
+    /// Iterates over every entry of `domain` in ascending key order.
+    pub fn iter(domain: &[u8]) -> StorageIter<'_> {
+        StorageIter { domain, batch: StorageBatch::EMPTY, cursor: None, exhausted: false }
+    }
+
+    /// Iterates over the entries of `domain` that come after `cursor`.
+    ///
+    /// Handy for sweeps that span several transactions: persist the last key
+    /// handled and resume from it next time.
+    pub fn iter_after<'a>(domain: &'a [u8], cursor: &'a [u8]) -> StorageIter<'a> {
+        StorageIter { domain, batch: StorageBatch::EMPTY, cursor: Some(cursor), exhausted: false }
+    }
+}
+
+/// A `(key, value)` pair read while iterating a storage domain.
+///
+/// The bytes live in the guest heap, which is never reclaimed during a call,
+/// hence the `'static` slices.
+#[derive(Clone, Copy, Debug)]
+pub struct StorageEntry {
+    pub key: &'static [u8],
+    pub value: &'static [u8],
+}
+
+impl StorageEntry {
+    /// Reads the value as `V`, the way `StorageMap::get` does.
+    pub fn value_as<V: Copy>(&self) -> O<V> {
+        if self.value.len() != size_of::<V>() {
+            return O::None;
+        }
+        let mut val = MaybeUninit::<V>::uninit();
+        unsafe {
+            core::ptr::copy_nonoverlapping(self.value.as_ptr(), val.as_mut_ptr() as *mut u8, self.value.len());
+            O::Some(val.assume_init())
+        }
+    }
+
+    /// Decodes the value as an encoded `StorageValue`.
+    pub fn decode<V: StorageValue>(&self) -> O<V> {
+        match V::from_storage_bytes(self.value) {
+            Some(value) => O::Some(value),
+            None => O::None,
+        }
+    }
+}
+
+/// One batch of entries returned by `StorageMap::next`.
+///
+/// Layout written by the host: `(key_len u32 | key | value_len u32 | value)*`.
+pub struct StorageBatch {
+    ptr: *const u8,
+    remaining: u32,
+    len: u32,
+}
+
+impl StorageBatch {
+    const EMPTY: StorageBatch = StorageBatch { ptr: core::ptr::null(), remaining: 0, len: 0 };
+
+    /// Number of entries the host returned.
+    pub fn len(&self) -> usize {
+        self.len as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Reads one length-prefixed chunk and advances past it.
+    unsafe fn take_chunk(&mut self) -> &'static [u8] {
+        unsafe {
+            let len = u32::from_le_bytes(core::slice::from_raw_parts(self.ptr, 4).try_into().unwrap()) as usize;
+            let chunk = core::slice::from_raw_parts(self.ptr.add(4), len);
+            self.ptr = self.ptr.add(4 + len);
+            chunk
+        }
+    }
+}
+
+impl Iterator for StorageBatch {
+    type Item = StorageEntry;
+
+    fn next(&mut self) -> Option<StorageEntry> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        // SAFETY: `remaining` entries laid out by the host follow `ptr`
+        unsafe {
+            let key = self.take_chunk();
+            let value = self.take_chunk();
+            Some(StorageEntry { key, value })
+        }
+    }
+}
+
+/// Walks a storage domain batch by batch, fetching the next batch lazily.
+///
+/// EDUCATIONAL: Every batch is a syscall the contract pays for, so stopping
+/// early (`take`, `find`, ...) also stops the host from reading further.
+pub struct StorageIter<'a> {
+    domain: &'a [u8],
+    batch: StorageBatch,
+    cursor: Option<&'a [u8]>,
+    exhausted: bool,
+}
+
+impl<'a> StorageIter<'a> {
+    /// Entries requested per syscall. The host may return fewer.
+    const BATCH_SIZE: u32 = 32;
+
+    /// Key of the last entry returned, to resume from with `iter_after`.
+    pub fn cursor(&self) -> Option<&'a [u8]> {
+        self.cursor
+    }
 }
 
+impl Iterator for StorageIter<'_> {
+    type Item = StorageEntry;
+
+    fn next(&mut self) -> Option<StorageEntry> {
+        loop {
+            if let Some(entry) = self.batch.next() {
+                self.cursor = Some(entry.key);
+                return Some(entry);
+            }
+            if self.exhausted {
+                return None;
+            }
+            self.batch = StorageMap::next(self.domain, self.cursor, Self::BATCH_SIZE);
+            // A short batch only says the host's cap was reached
+            self.exhausted = self.batch.is_empty();
+        }
+    }
+}
+
+
+/// The entries of a map that share a key prefix, itself usable as a map.
+///
+/// EDUCATIONAL PURPOSE: Nested maps (`owner => spender => allowance`) are
+/// stored flat: the outer key is written first and the inner key appended
+/// to it, all under the outer map's domain. `Allowances::at(owner)` is
+/// therefore just a view - it costs nothing until an entry is read or
+/// written - and `Allowances::at(owner).get(spender)` reads the same slot as
+/// `Allowances::get((owner, spender))`.
+#[derive(Clone, Copy)]
+pub struct SubMap {
+    domain: &'static str,
+    prefix: KeyBuf,
+}
+
+impl SubMap {
+    pub fn new<P: StorageKey>(domain: &'static str, prefix: P) -> Self {
+        Self { domain, prefix: KeyBuf::of(prefix) }
+    }
+
+    /// The full key `key` is stored under, prefix included.
+    pub fn key<K: StorageKey>(&self, key: K) -> KeyBuf {
+        let mut buf = self.prefix;
+        key.write_key(&mut buf);
+        buf
+    }
+
+    /// Narrows the view by another key level.
+    pub fn at<K: StorageKey>(&self, key: K) -> SubMap {
+        SubMap { domain: self.domain, prefix: self.key(key) }
+    }
+
+    pub fn get<K, V>(&self, key: K) -> O<V>
+    where
+        K: StorageKey,
+        V: Copy + Default,
+    {
+        StorageMap::get::<V>(self.domain.as_bytes(), self.key(key).as_bytes())
+    }
+
+    pub fn set<K, V>(&self, key: K, val: V)
+    where
+        K: StorageKey,
+        V: Copy,
+    {
+        StorageMap::set::<V>(self.domain.as_bytes(), self.key(key).as_bytes(), val);
+    }
+
+    pub fn get_value<K, V>(&self, key: K) -> O<V>
+    where
+        K: StorageKey,
+        V: StorageValue,
+    {
+        StorageMap::get_value::<V>(self.domain.as_bytes(), self.key(key).as_bytes())
+    }
+
+    pub fn set_value<K, V>(&self, key: K, val: &V)
+    where
+        K: StorageKey,
+        V: StorageValue,
+    {
+        StorageMap::set_value::<V>(self.domain.as_bytes(), self.key(key).as_bytes(), val);
+    }
+
+    pub fn remove<K: StorageKey>(&self, key: K) -> bool {
+        StorageMap::remove(self.domain.as_bytes(), self.key(key).as_bytes())
+    }
+
+    /// Iterates over the entries under this prefix. Keys are returned in
+    /// full, prefix included.
+    pub fn iter(&self) -> impl Iterator<Item = StorageEntry> + '_ {
+        let prefix = self.prefix.as_bytes();
+        StorageMap::iter_after(self.domain.as_bytes(), prefix)
+            .take_while(move |entry| entry.key.starts_with(prefix))
+    }
+}
 
 #[macro_export]
 macro_rules! Map {
@@ -113,22 +611,9 @@ macro_rules! Map {
         impl $name {
             pub const DOMAIN_NAME: &'static str = stringify!($name);
             pub const DOMAIN_NAME_LEN: usize = stringify!($name).len();
-            const MAX_KEY_LEN: usize = 64;
-
-            fn build_key<K: $crate::StorageKey>(key: K, out: &mut [u8]) -> usize {
-                let key_bytes = key.as_storage_key();
-                let key_len = key_bytes.len();
-
-                // 🛡 Copy key bytes into separate buffer to prevent aliasing
-                let mut tmp = [0u8; 64];
-                tmp[..key_len].copy_from_slice(key_bytes);
-
-                // ✅ Write key to output buffer
-                out[..key_len].copy_from_slice(&tmp[..key_len]);
 
-                $crate::require(key_len <= Self::MAX_KEY_LEN, b"key too long");
-
-                key_len
+            fn build_key<K: $crate::StorageKey>(key: K) -> $crate::storage_map::KeyBuf {
+                $crate::storage_map::KeyBuf::of(key)
             }
 
             pub fn get<K, V>(key: K) -> $crate::types::O<V>
@@ -136,9 +621,8 @@ macro_rules! Map {
                 K: $crate::StorageKey,
                 V: Copy + Default,
             {
-                let mut buf = [0u8; Self::MAX_KEY_LEN];
-                let total_len = Self::build_key(key, &mut buf);
-                $crate::StorageMap::get::<V>(Self::DOMAIN_NAME.as_bytes(), &buf[..total_len])
+                let key = Self::build_key(key);
+                $crate::StorageMap::get::<V>(Self::DOMAIN_NAME.as_bytes(), key.as_bytes())
             }
 
             pub fn set<K, V>(key: K, val: V)
@@ -146,15 +630,53 @@ macro_rules! Map {
                 K: $crate::StorageKey,
                 V: Copy,
             {
-                let mut buf = [0u8; Self::MAX_KEY_LEN];
-                let total_len = Self::build_key(key, &mut buf);
-                $crate::StorageMap::set::<V>(Self::DOMAIN_NAME.as_bytes(), &buf[..total_len], val);
+                let key = Self::build_key(key);
+                $crate::StorageMap::set::<V>(Self::DOMAIN_NAME.as_bytes(), key.as_bytes(), val);
             }
-        }
-    };
-}
 
+            /// Reads an encoded value, for types that are not plain `Copy` data.
+            pub fn get_value<K, V>(key: K) -> $crate::types::O<V>
+            where
+                K: $crate::StorageKey,
+                V: $crate::StorageValue,
+            {
+                let key = Self::build_key(key);
+                $crate::StorageMap::get_value::<V>(Self::DOMAIN_NAME.as_bytes(), key.as_bytes())
+            }
 
+            /// Stores `val` in its `StorageValue` encoding.
+            pub fn set_value<K, V>(key: K, val: &V)
+            where
+                K: $crate::StorageKey,
+                V: $crate::StorageValue,
+            {
+                let key = Self::build_key(key);
+                $crate::StorageMap::set_value::<V>(Self::DOMAIN_NAME.as_bytes(), key.as_bytes(), val);
+            }
 
+            /// Deletes the entry for `key`, returning true if it existed.
+            pub fn remove<K>(key: K) -> bool
+            where
+                K: $crate::StorageKey,
+            {
+                let key = Self::build_key(key);
+                $crate::StorageMap::remove(Self::DOMAIN_NAME.as_bytes(), key.as_bytes())
+            }
 
+            /// The nested map under `prefix`, e.g. `Allowances::at(owner).get(spender)`.
+            pub fn at<K: $crate::StorageKey>(prefix: K) -> $crate::storage_map::SubMap {
+                $crate::storage_map::SubMap::new(Self::DOMAIN_NAME, prefix)
+            }
+
+            /// Iterates over every entry of the map in ascending key order.
+            pub fn iter() -> $crate::storage_map::StorageIter<'static> {
+                $crate::StorageMap::iter(Self::DOMAIN_NAME.as_bytes())
+            }
 
+            /// Iterates over the entries of the map that come after `cursor`.
+            pub fn iter_after(cursor: &[u8]) -> $crate::storage_map::StorageIter<'_> {
+                $crate::StorageMap::iter_after(Self::DOMAIN_NAME.as_bytes(), cursor)
+            }
+        }
+    };
+}
@@ -0,0 +1,67 @@
+use core::marker::PhantomData;
+
+use crate::storage_map::{KeyBuf, StorageKey, StorageMap};
+use crate::storage_vec::{load_len, store_len, HEADER_KEY};
+
+/// Value stored for every member; only the presence of the key matters.
+const MEMBER: &[u8] = &[1];
+
+/// A persistent set of keys stored under its own domain.
+///
+/// Each member is an entry keyed by its `StorageKey` bytes, so membership is
+/// a single storage read. The number of members is kept in a header entry,
+/// like `StorageVec`'s length.
+///
+/// ```ignore
+/// static ADMINS: StorageSet<Address> = StorageSet::new("Admins");
+/// require(ADMINS.contains(caller), b"not an admin");
+/// ```
+pub struct StorageSet<K> {
+    domain: &'static str,
+    _marker: PhantomData<K>,
+}
+
+impl<K: StorageKey> StorageSet<K> {
+    pub const fn new(domain: &'static str) -> Self {
+        Self { domain, _marker: PhantomData }
+    }
+
+    pub fn len(&self) -> u32 {
+        load_len(self.domain)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn contains(&self, key: K) -> bool {
+        StorageMap::get_bytes(self.domain.as_bytes(), KeyBuf::of(key).as_bytes()).is_some()
+    }
+
+    /// Adds `key`, returning true if it was not already a member.
+    pub fn insert(&self, key: K) -> bool {
+        let key = KeyBuf::of(key);
+        if StorageMap::get_bytes(self.domain.as_bytes(), key.as_bytes()).is_some() {
+            return false;
+        }
+        StorageMap::set_bytes(self.domain.as_bytes(), key.as_bytes(), MEMBER);
+        store_len(self.domain, self.len() + 1);
+        true
+    }
+
+    /// Removes `key`, returning true if it was a member.
+    pub fn remove(&self, key: K) -> bool {
+        if !StorageMap::remove(self.domain.as_bytes(), KeyBuf::of(key).as_bytes()) {
+            return false;
+        }
+        store_len(self.domain, self.len() - 1);
+        true
+    }
+
+    /// Iterates over the members' key bytes in ascending order.
+    pub fn iter(&self) -> impl Iterator<Item = &'static [u8]> {
+        StorageMap::iter(self.domain.as_bytes())
+            .filter(|entry| entry.key != HEADER_KEY)
+            .map(|entry| entry.key)
+    }
+}
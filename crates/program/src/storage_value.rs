@@ -0,0 +1,65 @@
+use alloc::vec::Vec;
+use types::{Decode, Encode};
+
+/// Values that can be written to storage with an explicit encoding.
+///
+/// EDUCATIONAL PURPOSE: `StorageMap::get/set` copy a value's raw memory,
+/// which only works for fixed-size `Copy` types. Anything holding a `Vec` or
+/// `String` points at the heap, so its memory bytes are meaningless once the
+/// call ends. Encoding a value field by field gives it a layout of its own
+/// that can be stored and read back later.
+///
+/// ENCODING: the ABI encoding of `types::codec`, so a value is stored with
+/// the same bytes it is passed or returned with. Every type implementing
+/// `Encode` and `Decode` is a `StorageValue`; `storage_value!` implements
+/// both for a struct.
+///
+/// Fixed-size values encode to the same bytes `StorageMap::set` writes on
+/// the little-endian guest, so a `u32` stored one way can be read the other.
+pub trait StorageValue: Encode + for<'a> Decode<'a> {
+    /// Encodes `self` into a fresh buffer.
+    fn to_storage_bytes(&self) -> Vec<u8> {
+        self.to_encoded_bytes()
+    }
+
+    /// Decodes a value that must take up all of `bytes`.
+    fn from_storage_bytes(bytes: &[u8]) -> Option<Self> {
+        Self::decode_exact(bytes)
+    }
+}
+
+impl<T: Encode + for<'a> Decode<'a>> StorageValue for T {}
+
+/// Implements `Encode` and `Decode` (and so `StorageValue`) for a struct by
+/// encoding the listed fields in order.
+///
+/// Every field must itself implement both, and the list must name all of
+/// them - a struct literal is used to rebuild the value, so a missing field
+/// is a compile error.
+///
+/// ```ignore
+/// pub struct Profile { pub name: String, pub scores: Vec<u32> }
+/// storage_value!(Profile { name, scores });
+/// ```
+#[macro_export]
+macro_rules! storage_value {
+    ($name:ident { $($field:ident),* $(,)? }) => {
+        impl $crate::types::Encode for $name {
+            fn encoded_len(&self) -> usize {
+                0 $(+ $crate::types::Encode::encoded_len(&self.$field))*
+            }
+
+            fn encode_into(&self, buf: &mut [u8], offset: &mut usize) {
+                $($crate::types::Encode::encode_into(&self.$field, buf, offset);)*
+            }
+        }
+
+        impl<'a> $crate::types::Decode<'a> for $name {
+            fn decode(input: &mut &'a [u8]) -> Option<Self> {
+                Some($name {
+                    $($field: $crate::types::Decode::decode(input)?,)*
+                })
+            }
+        }
+    };
+}
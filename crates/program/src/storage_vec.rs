@@ -0,0 +1,110 @@
+use core::marker::PhantomData;
+
+use crate::storage_map::{KeyBuf, StorageMap};
+use crate::storage_value::StorageValue;
+use crate::{require, types::O, vm_panic};
+
+/// Key the length of a collection is kept under. Element keys are never
+/// empty, so the header cannot collide with one.
+pub(crate) const HEADER_KEY: &[u8] = &[];
+
+/// Reads the length header of the collection stored in `domain`.
+pub(crate) fn load_len(domain: &str) -> u32 {
+    match StorageMap::get_value::<u32>(domain.as_bytes(), HEADER_KEY) {
+        O::Some(len) => len,
+        O::None => 0,
+    }
+}
+
+pub(crate) fn store_len(domain: &str, len: u32) {
+    if len == 0 {
+        StorageMap::remove(domain.as_bytes(), HEADER_KEY);
+    } else {
+        StorageMap::set_value(domain.as_bytes(), HEADER_KEY, &len);
+    }
+}
+
+/// A persistent, growable array stored under its own domain.
+///
+/// EDUCATIONAL PURPOSE: Storage is a flat key-value map, so a list has to be
+/// laid out by hand. The length lives in a header entry and element `i`
+/// lives under the big-endian key `i`, which keeps the elements in index
+/// order for storage iteration. Every operation touches at most two slots,
+/// whatever the size of the list.
+///
+/// Elements are stored in their `StorageValue` encoding.
+///
+/// ```ignore
+/// static VOTERS: StorageVec<Address> = StorageVec::new("Voters");
+/// VOTERS.push(&caller);
+/// ```
+pub struct StorageVec<T> {
+    domain: &'static str,
+    _marker: PhantomData<T>,
+}
+
+impl<T: StorageValue> StorageVec<T> {
+    pub const fn new(domain: &'static str) -> Self {
+        Self { domain, _marker: PhantomData }
+    }
+
+    fn key(index: u32) -> KeyBuf {
+        KeyBuf::of(index)
+    }
+
+    pub fn len(&self) -> u32 {
+        load_len(self.domain)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns element `index`, or `None` past the end.
+    pub fn get(&self, index: u32) -> O<T> {
+        if index >= self.len() {
+            return O::None;
+        }
+        StorageMap::get_value(self.domain.as_bytes(), Self::key(index).as_bytes())
+    }
+
+    /// Overwrites element `index`, which must exist.
+    pub fn set(&self, index: u32, value: &T) {
+        require(index < self.len(), b"index out of bounds");
+        StorageMap::set_value(self.domain.as_bytes(), Self::key(index).as_bytes(), value);
+    }
+
+    /// Appends `value` at the end.
+    pub fn push(&self, value: &T) {
+        let len = self.len();
+        require(len < u32::MAX, b"vector full");
+        StorageMap::set_value(self.domain.as_bytes(), Self::key(len).as_bytes(), value);
+        store_len(self.domain, len + 1);
+    }
+
+    /// Removes and returns the last element.
+    pub fn pop(&self) -> O<T> {
+        let len = self.len();
+        if len == 0 {
+            return O::None;
+        }
+        let key = Self::key(len - 1);
+        let value = StorageMap::get_value(self.domain.as_bytes(), key.as_bytes());
+        StorageMap::remove(self.domain.as_bytes(), key.as_bytes());
+        store_len(self.domain, len - 1);
+        value
+    }
+
+    /// Iterates over the elements in index order, one storage read each.
+    ///
+    /// Panics on an element that is missing or cannot be decoded, rather
+    /// than skipping it and yielding fewer than `len` elements.
+    pub fn iter(&self) -> impl Iterator<Item = T> + '_ {
+        (0..self.len()).map(move |index| {
+            match StorageMap::get_value(self.domain.as_bytes(), Self::key(index).as_bytes()) {
+                O::Some(value) => value,
+                O::None => vm_panic(b"corrupt vector element"),
+            }
+        })
+    }
+}
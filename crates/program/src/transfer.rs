@@ -2,8 +2,13 @@ use types::address::Address;
 
 const SYSCALL_TRANSFER: u32 = 9;
 const SYSCALL_BALANCE: u32 = 10;
+#[cfg(target_arch = "riscv32")]
+const SYSCALL_CALL_VALUE: u32 = 11;
 
 /// Executes a native AM token transfer via syscall. Returns true on success.
+///
+/// The funds come out of the executing contract's own balance; the transfer
+/// fails (and moves nothing) if the contract cannot cover `value`.
 #[inline(always)]
 pub fn transfer(to: &Address, value: u64) -> bool {
     #[cfg(target_arch = "riscv32")]
@@ -66,6 +71,52 @@ pub fn balance(addr: &Address) -> u128 {
     }
 }
 
+/// Returns the native value attached to the current call.
+///
+/// The value is already in the contract's balance when it starts executing.
+#[inline(always)]
+pub fn msg_value() -> u64 {
+    #[cfg(target_arch = "riscv32")]
+    {
+        let mut ptr: u32;
+        unsafe {
+            core::arch::asm!(
+                "li a7, {call_value}",
+                "ecall",
+                lateout("a0") ptr,
+                call_value = const SYSCALL_CALL_VALUE,
+            );
+        }
+        if ptr == 0 {
+            return 0;
+        }
+        let mut bytes = [0u8; 8];
+        unsafe {
+            let src = ptr as *const u8;
+            for i in 0..8 {
+                bytes[i] = *src.add(i);
+            }
+        }
+        u64::from_le_bytes(bytes)
+    }
+
+    #[cfg(not(target_arch = "riscv32"))]
+    {
+        0
+    }
+}
+
+/// Aborts the call if any native value was attached to it.
+///
+/// Entry points that are not meant to receive funds call this first; the
+/// abort reverts the call, which returns the value to the caller.
+#[inline(always)]
+pub fn require_no_value() {
+    if msg_value() != 0 {
+        crate::vm_panic(b"call does not accept value");
+    }
+}
+
 /// Convenience macro to invoke a transfer from a contract.
 #[macro_export]
 macro_rules! transfer {
@@ -0,0 +1,163 @@
+use compiler::{AbiGenerator, ContractAbi, ParamType};
+use program::contract;
+use types::Encode;
+
+const PROGRAM: [u8; 20] = [0x11; 20];
+const CALLER: [u8; 20] = [0x22; 20];
+
+#[contract]
+mod counter {
+    use program::{event, CallContext};
+    use types::address::Address;
+    use types::result::Result;
+
+    event!(Bumped { by => u32, who => Address });
+
+    #[function(selector = 0x01)]
+    pub fn add(a: u32, b: u32) -> u32 {
+        a + b
+    }
+
+    #[function(selector = 0x02)]
+    pub fn whoami(ctx: CallContext) -> Address {
+        ctx.caller
+    }
+
+    #[function(selector = 0x03)]
+    pub fn echo_len(flag: bool, data: &[u8]) -> Result {
+        Result::new(flag, data.len() as u32)
+    }
+
+    #[function(selector = 0x04)]
+    pub fn noop() {}
+
+    #[function(selector = 0x05)]
+    pub fn offset(tag: [u8; 2], delta: i16) -> i32 {
+        i16::from_le_bytes(tag) as i32 + delta as i32
+    }
+
+    // Only `CallContext` is implicit; an address named `caller` is an input
+    #[function(selector = 0x06)]
+    pub fn is_caller(ctx: CallContext, caller: Address) -> bool {
+        ctx.caller == caller
+    }
+
+    #[function(selector = 0x07)]
+    pub fn join(words: Vec<String>, sep: &str) -> (u32, String) {
+        (words.len() as u32, words.join(sep))
+    }
+
+    pub fn not_exported() -> u32 {
+        7
+    }
+}
+
+fn call(selector: u8, args: &[u8]) -> types::result::Result {
+    let mut input = vec![selector, args.len() as u8];
+    input.extend_from_slice(args);
+    counter::dispatch(
+        types::address::Address(PROGRAM),
+        types::address::Address(CALLER),
+        &input,
+    )
+}
+
+#[test]
+fn test_dispatch_decodes_arguments() {
+    let mut args = 2u32.to_le_bytes().to_vec();
+    args.extend_from_slice(&40u32.to_le_bytes());
+    assert_eq!(call(0x01, &args).get_u32_data(), Some(42));
+}
+
+#[test]
+fn test_dispatch_passes_call_context() {
+    let result = call(0x02, &[]);
+    assert!(result.success);
+    let data_len = result.data_len as usize;
+    assert_eq!(&result.data[..data_len], &CALLER);
+}
+
+#[test]
+fn test_dispatch_bytes_and_unit() {
+    // The bytes carry their length like any other slice
+    let result = call(0x03, &[0, 3, 0, 0, 0, 9, 9, 9]);
+    assert!(!result.success);
+    let error_code = result.error_code;
+    assert_eq!(error_code, 3);
+
+    let result = call(0x04, &[]);
+    assert!(result.success);
+    assert_eq!(counter::not_exported(), 7);
+}
+
+#[test]
+fn test_dispatch_signed_and_fixed_bytes() {
+    let mut args = 100i16.to_le_bytes().to_vec();
+    args.extend_from_slice(&(-300i16).to_le_bytes());
+    let result = call(0x05, &args);
+    let data_len = result.data_len as usize;
+    assert_eq!(&result.data[..data_len], &(-200i32).to_le_bytes());
+}
+
+#[test]
+fn test_dispatch_caller_argument_and_composite_types() {
+    let result = call(0x06, &CALLER);
+    assert_eq!(result.data[..result.data_len as usize], [1]);
+    let result = call(0x06, &PROGRAM);
+    assert_eq!(result.data[..result.data_len as usize], [0]);
+
+    let result = call(0x07, &(vec!["a", "bc"], "-").to_encoded_bytes());
+    let data_len = result.data_len as usize;
+    assert_eq!(&result.data[..data_len], &(2u32, "a-bc").to_encoded_bytes());
+}
+
+#[test]
+#[should_panic(expected = "unknown selector")]
+fn test_dispatch_rejects_unknown_selector() {
+    call(0x09, &[]);
+}
+
+#[test]
+#[should_panic(expected = "too many arguments")]
+fn test_dispatch_rejects_extra_arguments() {
+    call(0x04, &[1]);
+}
+
+#[test]
+fn test_abi_json_matches_signatures() {
+    let abi = ContractAbi::from_json(counter::ABI_JSON).unwrap();
+    let names: Vec<_> = abi.functions.iter().map(|f| (f.selector, f.name.as_str())).collect();
+    assert_eq!(
+        names,
+        vec![(1, "add"), (2, "whoami"), (3, "echo_len"), (4, "noop"), (5, "offset"), (6, "is_caller"), (7, "join")]
+    );
+
+    let add = &abi.functions[0];
+    assert_eq!(add.inputs.len(), 2);
+    assert_eq!(add.inputs[0].kind, ParamType::Uint(32));
+    assert_eq!(add.outputs, vec![ParamType::Uint(32)]);
+
+    // The call context is not an input
+    assert!(abi.functions[1].inputs.is_empty());
+    assert_eq!(abi.functions[1].outputs, vec![ParamType::Address]);
+    assert_eq!(abi.functions[2].inputs[1].kind, ParamType::Bytes);
+    assert_eq!(abi.functions[2].outputs, vec![ParamType::Result]);
+    assert!(abi.functions[3].outputs.is_empty());
+    assert_eq!(abi.functions[4].inputs[0].kind, ParamType::FixedBytes(2));
+    assert_eq!(abi.functions[4].inputs[1].kind, ParamType::Int(16));
+    assert_eq!(abi.functions[4].outputs, vec![ParamType::Int(32)]);
+    assert_eq!(abi.functions[5].inputs[0].name, "caller");
+    assert_eq!(abi.functions[6].inputs[0].kind, ParamType::Array(Box::new(ParamType::String)));
+    assert_eq!(abi.functions[6].outputs, vec![ParamType::Tuple(vec![ParamType::Uint(32), ParamType::String])]);
+
+    assert_eq!(abi.events.len(), 1);
+    assert_eq!(abi.events[0].name, "Bumped");
+    assert_eq!(abi.events[0].inputs[1].kind, ParamType::Address);
+}
+
+#[test]
+fn test_source_abi_matches_embedded_abi() {
+    // `avm32 abi --src` on this file must agree with what the macro embeds
+    let abi = AbiGenerator::new(include_str!("contract.rs").to_string()).generate().unwrap();
+    assert_eq!(abi.to_json(), counter::ABI_JSON);
+}
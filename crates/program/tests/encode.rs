@@ -0,0 +1,67 @@
+use program::{event, DataParser};
+use types::address::Address;
+use types::{Decode, Encode};
+
+const ALICE: Address = Address([0xa1; 20]);
+
+#[test]
+fn test_layout_of_each_type() {
+    assert_eq!(0x0102u16.to_encoded_bytes(), vec![0x02, 0x01]);
+    assert_eq!((-2i32).to_encoded_bytes(), vec![0xfe, 0xff, 0xff, 0xff]);
+    assert_eq!(true.to_encoded_bytes(), vec![1]);
+    assert_eq!(ALICE.to_encoded_bytes(), vec![0xa1; 20]);
+    assert_eq!([7u8, 8, 9].to_encoded_bytes(), vec![7, 8, 9]);
+    assert_eq!([1u16, 2].to_encoded_bytes(), vec![1, 0, 2, 0]);
+    assert_eq!(vec![5u8, 6].to_encoded_bytes(), vec![2, 0, 0, 0, 5, 6]);
+    assert_eq!("hi".to_encoded_bytes(), vec![2, 0, 0, 0, b'h', b'i']);
+    assert_eq!((1u8, false).to_encoded_bytes(), vec![1, 0]);
+    assert_eq!(Some(5u8).to_encoded_bytes(), vec![1, 5]);
+    assert_eq!(None::<u8>.to_encoded_bytes(), vec![0]);
+}
+
+#[test]
+fn test_round_trip() {
+    let value = (ALICE, -7i64, vec![(1u32, String::from("a"))], [true, false]);
+    let bytes = value.to_encoded_bytes();
+    assert_eq!(bytes.len(), value.encoded_len());
+
+    let decoded = <(Address, i64, Vec<(u32, String)>, [bool; 2])>::decode_exact(&bytes).unwrap();
+    assert_eq!(decoded.0, ALICE);
+    assert_eq!(decoded.1, -7);
+    assert_eq!(decoded.2, vec![(1, String::from("a"))]);
+    assert_eq!(decoded.3, [true, false]);
+
+    let mut input: &[u8] = &[3, 0, 0, 0, b'a', b'b', b'c', 9];
+    assert_eq!(<&str>::decode(&mut input), Some("abc"));
+    assert_eq!(input, &[9]);
+}
+
+#[test]
+fn test_decode_rejects_bad_input() {
+    assert_eq!(u32::decode_exact(&[1, 2, 3]), None);
+    assert_eq!(u8::decode_exact(&[1, 2]), None);
+    assert_eq!(bool::decode_exact(&[2]), None);
+    assert_eq!(String::decode_exact(&[1, 0, 0, 0, 0xff]), None);
+    assert_eq!(<Vec<u8>>::decode_exact(&[9, 0, 0, 0, 1]), None);
+}
+
+#[test]
+fn test_data_parser_reads_host_encoding() {
+    let data = (ALICE, 42u64, true, -3i16).to_encoded_bytes();
+    let mut parser = DataParser::new(&data);
+    assert_eq!(parser.read_address(), ALICE);
+    assert_eq!(parser.read_u64(), 42);
+    assert!(parser.read_bool());
+    assert_eq!(parser.read::<i16>(), -3);
+    assert_eq!(parser.remaining(), 0);
+}
+
+event!(Paid { to => Address, amount => u128, memo => [u8; 4] });
+
+#[test]
+fn test_event_fields_use_encode() {
+    let mut buf = [0u8; 256];
+    let written = Paid::new(ALICE, 5, *b"rent").write_bytes(&mut buf);
+    assert_eq!(&buf[..4], b"Paid");
+    assert_eq!(&buf[32..written], (ALICE, 5u128, *b"rent").to_encoded_bytes().as_slice());
+}
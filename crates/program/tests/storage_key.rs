@@ -0,0 +1,52 @@
+use program::{KeyBuf, Map, StorageKey};
+use types::Address;
+
+const OWNER: Address = Address([0xaa; 20]);
+const SPENDER: Address = Address([0xbb; 20]);
+
+Map!(Allowances);
+
+fn key<K: StorageKey>(key: K) -> Vec<u8> {
+    KeyBuf::of(key).as_bytes().to_vec()
+}
+
+#[test]
+fn test_single_keys() {
+    assert_eq!(key(OWNER), OWNER.0.to_vec());
+    let borrowed: &Address = &OWNER;
+    assert_eq!(key(borrowed), OWNER.0.to_vec());
+    assert_eq!(key(0x0102u16), vec![1, 2]);
+    assert_eq!(key(true), vec![1]);
+    assert_eq!(key("ab"), vec![2, b'a', b'b']);
+}
+
+#[test]
+fn test_composite_keys_concatenate() {
+    let mut expected = OWNER.0.to_vec();
+    expected.extend_from_slice(&SPENDER.0);
+    assert_eq!(key((OWNER, SPENDER)), expected);
+    assert_eq!(key((OWNER, SPENDER)).len(), 40);
+    assert_ne!(key((OWNER, SPENDER)), key((SPENDER, OWNER)));
+
+    // Byte strings are length-prefixed, so component boundaries are kept
+    assert_ne!(key(("ab", "c")), key(("a", "bc")));
+    assert_eq!(key((1u8, 2u32, OWNER)).len(), 25);
+}
+
+#[test]
+fn test_integer_keys_sort_numerically() {
+    assert!(key(255u32) < key(256u32));
+    assert!(key((OWNER, 9u64)) < key((OWNER, 10u64)));
+}
+
+#[test]
+#[should_panic(expected = "vm_panic: key too long")]
+fn test_keys_are_bounded() {
+    key((OWNER, SPENDER, OWNER, SPENDER));
+}
+
+#[test]
+fn test_nested_map_shares_flat_slot() {
+    assert_eq!(Allowances::at(OWNER).key(SPENDER).as_bytes(), key((OWNER, SPENDER)));
+    assert_eq!(Allowances::at(OWNER).at(SPENDER).key(7u8).as_bytes(), key((OWNER, SPENDER, 7u8)));
+}
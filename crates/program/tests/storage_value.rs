@@ -0,0 +1,58 @@
+use program::{storage_value, StorageValue};
+use types::Address;
+
+#[derive(Debug, PartialEq)]
+pub struct Profile {
+    pub owner: Address,
+    pub name: String,
+    pub scores: Vec<u32>,
+    pub nickname: Option<String>,
+}
+
+storage_value!(Profile { owner, name, scores, nickname });
+
+fn profile() -> Profile {
+    Profile {
+        owner: Address([7; 20]),
+        name: "alice".to_string(),
+        scores: vec![1, 2, 300],
+        nickname: None,
+    }
+}
+
+#[test]
+fn test_struct_round_trip() {
+    let bytes = profile().to_storage_bytes();
+    assert_eq!(bytes.len(), 20 + (4 + 5) + (4 + 3 * 4) + 1);
+    assert_eq!(Profile::from_storage_bytes(&bytes), Some(profile()));
+}
+
+#[test]
+fn test_fixed_size_values_match_raw_layout() {
+    // Same bytes `StorageMap::set` writes for POD values on the guest
+    assert_eq!(0x0102_0304u32.to_storage_bytes(), 0x0102_0304u32.to_le_bytes());
+    assert_eq!((-2i64).to_storage_bytes(), (-2i64).to_le_bytes());
+    assert_eq!(u128::from_storage_bytes(&[1; 16]), Some(u128::from_le_bytes([1; 16])));
+}
+
+#[test]
+fn test_decoding_is_length_checked() {
+    let bytes = profile().to_storage_bytes();
+    assert_eq!(Profile::from_storage_bytes(&bytes[..bytes.len() - 1]), None);
+
+    let mut trailing = bytes.clone();
+    trailing.push(0);
+    assert_eq!(Profile::from_storage_bytes(&trailing), None);
+
+    // A length prefix that runs past the end of the input
+    assert_eq!(Vec::<u8>::from_storage_bytes(&[0xff, 0xff, 0xff, 0xff, 1]), None);
+    assert_eq!(u32::from_storage_bytes(&[1, 2, 3]), None);
+}
+
+#[test]
+fn test_invalid_values_are_rejected() {
+    assert_eq!(bool::from_storage_bytes(&[2]), None);
+    assert_eq!(Option::<u8>::from_storage_bytes(&[2, 0]), None);
+    assert_eq!(String::from_storage_bytes(&[2, 0, 0, 0, 0xff, 0xfe]), None);
+    assert_eq!(<(u8, bool)>::from_storage_bytes(&[5, 1]), Some((5, true)));
+}
@@ -0,0 +1,154 @@
+//! Attribute macros that turn a module of typed Rust functions into a contract.
+//!
+//! EDUCATIONAL PURPOSE: Without these macros a contract author writes the
+//! same glue for every function: match on the selector, read each argument
+//! out of the call bytes with a `DataParser`, pack the return value into a
+//! `Result`, and keep a separate ABI in sync by hand. `#[contract]` derives
+//! all of it from the function signatures, so the dispatch code and the ABI
+//! are generated from the same source and cannot disagree.
+//!
+//! ```ignore
+//! #[contract]
+//! mod token {
+//!     use super::*;
+//!
+//!     #[function(selector = 0x02)]
+//!     pub fn transfer(ctx: CallContext, to: Address, amount: u32) { /* ... */ }
+//!
+//!     #[function(selector = 0x05)]
+//!     pub fn balance_of(owner: Address) -> u32 { /* ... */ }
+//! }
+//! ```
+
+use compiler::contract_abi::{analyze_contract, is_function_attr, ContractFunction, ContractInput};
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::quote;
+use syn::spanned::Spanned;
+use syn::{Item, ItemMod, LitByteStr};
+
+/// Generates the dispatcher, the entrypoint and the ABI for a contract module.
+///
+/// Every function marked `#[function(selector = N)]` becomes callable through
+/// the router with selector `N`. Its arguments are decoded in order with
+/// `program::contract::ContractArg` and its return value is encoded with
+/// `program::contract::IntoResult`. A first argument of type `CallContext`
+/// receives the contract and caller addresses and is not part of the ABI.
+///
+/// The module gains:
+/// - `fn dispatch(program, caller, data) -> Result`, wired to `entrypoint!`
+///   on the guest
+/// - `const ABI_JSON: &str`, the ABI of the module's functions and `event!`s
+/// - on the guest, a copy of the ABI in the `.abi` ELF section, which
+///   `avm32 abi --elf` reads back
+#[proc_macro_attribute]
+pub fn contract(attr: TokenStream, item: TokenStream) -> TokenStream {
+    if !attr.is_empty() {
+        return syn::Error::new(Span::call_site(), "#[contract] takes no arguments")
+            .to_compile_error()
+            .into();
+    }
+    let module = syn::parse_macro_input!(item as ItemMod);
+    match expand_contract(module) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+/// Marks a function of a `#[contract]` module as callable.
+///
+/// `#[contract]` consumes this attribute; reaching this expansion means the
+/// function is outside a contract module.
+#[proc_macro_attribute]
+pub fn function(_attr: TokenStream, item: TokenStream) -> TokenStream {
+    let mut tokens: TokenStream2 =
+        syn::Error::new(Span::call_site(), "#[function] can only be used inside a #[contract] module")
+            .to_compile_error();
+    tokens.extend(TokenStream2::from(item));
+    tokens.into()
+}
+
+fn expand_contract(mut module: ItemMod) -> syn::Result<TokenStream2> {
+    let (brace, mut items) = match module.content.take() {
+        Some(content) => content,
+        None => {
+            return Err(syn::Error::new(
+                module.semi.span(),
+                "#[contract] needs an inline module: `mod name { ... }`",
+            ))
+        }
+    };
+
+    // `avm32 abi` reads the module with the same analysis, so the ABI it
+    // derives from the source is the one embedded here
+    let contract = analyze_contract(&items)?;
+    let arms: Vec<TokenStream2> = contract.functions.iter().map(dispatch_arm).collect();
+    for item in &mut items {
+        if let Item::Fn(func) = item {
+            func.attrs.retain(|attr| !is_function_attr(attr));
+        }
+    }
+
+    let json = contract.abi.to_json();
+    let json_bytes = LitByteStr::new(json.as_bytes(), Span::call_site());
+    let json_len = json.len();
+
+    items.push(syn::parse_quote! {
+        /// Routes every call in `data` to the matching contract function.
+        pub fn dispatch(
+            program: ::program::types::address::Address,
+            caller: ::program::types::address::Address,
+            data: &[u8],
+        ) -> ::program::types::result::Result {
+            ::program::router::route(data, program, caller, |__program, __caller, __call| {
+                match __call.selector {
+                    #(#arms)*
+                    _ => ::program::vm_panic(b"unknown selector"),
+                }
+            })
+        }
+    });
+    // The host entrypoint can only panic, so it is left out of host builds
+    items.push(syn::parse_quote! {
+        #[cfg(target_arch = "riscv32")]
+        ::program::entrypoint!(dispatch);
+    });
+    items.push(syn::parse_quote! {
+        /// ABI of this contract, generated from the function signatures.
+        pub const ABI_JSON: &str = #json;
+    });
+    items.push(syn::parse_quote! {
+        #[cfg(target_arch = "riscv32")]
+        #[used]
+        #[unsafe(link_section = ".abi")]
+        static __CONTRACT_ABI: [u8; #json_len] = *#json_bytes;
+    });
+
+    module.content = Some((brace, items));
+    Ok(quote!(#module))
+}
+
+/// Builds the dispatcher arm for one function.
+fn dispatch_arm(function: &ContractFunction) -> TokenStream2 {
+    let ContractFunction { ident, selector, inputs } = function;
+    let decode = inputs.iter().filter_map(|input| match input {
+        ContractInput::Value { name, ty } => Some(quote! {
+            let #name: #ty = ::program::contract::ContractArg::read(&mut __args);
+        }),
+        ContractInput::Context => None,
+    });
+    let call_args = inputs.iter().map(|input| match input {
+        ContractInput::Context => quote! {
+            ::program::contract::CallContext { program: __program, caller: __caller }
+        },
+        ContractInput::Value { name, .. } => quote!(#name),
+    });
+    quote! {
+        #selector => {
+            let mut __args = ::program::DataParser::new(__call.args);
+            #(#decode)*
+            ::program::contract::finish_args(&__args);
+            ::program::contract::IntoResult::into_result(#ident(#(#call_args),*))
+        }
+    }
+}
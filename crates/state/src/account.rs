@@ -1,4 +1,6 @@
 use alloc::collections::BTreeMap;
+use core::convert::TryInto;
+use storage::StorageKey;
 
 #[derive(Clone, Debug)]
 pub struct Account {
@@ -7,5 +9,41 @@ pub struct Account {
     pub code: Vec<u8>,
     pub is_contract: bool,
 
-    pub storage: BTreeMap<String, Vec<u8>>,
-}
\ No newline at end of file
+    pub storage: BTreeMap<StorageKey, Vec<u8>>,
+}
+
+/// Version byte that starts an encoded account.
+const ACCOUNT_ENCODING_VERSION: u8 = 1;
+
+impl Account {
+    /// Binary form of the account's own fields, used to persist it in a
+    /// `StorageBackend`.
+    ///
+    /// LAYOUT (little endian): version, nonce, balance, is_contract. Code and
+    /// storage are not included: `State` persists them under keys of their
+    /// own, so changing one slot does not rewrite the whole account.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut buf = vec![ACCOUNT_ENCODING_VERSION];
+        buf.extend_from_slice(&self.nonce.to_le_bytes());
+        buf.extend_from_slice(&self.balance.to_le_bytes());
+        buf.push(self.is_contract as u8);
+        buf
+    }
+
+    /// Parses bytes produced by `encode`; `None` if they are malformed. The
+    /// decoded account has no code or storage yet.
+    pub fn decode(input: &[u8]) -> Option<Self> {
+        let (&version, rest) = input.split_first()?;
+        if version != ACCOUNT_ENCODING_VERSION || rest.len() != 8 + 16 + 1 {
+            return None;
+        }
+        let nonce = u64::from_le_bytes(rest[..8].try_into().ok()?);
+        let balance = u128::from_le_bytes(rest[8..24].try_into().ok()?);
+        let is_contract = match rest[24] {
+            0 => false,
+            1 => true,
+            _ => return None,
+        };
+        Some(Account { nonce, balance, code: Vec::new(), is_contract, storage: BTreeMap::new() })
+    }
+}
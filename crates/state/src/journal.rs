@@ -0,0 +1,73 @@
+use types::address::Address;
+use alloc::collections::BTreeMap;
+use storage::StorageKey;
+
+/// A single reversible change made to the `State`.
+///
+/// EDUCATIONAL PURPOSE: Instead of copying the whole state before every
+/// transaction, we record the *previous* value of everything we touch. Undoing
+/// a transaction is then just replaying these entries backwards.
+#[derive(Clone, Debug)]
+pub enum JournalEntry {
+    /// An account that did not exist before was created
+    AccountCreated(Address),
+    /// An account's balance changed; holds the old balance
+    BalanceChanged { addr: Address, prev: u128 },
+    /// An account's nonce changed; holds the old nonce
+    NonceChanged { addr: Address, prev: u64 },
+    /// An account's storage changed; holds the old storage map
+    StorageChanged { addr: Address, prev: BTreeMap<StorageKey, Vec<u8>> },
+    /// An account's code changed; holds the old code and contract flag
+    CodeChanged { addr: Address, prev_code: Vec<u8>, prev_is_contract: bool },
+}
+
+/// Position in the journal that the state can be reverted to.
+///
+/// EDUCATIONAL: Checkpoints nest naturally. Taking one at the start of a
+/// transaction (or of a nested call) and reverting to it undoes exactly the
+/// changes made after that point and nothing before it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Checkpoint(pub(crate) usize);
+
+/// Ordered log of uncommitted state changes.
+#[derive(Clone, Debug, Default)]
+pub struct Journal {
+    entries: Vec<JournalEntry>,
+}
+
+impl Journal {
+    pub fn new() -> Self {
+        Self { entries: Vec::new() }
+    }
+
+    /// Records a change so it can be undone later.
+    pub fn record(&mut self, entry: JournalEntry) {
+        self.entries.push(entry);
+    }
+
+    /// Marks the current end of the journal.
+    pub fn checkpoint(&self) -> Checkpoint {
+        Checkpoint(self.entries.len())
+    }
+
+    /// Removes and returns (newest first) every entry recorded after `checkpoint`.
+    pub fn drain_since(&mut self, checkpoint: Checkpoint) -> Vec<JournalEntry> {
+        let start = checkpoint.0.min(self.entries.len());
+        let mut undone: Vec<JournalEntry> = self.entries.drain(start..).collect();
+        undone.reverse();
+        undone
+    }
+
+    /// Forgets every recorded change, making them permanent.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
@@ -1,9 +1,15 @@
 pub mod types;
 pub mod account;
 pub mod state;
+pub mod journal;
+pub mod merkle;
+pub mod proof;
 
 pub use types::*;
 pub use account::*;
 pub use state::*;
+pub use journal::*;
+pub use merkle::*;
+pub use proof::*;
 
 extern crate alloc;
\ No newline at end of file
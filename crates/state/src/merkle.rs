@@ -0,0 +1,256 @@
+use alloc::collections::BTreeMap;
+use sha2::{Digest, Sha256};
+use std::sync::OnceLock;
+use types::address::Address;
+use storage::StorageKey;
+use crate::Account;
+
+/// A 32-byte SHA-256 digest: a tree key, a leaf value or a node.
+pub type MerkleHash = [u8; 32];
+
+/// Number of levels below the root; one per bit of a key.
+pub const TREE_DEPTH: usize = 256;
+
+/// SHA-256 over the concatenation of `parts`.
+pub fn sha256(parts: &[&[u8]]) -> MerkleHash {
+    let mut hasher = Sha256::new();
+    for part in parts {
+        hasher.update(part);
+    }
+    hasher.finalize().into()
+}
+
+/// Hash of a leaf holding `value_hash` under `key`.
+///
+/// EDUCATIONAL: Leaves and inner nodes use different prefixes (0x00 / 0x01)
+/// so an inner node can never be passed off as a leaf. The key is part of the
+/// leaf so a value cannot be moved to another position in the tree.
+pub fn leaf_hash(key: &MerkleHash, value_hash: &MerkleHash) -> MerkleHash {
+    sha256(&[&[0x00], key, value_hash])
+}
+
+/// Hash of an inner node from its two children.
+pub fn node_hash(left: &MerkleHash, right: &MerkleHash) -> MerkleHash {
+    sha256(&[&[0x01], left, right])
+}
+
+/// Hash of an empty subtree at each depth (index 256 is an empty leaf).
+///
+/// EDUCATIONAL: Almost every subtree of a sparse tree is empty, and all empty
+/// subtrees of the same height hash to the same value. Precomputing them means
+/// only the nodes on paths to real leaves ever have to be stored.
+pub fn empty_hashes() -> &'static [MerkleHash; TREE_DEPTH + 1] {
+    static EMPTY: OnceLock<[MerkleHash; TREE_DEPTH + 1]> = OnceLock::new();
+    EMPTY.get_or_init(|| {
+        let mut empty = [[0u8; 32]; TREE_DEPTH + 1];
+        for depth in (0..TREE_DEPTH).rev() {
+            empty[depth] = node_hash(&empty[depth + 1], &empty[depth + 1]);
+        }
+        empty
+    })
+}
+
+/// Bit `index` of `key`, counting from the most significant bit of byte 0.
+fn key_bit(key: &MerkleHash, index: usize) -> bool {
+    (key[index / 8] >> (7 - index % 8)) & 1 == 1
+}
+
+/// `key` with every bit from `depth` on cleared, naming its ancestor at `depth`.
+fn prefix(key: &MerkleHash, depth: usize) -> MerkleHash {
+    let mut out = [0u8; 32];
+    let full = depth / 8;
+    out[..full].copy_from_slice(&key[..full]);
+    if !depth.is_multiple_of(8) {
+        out[full] = key[full] & (0xff << (8 - depth % 8));
+    }
+    out
+}
+
+/// Flips bit `index` of `key`.
+fn flip(mut key: MerkleHash, index: usize) -> MerkleHash {
+    key[index / 8] ^= 1 << (7 - index % 8);
+    key
+}
+
+/// Sparse Merkle tree over 256-bit keys.
+///
+/// EDUCATIONAL PURPOSE: Conceptually this is a complete binary tree with a
+/// leaf for every possible key, where the bits of the key spell out the path
+/// from the root (0 = left, 1 = right). Because the shape never depends on
+/// which keys are present, the root is the same no matter in which order the
+/// leaves were written, and updating one leaf only rehashes the 256 nodes on
+/// its path.
+///
+/// Only non-empty nodes are stored, keyed by depth and path prefix.
+#[derive(Clone, Default)]
+pub struct SparseMerkleTree {
+    nodes: BTreeMap<(u16, MerkleHash), MerkleHash>,
+}
+
+impl SparseMerkleTree {
+    pub fn new() -> Self {
+        Self { nodes: BTreeMap::new() }
+    }
+
+    /// Root hash; an empty tree has `empty_hashes()[0]`.
+    pub fn root(&self) -> MerkleHash {
+        self.node(0, &[0u8; 32])
+    }
+
+    /// Stores `value_hash` under `key`, or removes the leaf when `None`.
+    pub fn update(&mut self, key: &MerkleHash, value_hash: Option<&MerkleHash>) {
+        let empty = empty_hashes();
+        let mut current = match value_hash {
+            Some(value_hash) => leaf_hash(key, value_hash),
+            None => empty[TREE_DEPTH],
+        };
+
+        for depth in (1..=TREE_DEPTH).rev() {
+            self.set_node(depth, prefix(key, depth), current);
+            let sibling = self.node(depth, &flip(prefix(key, depth), depth - 1));
+            current = if key_bit(key, depth - 1) {
+                node_hash(&sibling, &current)
+            } else {
+                node_hash(&current, &sibling)
+            };
+        }
+        self.set_node(0, [0u8; 32], current);
+    }
+
+    fn node(&self, depth: usize, path: &MerkleHash) -> MerkleHash {
+        self.nodes
+            .get(&(depth as u16, *path))
+            .copied()
+            .unwrap_or(empty_hashes()[depth])
+    }
+
+    /// Proof that `key` holds its current value (or is empty).
+    pub fn prove(&self, key: &MerkleHash) -> MerkleProof {
+        let empty = empty_hashes();
+        let mut proof = MerkleProof { bitmap: [0u8; 32], siblings: Vec::new() };
+        for (level, depth) in (1..=TREE_DEPTH).rev().enumerate() {
+            let sibling = self.node(depth, &flip(prefix(key, depth), depth - 1));
+            if sibling != empty[depth] {
+                proof.bitmap[level / 8] |= 1 << (7 - level % 8);
+                proof.siblings.push(sibling);
+            }
+        }
+        proof
+    }
+
+    /// Number of stored (non-empty) nodes.
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    fn set_node(&mut self, depth: usize, path: MerkleHash, hash: MerkleHash) {
+        if hash == empty_hashes()[depth] {
+            self.nodes.remove(&(depth as u16, path));
+        } else {
+            self.nodes.insert((depth as u16, path), hash);
+        }
+    }
+}
+
+/// The sibling hashes needed to recompute a root from a single leaf.
+///
+/// EDUCATIONAL PURPOSE: A proof always has one sibling per level, but in a
+/// sparse tree nearly all of them are empty subtrees whose hashes everyone
+/// already knows. Bit `i` of `bitmap` (level 0 is next to the leaf) says
+/// whether sibling `i` is non-empty; only those are carried in `siblings`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MerkleProof {
+    pub bitmap: [u8; 32],
+    pub siblings: Vec<MerkleHash>,
+}
+
+impl MerkleProof {
+    /// Root of the tree this proof describes, given the leaf at `key`
+    /// (`None` for an empty leaf). Returns `None` for a malformed proof.
+    pub fn compute_root(&self, key: &MerkleHash, value_hash: Option<&MerkleHash>) -> Option<MerkleHash> {
+        let empty = empty_hashes();
+        let mut current = match value_hash {
+            Some(value_hash) => leaf_hash(key, value_hash),
+            None => empty[TREE_DEPTH],
+        };
+
+        let mut siblings = self.siblings.iter();
+        for (level, depth) in (1..=TREE_DEPTH).rev().enumerate() {
+            let sibling = if key_bit(&self.bitmap, level) {
+                *siblings.next()?
+            } else {
+                empty[depth]
+            };
+            current = if key_bit(key, depth - 1) {
+                node_hash(&sibling, &current)
+            } else {
+                node_hash(&current, &sibling)
+            };
+        }
+
+        match siblings.next() {
+            Some(_) => None,
+            None => Some(current),
+        }
+    }
+}
+
+impl core::fmt::Debug for SparseMerkleTree {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("SparseMerkleTree")
+            .field("root", &hex::encode(self.root()))
+            .field("nodes", &self.nodes.len())
+            .finish()
+    }
+}
+
+/// Position of an account in the state tree.
+pub fn account_key(addr: &Address) -> MerkleHash {
+    sha256(&[&addr.0])
+}
+
+/// What an account's leaf commits to: every field, with code and storage
+/// represented by their hashes.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AccountLeaf {
+    pub nonce: u64,
+    pub balance: u128,
+    pub code_hash: MerkleHash,
+    pub is_contract: bool,
+    pub storage_root: MerkleHash,
+}
+
+impl AccountLeaf {
+    pub fn new(account: &Account, storage_root: MerkleHash) -> Self {
+        AccountLeaf {
+            nonce: account.nonce,
+            balance: account.balance,
+            code_hash: sha256(&[&account.code]),
+            is_contract: account.is_contract,
+            storage_root,
+        }
+    }
+
+    pub fn hash(&self) -> MerkleHash {
+        sha256(&[
+            &self.nonce.to_le_bytes(),
+            &self.balance.to_le_bytes(),
+            &self.code_hash,
+            &[self.is_contract as u8],
+            &self.storage_root,
+        ])
+    }
+}
+
+/// Position of a storage slot in its account's storage tree.
+///
+/// The domain is length-prefixed, so no (domain, key) pair can hash the
+/// same input as another.
+pub fn slot_key(slot: &StorageKey) -> MerkleHash {
+    sha256(&[&(slot.domain.len() as u32).to_le_bytes(), &slot.domain, &slot.key])
+}
+
+/// What a storage slot's leaf commits to.
+pub fn slot_value_hash(value: &[u8]) -> MerkleHash {
+    sha256(&[value])
+}
@@ -0,0 +1,84 @@
+use storage::StorageKey;
+use types::address::Address;
+
+use crate::merkle::{account_key, slot_key, slot_value_hash, AccountLeaf, MerkleHash, MerkleProof, SparseMerkleTree};
+use crate::State;
+
+/// Proof that an account has the contents in `leaf`, or does not exist.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AccountProof {
+    pub address: Address,
+    /// Committed account fields, `None` if the account does not exist
+    pub leaf: Option<AccountLeaf>,
+    pub proof: MerkleProof,
+}
+
+/// Proof that a storage slot holds `value`, or is empty.
+///
+/// EDUCATIONAL PURPOSE: Storage proofs come in two steps. The account proof
+/// ties the account (and with it its storage root) to the state root, and
+/// the slot proof ties the slot to that storage root. A light client holding
+/// only a block header can check both.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StorageProof {
+    pub account: AccountProof,
+    pub slot: StorageKey,
+    /// Value of the slot, `None` if it is empty
+    pub value: Option<Vec<u8>>,
+    pub proof: MerkleProof,
+}
+
+impl State {
+    /// Proves the committed contents (or absence) of `addr` against `state_root`.
+    ///
+    /// Returns `None` while `addr` has uncommitted changes: the Merkle path
+    /// comes from the committed tree, so the leaf must too, and the live
+    /// account is the only copy of its fields. Call `commit` first.
+    pub fn prove_account(&self, addr: &Address) -> Option<AccountProof> {
+        if self.is_dirty(addr) {
+            return None;
+        }
+        let leaf = self.accounts.get(addr).map(|acc| {
+            let storage_root = self.storage_root(addr).unwrap_or_else(|| SparseMerkleTree::new().root());
+            AccountLeaf::new(acc, storage_root)
+        });
+        Some(AccountProof { address: *addr, leaf, proof: self.account_tree.prove(&account_key(addr)) })
+    }
+
+    /// Proves the committed value (or absence) of a storage slot of `addr`.
+    ///
+    /// Like `prove_account`, returns `None` while `addr` has uncommitted changes.
+    pub fn prove_storage(&self, addr: &Address, domain: &[u8], key: &[u8]) -> Option<StorageProof> {
+        let account = self.prove_account(addr)?;
+        let slot = StorageKey::new(domain, key);
+        let value = self.accounts.get(addr).and_then(|acc| acc.storage.get(&slot)).cloned();
+        let proof = match self.storage_trees.get(addr) {
+            Some(tree) => tree.prove(&slot_key(&slot)),
+            None => SparseMerkleTree::new().prove(&slot_key(&slot)),
+        };
+        Some(StorageProof { account, slot, value, proof })
+    }
+}
+
+/// Checks an account proof against a trusted state root.
+pub fn verify_account_proof(state_root: &MerkleHash, proof: &AccountProof) -> bool {
+    let value_hash = proof.leaf.as_ref().map(|leaf| leaf.hash());
+    proof.proof.compute_root(&account_key(&proof.address), value_hash.as_ref()) == Some(*state_root)
+}
+
+/// Checks a storage proof against a trusted state root.
+///
+/// A missing account proves every one of its slots empty, so in that case
+/// only `value == None` is accepted.
+pub fn verify_storage_proof(state_root: &MerkleHash, proof: &StorageProof) -> bool {
+    if !verify_account_proof(state_root, &proof.account) {
+        return false;
+    }
+    let leaf = match &proof.account.leaf {
+        Some(leaf) => leaf,
+        None => return proof.value.is_none(),
+    };
+
+    let value_hash = proof.value.as_ref().map(|value| slot_value_hash(value));
+    proof.proof.compute_root(&slot_key(&proof.slot), value_hash.as_ref()) == Some(leaf.storage_root)
+}
@@ -1,10 +1,14 @@
+use core::convert::TryInto;
+use std::io;
 use std::rc::Rc;
-use std::collections::HashMap;
-use storage::Storage;
+use std::collections::{HashMap, HashSet};
+use storage::{StorageBackend, StorageKey};
 use crate::{Account};
+use crate::journal::{Checkpoint, Journal, JournalEntry};
+use crate::merkle::{account_key, slot_key, slot_value_hash, AccountLeaf, MerkleHash, SparseMerkleTree};
 use types::address::Address;
 use hex::encode as hex_encode;
-use alloc::collections::BTreeMap;
+use alloc::collections::{BTreeMap, BTreeSet};
 
 /// Represents the global state of the blockchain virtual machine.
 /// 
@@ -26,8 +30,8 @@ use alloc::collections::BTreeMap;
 /// - Gas costs are associated with state operations
 /// 
 /// DATA STRUCTURE: Uses a HashMap for O(1) account lookups by address.
-/// In production blockchains, this would be a more sophisticated data structure
-/// like a Merkle tree for efficient state proofs.
+/// Alongside it, sparse Merkle trees over the accounts and over each
+/// account's storage produce a `state_root` committing to all of it.
 /// 
 /// MEMORY MANAGEMENT: All accounts are kept in memory for fast access.
 /// In production systems, only frequently accessed accounts would be in memory,
@@ -48,6 +52,79 @@ pub struct State {
     /// entire blockchain state. Each entry contains an account with its
     /// balance, code, storage, and other metadata.
     pub accounts: HashMap<Address, Account>,
+
+    /// Log of changes not yet committed.
+    /// 
+    /// EDUCATIONAL: Every mutation made through the `State` API records the
+    /// previous value here, so a failed transaction (or a failed nested call)
+    /// can be rolled back with `revert_to`.
+    journal: Journal,
+
+    /// Sparse Merkle tree over every committed account.
+    pub(crate) account_tree: SparseMerkleTree,
+
+    /// One sparse Merkle tree per account over its committed storage slots.
+    pub(crate) storage_trees: HashMap<Address, SparseMerkleTree>,
+
+    /// Accounts that may have changed since the last commit.
+    /// 
+    /// EDUCATIONAL: `commit` only rehashes these, so the cost of keeping the
+    /// root up to date grows with what a transaction touched, not with the
+    /// size of the state.
+    touched: HashSet<Address>,
+
+    /// Where committed accounts are persisted, if anywhere.
+    backend: Option<Rc<dyn StorageBackend>>,
+}
+
+/// Backend domain under which account fields are persisted, keyed by address.
+const ACCOUNT_DOMAIN: &[u8] = b"account";
+/// Backend domain under which contract code is persisted, keyed by address.
+const CODE_DOMAIN: &[u8] = b"code";
+/// Each account's storage slots live in a backend domain of their own: this
+/// prefix followed by the address.
+const STORAGE_DOMAIN_PREFIX: &[u8] = b"storage";
+
+fn account_backend_key(addr: &Address) -> StorageKey {
+    StorageKey::new(ACCOUNT_DOMAIN, &addr.0)
+}
+
+fn code_backend_key(addr: &Address) -> StorageKey {
+    StorageKey::new(CODE_DOMAIN, &addr.0)
+}
+
+fn storage_backend_domain(addr: &Address) -> Vec<u8> {
+    [STORAGE_DOMAIN_PREFIX, &addr.0[..]].concat()
+}
+
+/// Backend key of one storage slot: the slot's domain (length-prefixed)
+/// followed by its key, inside the account's storage domain.
+fn slot_backend_key(addr: &Address, slot: &StorageKey) -> StorageKey {
+    let mut key = (slot.domain.len() as u32).to_le_bytes().to_vec();
+    key.extend_from_slice(&slot.domain);
+    key.extend_from_slice(&slot.key);
+    StorageKey { domain: storage_backend_domain(addr), key }
+}
+
+/// Inverse of `slot_backend_key` for a key listed in a storage domain.
+fn slot_from_backend_key(key: &[u8]) -> Option<StorageKey> {
+    let len = u32::from_le_bytes(key.get(..4)?.try_into().ok()?) as usize;
+    let rest = &key[4..];
+    if rest.len() < len {
+        return None;
+    }
+    let (domain, key) = rest.split_at(len);
+    Some(StorageKey::new(domain, key))
+}
+
+/// Writes the given slots of `acc` to `backend`, deleting those it no longer holds.
+fn write_slots<'a>(backend: &dyn StorageBackend, addr: &Address, acc: &Account, slots: impl IntoIterator<Item = &'a StorageKey>) {
+    for slot in slots {
+        match acc.storage.get(slot) {
+            Some(value) => backend.write(&slot_backend_key(addr, slot), value.clone()),
+            None => backend.delete(&slot_backend_key(addr, slot)),
+        }
+    }
 }
 
 impl State {
@@ -60,20 +137,57 @@ impl State {
     /// USAGE: Typically called when starting a new blockchain or when
     /// resetting the state for testing purposes.
     pub fn new() -> Self {
-        Self { accounts: HashMap::new() }
+        Self {
+            accounts: HashMap::new(),
+            journal: Journal::new(),
+            account_tree: SparseMerkleTree::new(),
+            storage_trees: HashMap::new(),
+            touched: HashSet::new(),
+            backend: None,
+        }
     }
 
-    /// Constructs a State from an existing Storage instance.
+    /// Loads every account persisted in `backend` and keeps it attached.
+    /// 
+    /// EDUCATIONAL PURPOSE: This is how a node survives a restart. Each
+    /// account's fields are stored in the "account" domain under its address,
+    /// its code in the "code" domain and every storage slot as a separate
+    /// entry in a domain of the account's own. From then on `commit` writes
+    /// back what it touched and `flush` makes those writes durable.
     /// 
-    /// EDUCATIONAL PURPOSE: This demonstrates how state can be reconstructed
-    /// from persistent storage. In real blockchains, the state is often
-    /// stored on disk and loaded into memory when needed.
+    /// ERRORS: Fails with `InvalidData` if a stored account or slot cannot be decoded.
+    pub fn new_from_storage(backend: Rc<dyn StorageBackend>) -> io::Result<Self> {
+        let mut state = Self::new();
+        for key in backend.keys_in_domain(ACCOUNT_DOMAIN) {
+            let invalid = || io::Error::new(io::ErrorKind::InvalidData, format!("corrupt account record 0x{}", hex_encode(&key)));
+            let addr = Address(key.as_slice().try_into().map_err(|_| invalid())?);
+            let bytes = backend.read(&account_backend_key(&addr)).ok_or_else(invalid)?;
+            let mut account = Account::decode(&bytes).ok_or_else(invalid)?;
+            account.code = backend.read(&code_backend_key(&addr)).unwrap_or_default();
+            for key in backend.keys_in_domain(&storage_backend_domain(&addr)) {
+                let slot = slot_from_backend_key(&key).ok_or_else(invalid)?;
+                let value = backend.read(&slot_backend_key(&addr, &slot)).ok_or_else(invalid)?;
+                account.storage.insert(slot, value);
+            }
+            state.accounts.insert(addr, account);
+            state.touched.insert(addr);
+        }
+
+        // Build the Merkle trees before attaching, so loading writes nothing back
+        state.commit();
+        state.backend = Some(backend);
+        Ok(state)
+    }
+
+    /// Makes every account written by `commit` durable in the backend.
     /// 
-    /// NOTE: This is currently a placeholder implementation that always
-    /// returns an empty state. In a real system, this would deserialize
-    /// the state from the provided storage.
-    pub fn new_from_storage(_storage: Rc<Storage>) -> Self {
-        Self { accounts: HashMap::new() }
+    /// USAGE: Call at a natural boundary such as the end of a block. A state
+    /// without a backend has nothing to flush.
+    pub fn flush(&self) -> io::Result<()> {
+        match &self.backend {
+            Some(backend) => backend.flush(),
+            None => Ok(()),
+        }
     }
 
     /// Retrieves an account by address (immutable reference).
@@ -125,7 +239,18 @@ impl State {
     /// - addr: The address of the account to retrieve or create
     /// 
     /// RETURNS: Mutable reference to the account (guaranteed to exist)
+    /// 
+    /// JOURNALING: Creating the account is journaled, but changes made through
+    /// the returned reference are not. Use `set_balance`, `set_nonce` and
+    /// `set_storage` for changes that must be undone when a transaction fails.
+    /// Storage in particular must only change through `set_storage` and code
+    /// through `deploy_contract`, which is how `commit` learns which slots to
+    /// rehash and what to persist.
     pub fn get_account_mut(&mut self, addr: &Address) -> &mut Account {
+        self.touched.insert(*addr);
+        if !self.accounts.contains_key(addr) {
+            self.journal.record(JournalEntry::AccountCreated(*addr));
+        }
         self.accounts.entry(*addr).or_insert_with(|| Account {
             nonce: 0,                    // No transactions yet
             balance: 0,                  // No initial balance
@@ -141,17 +266,13 @@ impl State {
     /// regular accounts (that hold value) and contract accounts (that hold code).
     /// This is a fundamental concept in blockchain systems.
     /// 
-    /// NOTE: This is currently a simplified implementation that always returns true.
-    /// In a real system, this would check if the account has code deployed.
-    /// 
     /// PARAMETERS:
-    /// - _addr: The address to check
+    /// - addr: The address to check
     /// 
     /// RETURNS: true if the address is a contract, false otherwise
-    pub fn is_contract(&self, _addr: Address) -> bool {
-        // EDUCATIONAL: In a real implementation, this would check if the account has code
-        // self.accounts.get(addr).map_or(false, |acc| acc.code.is_some())
-        return true;
+    /// (including when no account exists at `addr`)
+    pub fn is_contract(&self, addr: Address) -> bool {
+        self.get_account(&addr).is_some_and(|acc| acc.is_contract)
     }   
 
     /// Deploys a contract to a specific address.
@@ -173,17 +294,192 @@ impl State {
     /// - code: The bytecode of the contract to deploy
     pub fn deploy_contract(&mut self, addr: Address, code: Vec<u8>) {
         // EDUCATIONAL: Get or create the account at the specified address
-        let acc = self.accounts.entry(addr).or_insert_with(|| Account {
-            nonce: 0,                    // No transactions yet
-            balance: 0,                  // No initial balance
-            code: Vec::new(),            // No code initially
-            is_contract: false,          // Not a contract initially
-            storage: BTreeMap::new(),    // Empty storage
-        });
-        
-        // EDUCATIONAL: Set the contract code and mark as contract
-        acc.code = code;                 // Deploy the bytecode
-        acc.is_contract = true;          // Mark as contract account
+        let acc = self.get_account_mut(&addr);
+        let prev_code = core::mem::replace(&mut acc.code, code); // Deploy the bytecode
+        let prev_is_contract = core::mem::replace(&mut acc.is_contract, true); // Mark as contract account
+
+        self.journal.record(JournalEntry::CodeChanged { addr, prev_code, prev_is_contract });
+    }
+
+    /// Inserts a brand new account. Returns false if the address is taken.
+    /// 
+    /// JOURNALING: The creation is recorded, so reverting removes the account.
+    pub fn insert_account(&mut self, addr: Address, account: Account) -> bool {
+        if self.accounts.contains_key(&addr) {
+            return false;
+        }
+        self.journal.record(JournalEntry::AccountCreated(addr));
+        self.touched.insert(addr);
+        self.accounts.insert(addr, account);
+        true
+    }
+
+    /// Sets an account's balance, creating the account if needed.
+    pub fn set_balance(&mut self, addr: &Address, balance: u128) {
+        let acc = self.get_account_mut(addr);
+        let prev = core::mem::replace(&mut acc.balance, balance);
+        self.journal.record(JournalEntry::BalanceChanged { addr: *addr, prev });
+    }
+
+    /// Sets an account's nonce, creating the account if needed.
+    pub fn set_nonce(&mut self, addr: &Address, nonce: u64) {
+        let acc = self.get_account_mut(addr);
+        let prev = core::mem::replace(&mut acc.nonce, nonce);
+        self.journal.record(JournalEntry::NonceChanged { addr: *addr, prev });
+    }
+
+    /// Replaces an account's storage map, creating the account if needed.
+    pub fn set_storage(&mut self, addr: &Address, storage: BTreeMap<StorageKey, Vec<u8>>) {
+        let acc = self.get_account_mut(addr);
+        let prev = core::mem::replace(&mut acc.storage, storage);
+        self.journal.record(JournalEntry::StorageChanged { addr: *addr, prev });
+    }
+
+    /// Moves `amount` from one account's balance to another's.
+    /// 
+    /// RETURNS: false (and changes nothing) if `from` cannot cover `amount`
+    pub fn transfer(&mut self, from: &Address, to: &Address, amount: u128) -> bool {
+        let from_balance = self.get_account(from).map(|a| a.balance).unwrap_or(0);
+        if from_balance < amount {
+            return false;
+        }
+        self.set_balance(from, from_balance - amount);
+
+        let to_balance = self.get_account_mut(to).balance;
+        self.set_balance(to, to_balance.saturating_add(amount));
+        true
+    }
+
+    /// Returns a checkpoint that later changes can be reverted to.
+    /// 
+    /// EDUCATIONAL PURPOSE: The AVM takes a checkpoint when a transaction
+    /// starts. If execution fails, `revert_to` undoes everything since then;
+    /// otherwise `commit` makes the changes permanent.
+    pub fn checkpoint(&self) -> Checkpoint {
+        self.journal.checkpoint()
+    }
+
+    /// Undoes every change recorded after `checkpoint`, newest first.
+    pub fn revert_to(&mut self, checkpoint: Checkpoint) {
+        for entry in self.journal.drain_since(checkpoint) {
+            match entry {
+                JournalEntry::AccountCreated(addr) => {
+                    self.accounts.remove(&addr);
+                }
+                JournalEntry::BalanceChanged { addr, prev } => {
+                    if let Some(acc) = self.accounts.get_mut(&addr) {
+                        acc.balance = prev;
+                    }
+                }
+                JournalEntry::NonceChanged { addr, prev } => {
+                    if let Some(acc) = self.accounts.get_mut(&addr) {
+                        acc.nonce = prev;
+                    }
+                }
+                JournalEntry::StorageChanged { addr, prev } => {
+                    if let Some(acc) = self.accounts.get_mut(&addr) {
+                        acc.storage = prev;
+                    }
+                }
+                JournalEntry::CodeChanged { addr, prev_code, prev_is_contract } => {
+                    if let Some(acc) = self.accounts.get_mut(&addr) {
+                        acc.code = prev_code;
+                        acc.is_contract = prev_is_contract;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Makes every recorded change permanent, folds it into the state root and
+    /// writes touched accounts to the backend (if one is attached).
+    /// 
+    /// EDUCATIONAL PURPOSE: Each touched account has its leaf rehashed. For
+    /// storage, the oldest `StorageChanged` entry in the journal holds the
+    /// storage as of the previous commit, so only slots that differ from it
+    /// are written to the account's storage tree and to the backend. Code is
+    /// only written when the account is new or was redeployed.
+    pub fn commit(&mut self) {
+        // Entries come back newest first, so the last insert per address wins
+        let mut committed_storage = HashMap::new();
+        let mut code_changed = HashSet::new();
+        for entry in self.journal.drain_since(Checkpoint(0)) {
+            match entry {
+                JournalEntry::StorageChanged { addr, prev } => {
+                    committed_storage.insert(addr, prev);
+                }
+                JournalEntry::CodeChanged { addr, .. } => {
+                    code_changed.insert(addr);
+                }
+                _ => {}
+            }
+        }
+
+        for addr in core::mem::take(&mut self.touched) {
+            let acc = match self.accounts.get(&addr) {
+                Some(acc) => acc,
+                None => {
+                    // Created and reverted before ever being committed
+                    self.account_tree.update(&account_key(&addr), None);
+                    self.storage_trees.remove(&addr);
+                    if let Some(backend) = &self.backend {
+                        backend.delete(&account_backend_key(&addr));
+                        backend.delete(&code_backend_key(&addr));
+                    }
+                    continue;
+                }
+            };
+
+            // An account without a storage tree has never been committed
+            let is_new = !self.storage_trees.contains_key(&addr);
+            let changed: BTreeSet<&StorageKey> = match committed_storage.get(&addr) {
+                _ if is_new => acc.storage.keys().collect(),
+                Some(prev) => prev.keys().chain(acc.storage.keys())
+                    .filter(|key| prev.get(*key) != acc.storage.get(*key))
+                    .collect(),
+                None => BTreeSet::new(),
+            };
+
+            let tree = self.storage_trees.entry(addr).or_default();
+            for key in &changed {
+                let value = acc.storage.get(*key).map(|v| slot_value_hash(v));
+                tree.update(&slot_key(key), value.as_ref());
+            }
+
+            let value = AccountLeaf::new(acc, tree.root()).hash();
+            self.account_tree.update(&account_key(&addr), Some(&value));
+            if let Some(backend) = &self.backend {
+                backend.write(&account_backend_key(&addr), acc.encode());
+                if (is_new && !acc.code.is_empty()) || code_changed.contains(&addr) {
+                    backend.write(&code_backend_key(&addr), acc.code.clone());
+                }
+                write_slots(backend.as_ref(), &addr, acc, changed);
+            }
+        }
+    }
+
+    /// Root hash committing to every account and storage slot.
+    /// 
+    /// EDUCATIONAL: Two states have the same root exactly when they hold the
+    /// same accounts with the same contents, regardless of the order in which
+    /// they were built. Only committed changes are included.
+    pub fn state_root(&self) -> MerkleHash {
+        self.account_tree.root()
+    }
+
+    /// Root of an account's committed storage, if the account exists.
+    pub fn storage_root(&self, addr: &Address) -> Option<MerkleHash> {
+        self.storage_trees.get(addr).map(|tree| tree.root())
+    }
+
+    /// Number of uncommitted changes in the journal.
+    pub fn pending_changes(&self) -> usize {
+        self.journal.len()
+    }
+
+    /// True if `addr` may differ from its committed version.
+    pub fn is_dirty(&self, addr: &Address) -> bool {
+        self.touched.contains(addr)
     }
 
     /// Prints a human-readable representation of the current state.
@@ -215,74 +511,21 @@ impl State {
             
             // EDUCATIONAL: Display storage contents
             println!("      - Storage:");
-            for (key, value) in &acc.storage {
+            for (slot, value) in &acc.storage {
                 // EDUCATIONAL: Convert storage values to hexadecimal for readability
                 let value_hex: Vec<String> = value.iter().map(|b| format!("{:02x}", b)).collect();
                 
-                // Parse the key as "domain:key" format
-                if let Some((domain, key_part)) = Self::parse_domain_key(key) {
-                    if domain == "P" {
-                        // For persistent storage, treat key as ASCII
-                        if let Ok(ascii_key) = String::from_utf8(hex::decode(&key_part).unwrap_or_default()) {
-                            println!("          Key: {}:{} | Value ({} bytes): {}", domain, ascii_key, value.len(), value_hex.join(" "));
-                        } else {
-                            println!("          Key: {}:{} | Value ({} bytes): {}", domain, key_part, value.len(), value_hex.join(" "));
-                        }
-                    } else {
-                        // For storage maps, treat domain as ASCII and key as hex
-                        println!("          Key: {}:{} | Value ({} bytes): {}", domain, key_part, value.len(), value_hex.join(" "));
+                if slot.domain == b"P" {
+                    // For persistent storage, show the key as ASCII when it is text
+                    if let Ok(ascii_key) = core::str::from_utf8(&slot.key) {
+                        println!("          Key: P:{} | Value ({} bytes): {}", ascii_key, value.len(), value_hex.join(" "));
+                        continue;
                     }
-                } else {
-                    // Fall back to showing the raw key
-                    println!("          Key: {:<20} | Value ({} bytes): {}", key, value.len(), value_hex.join(" "));
                 }
+                println!("          Key: {} | Value ({} bytes): {}", slot, value.len(), value_hex.join(" "));
             }
             println!();
         }
         println!("--------------------");
     }
-    
-    /// Parses a storage key in "domain:key" format to extract domain and key components.
-    /// 
-    /// Storage keys are formatted as: "domain:key"
-    /// where domain is like "P" or "Balances" and key is hex-encoded
-    fn parse_domain_key(key: &str) -> Option<(String, String)> {
-        // Find the first colon to separate domain and key
-        if let Some(colon_pos) = key.find(':') {
-            let domain = key[..colon_pos].to_string();
-            let key_part = key[colon_pos + 1..].to_string();
-            return Some((domain, key_part));
-        }
-        
-        None
-    }
-    
-    /// Parses a storage map key to extract address and domain components.
-    /// 
-    /// Storage map keys are formatted as: [address_bytes][domain]
-    /// where address_bytes is 20 bytes and domain is like "-Balances"
-    fn parse_storage_map_key(key: &str) -> Option<(String, String)> {
-        // Check if the key is long enough to contain an address (20 bytes = 40 hex chars)
-        if key.len() < 40 {
-            return None;
-        }
-        
-        // Try to parse the first 40 characters as a hex address
-        if let Ok(address_bytes) = hex::decode(&key[..40]) {
-            if address_bytes.len() == 20 {
-                // Convert to proper address format
-                let address = format!("0x{}", &key[..40]);
-                
-                // Parse the domain (remaining hex characters)
-                let domain_hex = &key[40..];
-                if let Ok(domain_bytes) = hex::decode(domain_hex) {
-                    if let Ok(domain_str) = String::from_utf8(domain_bytes) {
-                        return Some((address, domain_str));
-                    }
-                }
-            }
-        }
-        
-        None
-    }
 }
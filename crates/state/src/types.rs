@@ -1,2 +1,2 @@
-pub type StorageKey = [u8; 32];
-pub type StorageValue = [u8; 32];
+pub use storage::StorageKey;
+pub type StorageValue = Vec<u8>;
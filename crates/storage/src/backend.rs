@@ -0,0 +1,62 @@
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+use core::fmt::Debug;
+use std::io;
+
+use crate::{Storage, StorageKey};
+
+/// Key-value store that state can be loaded from and flushed to.
+///
+/// EDUCATIONAL PURPOSE: The VM does not care where its data lives. Keeping
+/// the store behind a trait lets tests use a plain in-memory map while a
+/// devnet uses a file that survives restarts, without either knowing about
+/// the other.
+///
+/// Methods take `&self` (like `Storage::set`) so a backend can be shared
+/// through an `Rc`. Writes may be buffered; they are only guaranteed to be
+/// durable once `flush` returns `Ok`.
+pub trait StorageBackend: Debug {
+    /// Returns the value stored under `key`, if any.
+    fn read(&self, key: &StorageKey) -> Option<Vec<u8>>;
+
+    /// Stores `value` under `key`, replacing any previous value.
+    fn write(&self, key: &StorageKey, value: Vec<u8>);
+
+    /// Removes `key`; removing a missing key is not an error.
+    fn delete(&self, key: &StorageKey);
+
+    /// Every key stored in `domain`, in ascending order.
+    fn keys_in_domain(&self, domain: &[u8]) -> Vec<Vec<u8>>;
+
+    /// Makes every write so far durable.
+    fn flush(&self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// The in-memory map: nothing to flush, nothing survives a restart.
+impl StorageBackend for Storage {
+    fn read(&self, key: &StorageKey) -> Option<Vec<u8>> {
+        self.map.borrow().get(key).cloned()
+    }
+
+    fn write(&self, key: &StorageKey, value: Vec<u8>) {
+        self.map.borrow_mut().insert(key.clone(), value);
+    }
+
+    fn delete(&self, key: &StorageKey) {
+        self.map.borrow_mut().remove(key);
+    }
+
+    fn keys_in_domain(&self, domain: &[u8]) -> Vec<Vec<u8>> {
+        keys_in_domain(&self.map.borrow(), domain)
+    }
+}
+
+/// Keys of `domain` in a map ordered by `StorageKey` (domain first).
+pub(crate) fn keys_in_domain<V>(map: &BTreeMap<StorageKey, V>, domain: &[u8]) -> Vec<Vec<u8>> {
+    map.range(StorageKey::new(domain, &[])..)
+        .take_while(|(slot, _)| slot.domain == domain)
+        .map(|(slot, _)| slot.key.clone())
+        .collect()
+}
@@ -0,0 +1,196 @@
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+use core::cell::{Cell, RefCell};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use crate::backend::{keys_in_domain, StorageBackend};
+use crate::StorageKey;
+
+const RECORD_WRITE: u8 = 1;
+const RECORD_DELETE: u8 = 0;
+
+/// Compaction runs once the log holds this many times more records than
+/// there are live keys (and at least `COMPACT_MIN_RECORDS` records).
+const COMPACT_RATIO: usize = 4;
+const COMPACT_MIN_RECORDS: usize = 1024;
+
+/// Durable backend: an append-only log on disk with an in-memory index.
+///
+/// EDUCATIONAL PURPOSE: Appending is the simplest way to write to disk
+/// safely. A record is never modified after it is written, so a crash can at
+/// worst leave the last record half-written, and that tail is simply dropped
+/// when the file is reopened. The price is that overwritten values keep
+/// taking space, which `compact` reclaims by rewriting only the live entries
+/// to a new file and atomically renaming it over the old one.
+///
+/// RECORD FORMAT (little endian):
+/// - write:  [1][domain_len: u32][domain][key_len: u32][key][value_len: u32][value]
+/// - delete: [0][domain_len: u32][domain][key_len: u32][key]
+///
+/// Reads are served from the in-memory map, which is rebuilt by replaying
+/// the log on `open`. Writes are buffered until `flush`.
+#[derive(Debug)]
+pub struct FileStorage {
+    path: PathBuf,
+    map: RefCell<BTreeMap<StorageKey, Vec<u8>>>,
+    /// Encoded records not yet appended to the file
+    pending: RefCell<Vec<u8>>,
+    /// Records in the file plus those pending
+    log_records: Cell<usize>,
+}
+
+impl FileStorage {
+    /// Opens (or creates) the log at `path` and replays it.
+    ///
+    /// A truncated record at the end of the file (from a crash during a
+    /// write) is discarded and cut off; any other malformed data is an error.
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let bytes = match fs::read(&path) {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(e),
+        };
+
+        let mut map = BTreeMap::new();
+        let mut records = 0;
+        let mut pos = 0;
+        while pos < bytes.len() {
+            match Self::replay_record(&bytes[pos..], &mut map)? {
+                Some(len) => {
+                    pos += len;
+                    records += 1;
+                }
+                None => break,
+            }
+        }
+        if pos < bytes.len() {
+            OpenOptions::new().write(true).open(&path)?.set_len(pos as u64)?;
+        }
+
+        Ok(Self {
+            path,
+            map: RefCell::new(map),
+            pending: RefCell::new(Vec::new()),
+            log_records: Cell::new(records),
+        })
+    }
+
+    /// Path of the log file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Rewrites the log so it holds exactly one record per live key.
+    ///
+    /// The new log is written next to the old one and renamed over it, so
+    /// the file on disk is always either the old or the new log in full.
+    pub fn compact(&self) -> io::Result<()> {
+        let mut log = Vec::new();
+        for (key, value) in self.map.borrow().iter() {
+            Self::encode_record(&mut log, key, Some(value));
+        }
+
+        let tmp = self.path.with_extension("compacting");
+        let mut file = File::create(&tmp)?;
+        file.write_all(&log)?;
+        file.sync_all()?;
+        fs::rename(&tmp, &self.path)?;
+
+        self.pending.borrow_mut().clear();
+        self.log_records.set(self.map.borrow().len());
+        Ok(())
+    }
+
+    fn append(&self, key: &StorageKey, value: Option<&[u8]>) {
+        Self::encode_record(&mut self.pending.borrow_mut(), key, value);
+        self.log_records.set(self.log_records.get() + 1);
+    }
+
+    fn encode_record(buf: &mut Vec<u8>, key: &StorageKey, value: Option<&[u8]>) {
+        fn field(buf: &mut Vec<u8>, data: &[u8]) {
+            buf.extend_from_slice(&(data.len() as u32).to_le_bytes());
+            buf.extend_from_slice(data);
+        }
+        buf.push(if value.is_some() { RECORD_WRITE } else { RECORD_DELETE });
+        field(buf, &key.domain);
+        field(buf, &key.key);
+        if let Some(value) = value {
+            field(buf, value);
+        }
+    }
+
+    /// Applies the record at the start of `bytes` and returns its length, or
+    /// `None` if the record is cut short.
+    fn replay_record(bytes: &[u8], map: &mut BTreeMap<StorageKey, Vec<u8>>) -> io::Result<Option<usize>> {
+        fn field(bytes: &[u8], pos: &mut usize) -> Option<Vec<u8>> {
+            let len = u32::from_le_bytes(bytes.get(*pos..*pos + 4)?.try_into().ok()?) as usize;
+            let data = bytes.get(*pos + 4..*pos + 4 + len)?.to_vec();
+            *pos += 4 + len;
+            Some(data)
+        }
+        let invalid = |msg: &str| io::Error::new(io::ErrorKind::InvalidData, format!("storage log: {}", msg));
+
+        let tag = bytes[0];
+        let mut pos = 1;
+        let key = match tag {
+            RECORD_WRITE | RECORD_DELETE => {
+                let Some(domain) = field(bytes, &mut pos) else { return Ok(None) };
+                let Some(key) = field(bytes, &mut pos) else { return Ok(None) };
+                StorageKey { domain, key }
+            }
+            tag => return Err(invalid(&format!("unknown record tag {}", tag))),
+        };
+
+        if tag == RECORD_WRITE {
+            let Some(value) = field(bytes, &mut pos) else { return Ok(None) };
+            map.insert(key, value);
+        } else {
+            map.remove(&key);
+        }
+        Ok(Some(pos))
+    }
+}
+
+impl StorageBackend for FileStorage {
+    fn read(&self, key: &StorageKey) -> Option<Vec<u8>> {
+        self.map.borrow().get(key).cloned()
+    }
+
+    fn write(&self, key: &StorageKey, value: Vec<u8>) {
+        self.append(key, Some(&value));
+        self.map.borrow_mut().insert(key.clone(), value);
+    }
+
+    fn delete(&self, key: &StorageKey) {
+        if self.map.borrow_mut().remove(key).is_some() {
+            self.append(key, None);
+        }
+    }
+
+    fn keys_in_domain(&self, domain: &[u8]) -> Vec<Vec<u8>> {
+        keys_in_domain(&self.map.borrow(), domain)
+    }
+
+    /// Appends buffered records and syncs them, compacting if the log has
+    /// grown much larger than the live data.
+    fn flush(&self) -> io::Result<()> {
+        let live = self.map.borrow().len();
+        let records = self.log_records.get();
+        if records >= COMPACT_MIN_RECORDS && records > live * COMPACT_RATIO {
+            return self.compact();
+        }
+
+        let mut pending = self.pending.borrow_mut();
+        if pending.is_empty() {
+            return Ok(());
+        }
+        let mut file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+        file.write_all(&pending)?;
+        file.sync_data()?;
+        pending.clear();
+        Ok(())
+    }
+}
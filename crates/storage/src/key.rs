@@ -0,0 +1,62 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+
+/// A storage slot address: a domain and a key within it, both raw bytes.
+///
+/// EDUCATIONAL PURPOSE: Contracts name storage by `(domain, key)`, e.g.
+/// `("Balances", owner_address)`. Keeping the two parts separate (instead of
+/// gluing them into one string) means no domain can ever collide with
+/// another, whatever bytes either part contains, and keys never need to be
+/// re-encoded on their way from the guest to the state.
+///
+/// ORDERING: Slots sort by domain first and then by key, so all the slots of
+/// one domain are adjacent.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct StorageKey {
+    pub domain: Vec<u8>,
+    pub key: Vec<u8>,
+}
+
+impl StorageKey {
+    pub fn new(domain: &[u8], key: &[u8]) -> Self {
+        Self { domain: domain.to_vec(), key: key.to_vec() }
+    }
+
+    /// Converts a key from the old `"domain:hex(key)"` string format.
+    ///
+    /// MIGRATION: Before keys were binary, the VM stored every slot under
+    /// such a string. Anything persisted in that format can be converted with
+    /// this. The hex key never contains ':', so the domain ends at the last
+    /// one and domains that contain ':' themselves still convert correctly.
+    pub fn from_legacy(legacy: &str) -> Option<Self> {
+        let (domain, key) = legacy.rsplit_once(':')?;
+        let key = (0..key.len())
+            .step_by(2)
+            .map(|i| key.get(i..i + 2).and_then(|byte| u8::from_str_radix(byte, 16).ok()))
+            .collect::<Option<Vec<u8>>>()?;
+        Some(Self { domain: domain.as_bytes().to_vec(), key })
+    }
+
+    /// Domain as text for display (lossy for non UTF-8 domains).
+    pub fn domain_str(&self) -> String {
+        String::from_utf8_lossy(&self.domain).into_owned()
+    }
+}
+
+/// Prints as `domain:0x<hex key>`, e.g. `Balances:0xa1a1..`.
+impl fmt::Display for StorageKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:0x", self.domain_str())?;
+        for byte in &self.key {
+            write!(f, "{:02x}", byte)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for StorageKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "StorageKey({})", self)
+    }
+}
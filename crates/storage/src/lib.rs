@@ -2,7 +2,16 @@
 
 extern crate alloc;
 
+pub mod backend;
+pub mod file_storage;
+pub mod key;
+
+pub use backend::StorageBackend;
+pub use file_storage::FileStorage;
+pub use key::StorageKey;
+
 use core::cell::RefCell;
+use core::ops::Bound;
 use alloc::collections::BTreeMap;
 use alloc::vec::Vec;
 use alloc::string::String;
@@ -19,7 +28,8 @@ use alloc::string::String;
 /// - Persistent across VM restarts
 /// - Thread-safe access using RefCell
 /// - Ordered storage using BTreeMap
-/// - Domain-based storage organization
+/// - Domain-based storage organization: every slot is a binary
+///   `(domain, key)` pair (see `StorageKey`)
 /// 
 /// REAL-WORLD BLOCKCHAIN COMPARISON:
 /// In Ethereum, storage is organized differently:
@@ -32,9 +42,8 @@ use alloc::string::String;
 /// lookups. The RefCell wrapper provides interior mutability, allowing
 /// the storage to be modified even when borrowed immutably.
 /// 
-/// PERSISTENCE: This is an in-memory implementation. In production,
-/// data would be persisted to disk or a database to survive system
-/// restarts and crashes.
+/// PERSISTENCE: This is an in-memory implementation. It also serves as the
+/// in-memory `StorageBackend`; `FileStorage` is the durable one.
 /// 
 /// THREAD SAFETY: The current implementation is not thread-safe. In a
 /// real blockchain, storage would need to handle concurrent access
@@ -47,7 +56,7 @@ pub struct Storage {
     /// iteration, which is useful for debugging and deterministic
     /// behavior. RefCell allows interior mutability while maintaining
     /// Rust's borrowing rules at runtime.
-    pub map: RefCell<BTreeMap<String, Vec<u8>>>,
+    pub map: RefCell<BTreeMap<StorageKey, Vec<u8>>>,
 }
 
 impl Storage {
@@ -78,30 +87,13 @@ impl Storage {
     /// 
     /// PARAMETERS:
     /// - initial: Pre-existing key-value pairs to populate storage
-    pub fn with_map(initial: BTreeMap<String, Vec<u8>>) -> Self {
+    pub fn with_map(initial: BTreeMap<StorageKey, Vec<u8>>) -> Self {
         Self {
             map: RefCell::new(initial),
         }
     }
 
-    /// Builds a composite key from domain and key components.
-    /// 
-    /// EDUCATIONAL PURPOSE: This demonstrates how to create hierarchical
-    /// storage keys that include domain information for better organization.
-    /// 
-    /// KEY FORMAT: The composite key is formatted as "domain:key" to
-    /// ensure proper separation and avoid key collisions between domains.
-    /// 
-    /// PARAMETERS:
-    /// - domain: The storage domain (e.g., contract name, module name)
-    /// - key: The specific key within that domain
-    /// 
-    /// RETURNS: A composite key string in the format "domain:key"
-    fn build_composite_key(domain: &str, key: &str) -> String {
-        format!("{}:{}", domain, key)
-    }
-
-    /// Retrieves a value from storage by domain and key.
+    /// Retrieves the value stored in a slot.
     /// 
     /// EDUCATIONAL PURPOSE: This demonstrates safe storage access with domain
     /// separation. Returns None if the key doesn't exist, which is common in
@@ -115,16 +107,14 @@ impl Storage {
     /// memory management to avoid copying large values.
     /// 
     /// PARAMETERS:
-    /// - domain: The storage domain
-    /// - key: The storage key to look up within the domain
+    /// - slot: The domain and key to look up
     /// 
     /// RETURNS: Some(value) if the key exists, None otherwise
-    pub fn get(&self, domain: &str, key: &str) -> Option<Vec<u8>> {
-        let composite_key = Self::build_composite_key(domain, key);
-        self.map.borrow().get(&composite_key).cloned()
+    pub fn get(&self, slot: &StorageKey) -> Option<Vec<u8>> {
+        self.map.borrow().get(slot).cloned()
     }
 
-    /// Stores a value in storage with the specified domain and key.
+    /// Stores a value in a slot.
     /// 
     /// EDUCATIONAL PURPOSE: This demonstrates persistent storage updates with
     /// domain organization. In blockchain systems, storage changes are part
@@ -135,17 +125,53 @@ impl Storage {
     /// to the storage map. This allows modification while maintaining
     /// Rust's borrowing rules.
     /// 
-    /// KEY OWNERSHIP: The composite key is converted to a String to ensure
-    /// ownership. In a production system, you might use more efficient
-    /// key representations or avoid unnecessary allocations.
+    /// KEY OWNERSHIP: The slot is stored as given, holding the domain and key
+    /// bytes exactly as the guest passed them, with no re-encoding.
     /// 
     /// PARAMETERS:
-    /// - domain: The storage domain
-    /// - key: The storage key within the domain
+    /// - slot: The domain and key to store under
     /// - value: The data to store
-    pub fn set(&self, domain: &str, key: &str, value: Vec<u8>) {
-        let composite_key = Self::build_composite_key(domain, key);
-        self.map.borrow_mut().insert(composite_key, value);
+    pub fn set(&self, slot: StorageKey, value: Vec<u8>) {
+        self.map.borrow_mut().insert(slot, value);
+    }
+
+    /// Removes a value from storage, returning it if it was present.
+    /// 
+    /// EDUCATIONAL PURPOSE: Removal deletes the entry outright rather than
+    /// leaving an empty value behind, so the slot no longer counts towards
+    /// the account's storage (or its state root) once the call commits.
+    /// 
+    /// PARAMETERS:
+    /// - slot: The domain and key to remove
+    pub fn remove(&self, slot: &StorageKey) -> Option<Vec<u8>> {
+        self.map.borrow_mut().remove(slot)
+    }
+
+    /// Returns up to `limit` entries of `domain` that come after `cursor`.
+    ///
+    /// EDUCATIONAL PURPOSE: This is cursor-based paging. Each call hands back
+    /// one bounded batch in ascending key order; passing the last key of a
+    /// batch as the next cursor continues where it left off. Because keys are
+    /// ordered bytewise, every node walks a domain in exactly the same order.
+    ///
+    /// PARAMETERS:
+    /// - domain: The storage domain to walk
+    /// - cursor: Key to start after, or `None` to start at the first key
+    /// - limit: Maximum number of entries to return
+    ///
+    /// RETURNS: `(key, value)` pairs, fewer than `limit` once the domain ends
+    pub fn entries_after(&self, domain: &[u8], cursor: Option<&[u8]>, limit: usize) -> Vec<(Vec<u8>, Vec<u8>)> {
+        let start = match cursor {
+            Some(key) => Bound::Excluded(StorageKey::new(domain, key)),
+            None => Bound::Included(StorageKey::new(domain, &[])),
+        };
+        self.map
+            .borrow()
+            .range((start, Bound::Unbounded))
+            .take_while(|(slot, _)| slot.domain == domain)
+            .take(limit)
+            .map(|(slot, value)| (slot.key.clone(), value.clone()))
+            .collect()
     }
 
     /// Dumps the contents of persistent storage for debugging.
@@ -162,9 +188,8 @@ impl Storage {
     pub fn dump(&self) {
         println!("--- Storage Dump ---");
         for (key, value) in self.map.borrow().iter() {
-            let key_str = key;
             let value_hex: Vec<String> = value.iter().map(|b| format!("{:02x}", b)).collect();
-            println!("Key: {:<20} | Value ({} bytes): {}", key_str, value.len(), value_hex.join(" "));
+            println!("Key: {:<20} | Value ({} bytes): {}", key.to_string(), value.len(), value_hex.join(" "));
         }
         println!("--------------------");
     }
@@ -0,0 +1,113 @@
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+
+use storage::{FileStorage, Storage, StorageBackend, StorageKey};
+
+fn k(key: &str) -> StorageKey {
+    StorageKey::new(b"D", key.as_bytes())
+}
+
+fn temp_log(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("avm-storage-{}-{}.log", name, std::process::id()));
+    let _ = fs::remove_file(&path);
+    path
+}
+
+#[test]
+fn test_writes_survive_reopen_after_flush() {
+    let path = temp_log("reopen");
+    {
+        let store = FileStorage::open(&path).unwrap();
+        store.write(&k("a"), vec![1]);
+        store.write(&k("b"), vec![2]);
+        store.write(&k("a"), vec![3]);
+        store.delete(&k("b"));
+        store.flush().unwrap();
+        store.write(&k("c"), vec![4]); // never flushed
+    }
+
+    let store = FileStorage::open(&path).unwrap();
+    assert_eq!(store.read(&k("a")), Some(vec![3]));
+    assert_eq!(store.read(&k("b")), None);
+    assert_eq!(store.read(&k("c")), None);
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_torn_tail_is_dropped() {
+    let path = temp_log("torn");
+    {
+        let store = FileStorage::open(&path).unwrap();
+        store.write(&k("key"), vec![7; 8]);
+        store.flush().unwrap();
+    }
+    let good_len = fs::metadata(&path).unwrap().len();
+
+    // A write record that stops halfway through its value
+    let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+    file.write_all(&[1, 1, 0, 0, 0, b'D', 1, 0, 0, 0, b'x', 8, 0, 0, 0, 1, 2]).unwrap();
+    drop(file);
+
+    let store = FileStorage::open(&path).unwrap();
+    assert_eq!(store.read(&k("key")), Some(vec![7; 8]));
+    assert_eq!(store.read(&k("x")), None);
+    assert_eq!(fs::metadata(&path).unwrap().len(), good_len);
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_compaction_keeps_live_entries_only() {
+    let path = temp_log("compact");
+    let store = FileStorage::open(&path).unwrap();
+    for i in 0..100u8 {
+        store.write(&k("counter"), vec![i]);
+    }
+    store.write(&k("other"), vec![1]);
+    store.flush().unwrap();
+    let before = fs::metadata(&path).unwrap().len();
+
+    store.compact().unwrap();
+    assert!(fs::metadata(&path).unwrap().len() < before);
+
+    let reopened = FileStorage::open(&path).unwrap();
+    assert_eq!(reopened.read(&k("counter")), Some(vec![99]));
+    assert_eq!(reopened.read(&k("other")), Some(vec![1]));
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_domain_scan() {
+    let path = temp_log("domain");
+    let backends: Vec<Box<dyn StorageBackend>> = vec![Box::new(Storage::new()), Box::new(FileStorage::open(&path).unwrap())];
+    for backend in backends {
+        backend.write(&StorageKey::new(b"account", &[2]), vec![]);
+        backend.write(&StorageKey::new(b"account", &[1]), vec![]);
+        backend.write(&StorageKey::new(b"accounts", &[0]), vec![]);
+        backend.write(&StorageKey::new(b"", b"account"), vec![]);
+        assert_eq!(backend.keys_in_domain(b"account"), vec![vec![1], vec![2]]);
+    }
+    let _ = fs::remove_file(&path);
+}
+
+#[test]
+fn test_domains_may_contain_separator() {
+    // The old "domain:hex(key)" strings could not be split back reliably
+    // once a domain contained ':'
+    let storage = Storage::new();
+    storage.set(StorageKey::new(b"a", b"b:c"), vec![1]);
+    storage.set(StorageKey::new(b"a:b", b"c"), vec![2]);
+    assert_eq!(storage.get(&StorageKey::new(b"a", b"b:c")), Some(vec![1]));
+    assert_eq!(storage.get(&StorageKey::new(b"a:b", b"c")), Some(vec![2]));
+}
+
+#[test]
+fn test_from_legacy() {
+    assert_eq!(StorageKey::from_legacy("Balances:a1ff"), Some(StorageKey::new(b"Balances", &[0xa1, 0xff])));
+    assert_eq!(StorageKey::from_legacy("P:"), Some(StorageKey::new(b"P", &[])));
+    assert_eq!(StorageKey::from_legacy("no-separator"), None);
+    assert_eq!(StorageKey::from_legacy("P:abc"), None);
+    assert_eq!(StorageKey::from_legacy("P:zz"), None);
+    // Hex keys never contain the separator, so a domain may
+    assert_eq!(StorageKey::from_legacy("a:b:0c"), Some(StorageKey::new(b"a:b", &[0x0c])));
+}
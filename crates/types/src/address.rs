@@ -1,6 +1,5 @@
 use core::fmt;
 use crate::O;
-use crate::SerializeField;
 
 #[derive(Clone, Copy, PartialEq, Eq, Hash)]
 #[repr(C)]
@@ -45,15 +44,3 @@ impl AsRef<[u8]> for Address {
         &self.0
     }
 }
-
-impl SerializeField for Address {
-    fn serialize_field(&self, buf: &mut [u8], offset: &mut usize) {
-        let bytes = &self.0;
-        if *offset + 20 <= buf.len() {
-            buf[*offset..*offset + 20].copy_from_slice(bytes);
-            *offset += 20;
-        } else {
-            panic!("Buffer overflow in Address serialization");
-        }
-    }
-}
\ No newline at end of file
@@ -0,0 +1,323 @@
+//! The ABI encoding, shared by guest programs and host tools.
+//!
+//! EDUCATIONAL PURPOSE: a call's arguments are written by one program (a
+//! test, a client, another contract) and read by a different one. If each
+//! side hand-packs bytes, the two only agree by convention and a changed
+//! argument type is found at runtime, if at all. Implementing both sides
+//! on the same trait for the same Rust type makes them agree by
+//! construction.
+//!
+//! ENCODING (the layout documented on `compiler::ParamType`):
+//! - Integers are fixed-width little-endian, two's complement when signed
+//! - `bool` is one byte, 0 or 1
+//! - `Address` is its 20 raw bytes
+//! - `[T; N]` is its N elements back to back, so `[u8; N]` is N raw bytes
+//! - Slices, `Vec`, `str` and `String` are a u32 length, then the elements
+//! - Tuples are their members back to back, in order
+//! - `Option<T>` is one byte, 0 for `None` or 1 followed by the value. It is
+//!   not an ABI type, but lets contracts store optional fields
+//!
+//! DECODING is length-checked: reading past the end, an invalid `bool` or
+//! invalid UTF-8 returns `None` instead of producing a garbage value.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::address::Address;
+
+/// A value with an ABI encoding.
+pub trait Encode {
+    /// Number of bytes `encode_into` writes.
+    fn encoded_len(&self) -> usize;
+
+    /// Writes the encoding of `self` at `buf[*offset..]`, advancing the offset.
+    ///
+    /// Panics if `buf` is too short; check `encoded_len` first.
+    fn encode_into(&self, buf: &mut [u8], offset: &mut usize);
+
+    /// Appends the encoding of `self` to `out`.
+    fn encode(&self, out: &mut Vec<u8>) {
+        let mut offset = out.len();
+        out.resize(offset + self.encoded_len(), 0);
+        self.encode_into(out, &mut offset);
+    }
+
+    /// Encodes `self` into a fresh buffer.
+    fn to_encoded_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        self.encode(&mut out);
+        out
+    }
+}
+
+/// A value that can be read back from its ABI encoding.
+///
+/// The lifetime lets `&[u8]` and `&str` borrow from the input instead of
+/// copying it.
+pub trait Decode<'a>: Sized {
+    /// Reads a value from the front of `input`, advancing it past the bytes used.
+    fn decode(input: &mut &'a [u8]) -> Option<Self>;
+
+    /// Decodes a value that must take up all of `bytes`.
+    fn decode_exact(mut bytes: &'a [u8]) -> Option<Self> {
+        let value = Self::decode(&mut bytes)?;
+        if !bytes.is_empty() {
+            return None;
+        }
+        Some(value)
+    }
+}
+
+/// Splits `len` bytes off the front of `input`.
+fn take<'a>(input: &mut &'a [u8], len: usize) -> Option<&'a [u8]> {
+    if input.len() < len {
+        return None;
+    }
+    let (head, rest) = input.split_at(len);
+    *input = rest;
+    Some(head)
+}
+
+/// Reads a u32 length prefix.
+fn take_len(input: &mut &[u8]) -> Option<usize> {
+    u32::decode(input).map(|len| len as usize)
+}
+
+/// Copies `bytes` into `buf` at `*offset`, advancing the offset.
+fn put(buf: &mut [u8], offset: &mut usize, bytes: &[u8]) {
+    let end = *offset + bytes.len();
+    if end > buf.len() {
+        panic!("buffer too small for encoded value");
+    }
+    buf[*offset..end].copy_from_slice(bytes);
+    *offset = end;
+}
+
+fn encode_len_prefix(len: usize, buf: &mut [u8], offset: &mut usize) {
+    (len as u32).encode_into(buf, offset);
+}
+
+macro_rules! impl_codec_for_int {
+    ($($ty:ty),*) => {
+        $(
+            impl Encode for $ty {
+                fn encoded_len(&self) -> usize {
+                    core::mem::size_of::<$ty>()
+                }
+
+                fn encode_into(&self, buf: &mut [u8], offset: &mut usize) {
+                    put(buf, offset, &self.to_le_bytes());
+                }
+            }
+
+            impl<'a> Decode<'a> for $ty {
+                fn decode(input: &mut &'a [u8]) -> Option<Self> {
+                    let bytes = take(input, core::mem::size_of::<$ty>())?;
+                    Some(<$ty>::from_le_bytes(bytes.try_into().ok()?))
+                }
+            }
+        )*
+    };
+}
+
+impl_codec_for_int!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128);
+
+impl Encode for bool {
+    fn encoded_len(&self) -> usize {
+        1
+    }
+
+    fn encode_into(&self, buf: &mut [u8], offset: &mut usize) {
+        put(buf, offset, &[*self as u8]);
+    }
+}
+
+impl<'a> Decode<'a> for bool {
+    fn decode(input: &mut &'a [u8]) -> Option<Self> {
+        match take(input, 1)?[0] {
+            0 => Some(false),
+            1 => Some(true),
+            _ => None,
+        }
+    }
+}
+
+impl Encode for Address {
+    fn encoded_len(&self) -> usize {
+        20
+    }
+
+    fn encode_into(&self, buf: &mut [u8], offset: &mut usize) {
+        put(buf, offset, &self.0);
+    }
+}
+
+impl<'a> Decode<'a> for Address {
+    fn decode(input: &mut &'a [u8]) -> Option<Self> {
+        Some(Address(take(input, 20)?.try_into().ok()?))
+    }
+}
+
+impl<T: Encode, const N: usize> Encode for [T; N] {
+    fn encoded_len(&self) -> usize {
+        self.iter().map(Encode::encoded_len).sum()
+    }
+
+    fn encode_into(&self, buf: &mut [u8], offset: &mut usize) {
+        for item in self {
+            item.encode_into(buf, offset);
+        }
+    }
+}
+
+impl<'a, T: Decode<'a>, const N: usize> Decode<'a> for [T; N] {
+    fn decode(input: &mut &'a [u8]) -> Option<Self> {
+        let mut items = Vec::with_capacity(N);
+        for _ in 0..N {
+            items.push(T::decode(input)?);
+        }
+        items.try_into().ok()
+    }
+}
+
+impl<T: Encode> Encode for [T] {
+    fn encoded_len(&self) -> usize {
+        4 + self.iter().map(Encode::encoded_len).sum::<usize>()
+    }
+
+    fn encode_into(&self, buf: &mut [u8], offset: &mut usize) {
+        encode_len_prefix(self.len(), buf, offset);
+        for item in self {
+            item.encode_into(buf, offset);
+        }
+    }
+}
+
+impl<T: Encode> Encode for Vec<T> {
+    fn encoded_len(&self) -> usize {
+        self.as_slice().encoded_len()
+    }
+
+    fn encode_into(&self, buf: &mut [u8], offset: &mut usize) {
+        self.as_slice().encode_into(buf, offset);
+    }
+}
+
+impl<'a, T: Decode<'a>> Decode<'a> for Vec<T> {
+    fn decode(input: &mut &'a [u8]) -> Option<Self> {
+        let len = take_len(input)?;
+        // Every element takes at least one byte, so a bogus length cannot
+        // make us reserve more than the input could hold
+        let mut items = Vec::with_capacity(len.min(input.len()));
+        for _ in 0..len {
+            items.push(T::decode(input)?);
+        }
+        Some(items)
+    }
+}
+
+impl<'a> Decode<'a> for &'a [u8] {
+    fn decode(input: &mut &'a [u8]) -> Option<Self> {
+        let len = take_len(input)?;
+        take(input, len)
+    }
+}
+
+impl Encode for str {
+    fn encoded_len(&self) -> usize {
+        self.as_bytes().encoded_len()
+    }
+
+    fn encode_into(&self, buf: &mut [u8], offset: &mut usize) {
+        self.as_bytes().encode_into(buf, offset);
+    }
+}
+
+impl Encode for String {
+    fn encoded_len(&self) -> usize {
+        self.as_str().encoded_len()
+    }
+
+    fn encode_into(&self, buf: &mut [u8], offset: &mut usize) {
+        self.as_str().encode_into(buf, offset);
+    }
+}
+
+impl<'a> Decode<'a> for &'a str {
+    fn decode(input: &mut &'a [u8]) -> Option<Self> {
+        core::str::from_utf8(<&[u8]>::decode(input)?).ok()
+    }
+}
+
+impl<'a> Decode<'a> for String {
+    fn decode(input: &mut &'a [u8]) -> Option<Self> {
+        <&str>::decode(input).map(String::from)
+    }
+}
+
+impl<T: Encode + ?Sized> Encode for &T {
+    fn encoded_len(&self) -> usize {
+        (**self).encoded_len()
+    }
+
+    fn encode_into(&self, buf: &mut [u8], offset: &mut usize) {
+        (**self).encode_into(buf, offset);
+    }
+}
+
+impl<T: Encode> Encode for Option<T> {
+    fn encoded_len(&self) -> usize {
+        1 + self.as_ref().map_or(0, Encode::encoded_len)
+    }
+
+    fn encode_into(&self, buf: &mut [u8], offset: &mut usize) {
+        match self {
+            Some(value) => {
+                put(buf, offset, &[1]);
+                value.encode_into(buf, offset);
+            }
+            None => put(buf, offset, &[0]),
+        }
+    }
+}
+
+impl<'a, T: Decode<'a>> Decode<'a> for Option<T> {
+    fn decode(input: &mut &'a [u8]) -> Option<Self> {
+        match take(input, 1)?[0] {
+            0 => Some(None),
+            1 => T::decode(input).map(Some),
+            _ => None,
+        }
+    }
+}
+
+macro_rules! impl_codec_for_tuple {
+    ($($name:ident),+) => {
+        impl<$($name: Encode),+> Encode for ($($name,)+) {
+            #[allow(non_snake_case)]
+            fn encoded_len(&self) -> usize {
+                let ($($name,)+) = self;
+                0 $(+ $name.encoded_len())+
+            }
+
+            #[allow(non_snake_case)]
+            fn encode_into(&self, buf: &mut [u8], offset: &mut usize) {
+                let ($($name,)+) = self;
+                $($name.encode_into(buf, offset);)+
+            }
+        }
+
+        impl<'a, $($name: Decode<'a>),+> Decode<'a> for ($($name,)+) {
+            fn decode(input: &mut &'a [u8]) -> Option<Self> {
+                Some(($($name::decode(input)?,)+))
+            }
+        }
+    };
+}
+
+impl_codec_for_tuple!(A);
+impl_codec_for_tuple!(A, B);
+impl_codec_for_tuple!(A, B, C);
+impl_codec_for_tuple!(A, B, C, D);
+impl_codec_for_tuple!(A, B, C, D, E);
+impl_codec_for_tuple!(A, B, C, D, E, F);
@@ -1,5 +1,7 @@
 #![no_std]  
 
+extern crate alloc;
+
 pub mod address;
 pub use address::Address;
 
@@ -10,11 +12,6 @@ pub use result::Result;
 pub mod o;
 pub use o::*; // Allow `$crate::O` in macros
 
-pub mod primitives;
-pub use primitives::*; 
-
-// used for serialization
-pub trait SerializeField {
-    /// Appends `self` into `buf` at `*offset`, advancing the offset.
-    fn serialize_field(&self, buf: &mut [u8], offset: &mut usize);
-}
\ No newline at end of file
+// ABI encoding of arguments, results and event fields
+pub mod codec;
+pub use codec::{Decode, Encode};
@@ -10,6 +10,8 @@ use crate::registers::Register;
 use core::fmt::Write;
 use std::collections::HashMap;
 use crate::instruction::CsrOp;
+use crate::gas::{syscall_payload_len, GasCosts, GasMeter, InstructionClass, SyscallGas};
+use crate::trap::{AccessKind, VmTrap};
 
 /// Represents the Central Processing Unit (CPU) of our RISC-V virtual machine.
 /// 
@@ -75,6 +77,13 @@ pub struct CPU {
 
     /// Minimal CSR storage for CSR instructions
     pub csrs: HashMap<u16, u32>,
+
+    /// Price list used to charge gas for every executed instruction
+    pub gas_costs: GasCosts,
+
+    /// Gas budget this CPU draws from
+    /// EDUCATIONAL: Shared with nested VMs so a whole call tree has one budget
+    pub gas: Rc<RefCell<GasMeter>>,
 }
 
 impl std::fmt::Debug for CPU {
@@ -84,6 +93,7 @@ impl std::fmt::Debug for CPU {
             .field("regs", &self.regs)
             .field("verbose", &self.verbose)
             .field("reservation_addr", &self.reservation_addr)
+            .field("gas", &self.gas)
             .field("verbose_writer", &self.verbose_writer.as_ref().map(|_| "Some(<writer>)"))
             .finish()
     }
@@ -99,6 +109,7 @@ impl CPU {
     /// - PC starts at 0 (first instruction)
     /// - All registers start at 0 (except x0 which is always 0)
     /// - Verbose logging is disabled by default
+    /// - Gas is unlimited until a limit is set
     pub fn new(syscall_handler: Box<dyn SyscallHandler>) -> Self {
         Self {
             pc: 0,
@@ -108,6 +119,8 @@ impl CPU {
             reservation_addr: None,
             verbose_writer: None,
             csrs: HashMap::new(),
+            gas_costs: GasCosts::default(),
+            gas: Rc::new(RefCell::new(GasMeter::unlimited())),
         }
     }
     
@@ -149,6 +162,48 @@ impl CPU {
         self.csrs.insert(csr, value);
     }
 
+    /// Charges gas for an instruction before it executes.
+    ///
+    /// EDUCATIONAL: Charging up front means an instruction that cannot be
+    /// paid for never runs. System calls pay their instruction cost plus a
+    /// base cost and a per-byte cost for the guest memory they consume.
+    ///
+    /// RETURN VALUE: Returns an `OutOfGas` trap when the budget is exhausted
+    fn charge_gas(&mut self, instr: &Instruction) -> Result<(), VmTrap> {
+        let class = InstructionClass::of(instr);
+        let mut cost = self.gas_costs.instruction_cost(class);
+        if class == InstructionClass::Ecall {
+            let call_id = self.regs[Register::A7 as usize];
+            let args = [
+                self.regs[Register::A1 as usize],
+                self.regs[Register::A2 as usize],
+                self.regs[Register::A3 as usize],
+                self.regs[Register::A4 as usize],
+                self.regs[Register::A5 as usize],
+                self.regs[Register::A6 as usize],
+            ];
+            let payload_len = syscall_payload_len(call_id, &args, &self.regs);
+            cost = cost.saturating_add(self.gas_costs.syscall_cost(payload_len));
+        }
+
+        let charged = self.gas.borrow_mut().charge(cost);
+        charged.map_err(|err| {
+            self.log(&format!(
+                "⛽ Out of gas at PC = 0x{:08x}: requested {}, remaining {}",
+                self.pc, err.requested, err.remaining
+            ), false);
+            VmTrap::OutOfGas(err)
+        })
+    }
+
+    /// Atomic memory operations require naturally aligned addresses.
+    fn check_aligned(addr: usize, size: usize, kind: AccessKind) -> Result<(), VmTrap> {
+        if !addr.is_multiple_of(size) {
+            return Err(VmTrap::MisalignedAccess { addr, size, kind });
+        }
+        Ok(())
+    }
+
     /// Executes a single instruction cycle (fetch, decode, execute).
     /// 
     /// EDUCATIONAL PURPOSE: This is the heart of the CPU - the instruction cycle.
@@ -166,10 +221,14 @@ impl CPU {
     ///   control flow, etc.)
     /// 
     /// ERROR HANDLING: If an invalid instruction is encountered, the CPU
-    /// handles it gracefully by calling unknown_instruction() which provides
-    /// debugging information and halts execution safely.
+    /// handles it gracefully by calling unknown_instruction() which builds
+    /// a `VmTrap` describing the failure instead of panicking.
+    /// 
+    /// RETURN VALUE: Returns Ok(true) if execution should continue, Ok(false)
+    /// to halt normally, or Err(trap) when execution must stop abnormally
     /// 
-    /// RETURN VALUE: Returns true if execution should continue, false to halt
+    /// GAS: Every instruction is charged against `gas` before it runs. When
+    /// the budget is exhausted the CPU stops with an `OutOfGas` trap.
     /// 
     /// MEMORY ACCESS: Uses shared references to memory and storage to allow
     /// the CPU to read/write while maintaining Rust's safety guarantees.
@@ -183,19 +242,25 @@ impl CPU {
         memory: Rc<RefCell<MemoryPage>>,
         storage: Rc<RefCell<Storage>>,
         host: &mut Box<dyn HostInterface>,
-    ) -> bool {
+    ) -> Result<bool, VmTrap> {
+        // EDUCATIONAL: Instructions are at least 2-byte aligned (RV32C)
+        Self::check_aligned(self.pc as usize, 2, AccessKind::Fetch)?;
+
         // EDUCATIONAL: Step 1 - Fetch and decode the next instruction
         let instr = self.next_instruction(Rc::clone(&memory));
         
         // EDUCATIONAL: Step 2 - Execute the instruction or handle errors
         match instr {
             Some((instr, size)) => {
+                // EDUCATIONAL: Pay for the instruction before executing it
+                self.charge_gas(&instr)?;
+
                 // Valid instruction found - execute it
                 self.run_instruction(instr, size, Rc::clone(&memory), storage, host)
             }
             None => {
                 // No valid instruction found - handle the error
-                self.unknown_instruction(Rc::clone(&memory), storage)
+                Err(self.unknown_instruction(Rc::clone(&memory), storage))
             }
         }
     }
@@ -220,7 +285,7 @@ impl CPU {
         size: u8, 
         memory: Rc<RefCell<MemoryPage>>, 
         storage: Rc<RefCell<Storage>>,
-        host: &mut Box<dyn HostInterface>) -> bool {
+        host: &mut Box<dyn HostInterface>) -> Result<bool, VmTrap> {
         // EDUCATIONAL: Debug output to help understand what's happening
         // Get the actual instruction bytes for debugging
         if let Some(bytes) = memory.borrow().mem_slice(self.pc as usize, self.pc as usize + size as usize) {
@@ -234,14 +299,14 @@ impl CPU {
         let old_pc = self.pc;
         
         // EDUCATIONAL: Execute the instruction
-        let result = self.execute(instr, memory, storage, host);      
+        let result = self.execute(instr, memory, storage, host)?;
 
         // EDUCATIONAL: Only increment PC if the instruction didn't change it
         // This handles branches, jumps, and calls correctly
         if self.pc == old_pc {
             self.pc = self.pc.wrapping_add(size as u32);
         }
-        result
+        Ok(result)
     }
 
     /// Handles unknown or invalid instructions.
@@ -250,31 +315,18 @@ impl CPU {
     /// When a CPU encounters an invalid instruction, it needs to handle it
     /// gracefully rather than crashing.
     /// 
-    /// DEBUGGING: This function provides detailed information about what
-    /// went wrong, including the hex dump of the invalid bytes.
+    /// DEBUGGING: The returned trap carries the PC and the raw bytes that
+    /// failed to decode, so the embedder can report what went wrong.
     /// 
-    /// RETURN VALUE: Returns false to halt execution on invalid instructions
-    fn unknown_instruction(&mut self, memory: Rc<RefCell<MemoryPage>>, _storage: Rc<RefCell<Storage>>) -> bool {
+    /// RETURN VALUE: Returns the trap that halts execution
+    fn unknown_instruction(&mut self, memory: Rc<RefCell<MemoryPage>>, _storage: Rc<RefCell<Storage>>) -> VmTrap {
         // EDUCATIONAL: Try to read the invalid instruction bytes for debugging
-        if let Some(slice_ref) = memory.borrow().mem_slice(self.pc as usize, self.pc as usize + 4) {
-            // EDUCATIONAL: Convert bytes to hex for human-readable debugging
-            let hex_dump = slice_ref.iter()
-                .map(|b| format!("{:02x}", b)) // still needs deref
-                .collect::<Vec<_>>()
-                .join(" ");
-
-            panic!(
-                "🚨 Unknown or invalid instruction at PC = 0x{:08x} (bytes: [{}])",
-                self.pc,
-                hex_dump
-            );
-        } else {
-            panic!(
-                "🚨 Unknown or invalid instruction at PC = 0x{:08x} (could not read memory)",
-                self.pc
-            );
+        let pc = self.pc as usize;
+        let bytes = memory.borrow().mem_slice(pc, pc + 4).map(|slice| slice.to_vec());
+        match bytes {
+            Some(bytes) => VmTrap::IllegalInstruction { pc: self.pc, bytes },
+            None => VmTrap::OutOfBounds { addr: pc, size: 4, kind: AccessKind::Fetch },
         }
-        false
     }
 
     /// Fetches and decodes the next instruction from memory.
@@ -344,13 +396,16 @@ impl CPU {
     /// - rs1, rs2: Source registers (operands)
     /// - imm: Immediate value (constant)
     /// 
-    /// RETURN VALUE: Returns true to continue execution, false to halt
+    /// RETURN VALUE: Returns Ok(true) to continue execution, Ok(false) to
+    /// halt, or Err(trap) on memory faults, failed syscalls and unsupported
+    /// instructions
     pub fn execute(
         &mut self, 
         instr: Instruction, 
         memory: Rc<RefCell<MemoryPage>>, 
         storage: Rc<RefCell<Storage>>,
-        host: &mut Box<dyn HostInterface>) -> bool {
+        host: &mut Box<dyn HostInterface>) -> Result<bool, VmTrap> {
+        let pc = self.pc;
         match instr {
             // EDUCATIONAL: Arithmetic instructions - perform mathematical operations
             Instruction::Add { rd, rs1, rs2 } => {
@@ -416,38 +471,38 @@ impl CPU {
                 // EDUCATIONAL: Load word (32-bit) from memory
                 // Address = base register + offset
                 let addr = self.regs[rs1].wrapping_add(offset as u32) as usize;
-                self.write_reg(rd, memory.borrow().load_u32(addr));
+                self.write_reg(rd, memory.borrow().load_u32(addr)?);
             }
             Instruction::Ld { rd, rs1, offset } => {
                 // EDUCATIONAL: Load doubleword (64-bit) from memory, truncated to 32-bit
                 // Since this is a 32-bit VM, we only load the lower 32 bits
                 let addr = self.regs[rs1].wrapping_add(offset as u32) as usize;
-                self.write_reg(rd, memory.borrow().load_u32(addr));
+                self.write_reg(rd, memory.borrow().load_u32(addr)?);
             }
             Instruction::Lb { rd, rs1, offset } => {
                 // EDUCATIONAL: Load byte (8-bit, sign-extended)
                 let addr = self.regs[rs1].wrapping_add(offset as u32) as usize;
-                let byte = memory.borrow().load_byte(addr);
+                let byte = memory.borrow().load_byte(addr)?;
                 let value = (byte as i8) as i32 as u32; // sign-extend to 32-bit
                 self.write_reg(rd, value);
             }
             Instruction::Lbu { rd, rs1, offset } => {
                 // EDUCATIONAL: Load byte unsigned (8-bit, zero-extended)
                 let addr = self.regs[rs1].wrapping_add(offset as u32) as usize;
-                let byte = memory.borrow().load_byte(addr);
+                let byte = memory.borrow().load_byte(addr)?;
                 self.write_reg(rd, byte as u32);
             }
             Instruction::Lh { rd, rs1, offset } => {
                 // EDUCATIONAL: Load halfword (16-bit, sign-extended)
                 let addr = self.regs[rs1].wrapping_add(offset as u32) as usize;
-                let halfword = memory.borrow().load_halfword(addr);
+                let halfword = memory.borrow().load_halfword(addr)?;
                 let value = (halfword as i16) as i32 as u32; // sign-extend to 32-bit
                 self.write_reg(rd, value);
             }
             Instruction::Lhu { rd, rs1, offset } => {
                 // EDUCATIONAL: Load halfword unsigned (16-bit, zero-extended)
                 let addr = self.regs[rs1].wrapping_add(offset as u32) as usize;
-                let halfword = memory.borrow().load_halfword(addr);
+                let halfword = memory.borrow().load_halfword(addr)?;
                 self.write_reg(rd, halfword as u32); // zero-extend to 32-bit
             }
 
@@ -455,17 +510,17 @@ impl CPU {
             Instruction::Sh { rs1, rs2, offset } => {
                 // EDUCATIONAL: Store halfword (16-bit)
                 let addr = self.regs[rs1].wrapping_add(offset as u32) as usize;
-                memory.borrow_mut().store_u16(addr, (self.regs[rs2] & 0xFFFF) as u16);
+                memory.borrow_mut().store_u16(addr, (self.regs[rs2] & 0xFFFF) as u16)?;
             }
             Instruction::Sw { rs1, rs2, offset } => {
                 // EDUCATIONAL: Store word (32-bit)
                 let addr = self.regs[rs1].wrapping_add(offset as u32) as usize;
-                memory.borrow_mut().store_u32(addr, self.regs[rs2]);
+                memory.borrow_mut().store_u32(addr, self.regs[rs2])?;
             }
             Instruction::Sb { rs1, rs2, offset } => {
                 // EDUCATIONAL: Store byte (8-bit)
                 let addr = self.regs[rs1].wrapping_add(offset as u32) as usize;
-                memory.borrow_mut().store_u8(addr, (self.regs[rs2] & 0xFF) as u8);
+                memory.borrow_mut().store_u8(addr, (self.regs[rs2] & 0xFF) as u8)?;
             }
         
             // EDUCATIONAL: Branch instructions - conditionally change the PC
@@ -474,35 +529,35 @@ impl CPU {
                 // EDUCATIONAL: Branch if equal - jump if two registers are equal
                 if self.regs[rs1] == self.regs[rs2] {
                     self.pc = self.pc.wrapping_add(offset as u32);
-                    return true;
+                    return Ok(true);
                 }
             }
             Instruction::Bne { rs1, rs2, offset } => {
                 // EDUCATIONAL: Branch if not equal
                 if self.regs[rs1] != self.regs[rs2] {
                     self.pc = self.pc.wrapping_add(offset as u32);
-                    return true;
+                    return Ok(true);
                 }
             }
             Instruction::Blt { rs1, rs2, offset } => {
                 // EDUCATIONAL: Branch if less than (signed comparison)
                 if (self.regs[rs1] as i32) < (self.regs[rs2] as i32) {
                     self.pc = self.pc.wrapping_add(offset as u32);
-                    return true;
+                    return Ok(true);
                 }
             }
             Instruction::Bge { rs1, rs2, offset } => {
                 // EDUCATIONAL: Branch if greater than or equal (signed)
                 if (self.regs[rs1] as i32) >= (self.regs[rs2] as i32) {
                     self.pc = self.pc.wrapping_add(offset as u32);
-                    return true;
+                    return Ok(true);
                 }
             }
             Instruction::Bltu { rs1, rs2, offset } => {
                 // EDUCATIONAL: Branch if less than (unsigned comparison)
                 if self.regs[rs1] < self.regs[rs2] {
                     self.pc = self.pc.wrapping_add(offset as u32);
-                    return true;
+                    return Ok(true);
                 }
             }
 
@@ -510,7 +565,7 @@ impl CPU {
                 // EDUCATIONAL: Branch if greater than or equal (unsigned)
                 if self.regs[rs1] >= self.regs[rs2] {
                     self.pc = self.pc.wrapping_add(offset as u32);
-                    return true;
+                    return Ok(true);
                 }
             }
             // EDUCATIONAL: Jump and Link instructions - for function calls
@@ -521,7 +576,7 @@ impl CPU {
                 let return_address = if compressed { self.pc + 2 } else { self.pc + 4 };
                 self.write_reg(rd, return_address);
                 self.pc = self.pc.wrapping_add(offset as u32);
-                return true;
+                return Ok(true);
             }
             Instruction::Jalr { rd, rs1, offset , compressed} => {
                 // EDUCATIONAL: JALR (Jump and Link Register) - indirect function calls
@@ -537,7 +592,7 @@ impl CPU {
                 self.write_reg(rd, return_address);
 
                 self.pc = target;
-                return true;
+                return Ok(true);
             }
 
             // EDUCATIONAL: Load Upper Immediate - loads immediate into upper bits
@@ -643,9 +698,10 @@ impl CPU {
                     self.regs[Register::A6 as usize],
                 ];
                 let call_id = self.regs[Register::A7 as usize];
-                let (result, cont) = self.syscall_handler.handle_syscall(call_id, args, memory, storage, host, &mut self.regs);
+                let gas = SyscallGas::new(self.gas.clone(), &self.gas_costs);
+                let (result, cont) = self.syscall_handler.handle_syscall(call_id, args, memory, storage, host, &mut self.regs, &gas)?;
                 self.regs[Register::A0 as usize] = result;
-                return cont;
+                return Ok(cont);
             }
             Instruction::Csr { rd, rs1, csr, op, imm } => {
                 let src = if imm { rs1 as u32 } else { self.regs[rs1] };
@@ -682,29 +738,29 @@ impl CPU {
             Instruction::Ebreak => {
                 // EDUCATIONAL: EBREAK - Environment Break - for debugging
                 // In real systems, this would trigger a debugger breakpoint
-                return false
+                return Ok(false);
             }
             Instruction::Mret => {
                 // Treat MRET as a simple return/halt in this VM
-                return false;
+                return Ok(false);
             }
             
             // EDUCATIONAL: Compressed instruction set (RV32C) - space-saving instructions
             Instruction::Jr { rs1 } => {
                 // EDUCATIONAL: JR (Jump Register) - compressed jump to register
                 self.pc = self.regs[rs1];
-                return true;
+                return Ok(true);
             }
             Instruction::Ret => {
                 // EDUCATIONAL: RET - compressed return instruction
                 // Equivalent to JR x1 (jump to return address register)
                 let target = self.regs[1]; // x1 = ra (return address)
                 if target == 0 || target == 0xFFFF_FFFF {
-                    return false; // halt if ret target is 0 or invalid
+                    return Ok(false); // halt if ret target is 0 or invalid
                 }
     
                 self.pc = target;
-                return true;
+                return Ok(true);
             }
             Instruction::Mv { rd, rs2 } => {
                 // EDUCATIONAL: MV (Move) - compressed register copy
@@ -728,14 +784,14 @@ impl CPU {
                 // EDUCATIONAL: BEQZ - Branch if Equal to Zero (compressed)
                 if self.regs[rs1] == 0 {
                     self.pc = self.pc.wrapping_add(offset as u32);
-                    return true;
+                    return Ok(true);
                 }
             }
             Instruction::Bnez { rs1, offset } => {
                 // EDUCATIONAL: BNEZ - Branch if Not Equal to Zero (compressed)
                 if self.regs[rs1] != 0 {
                     self.pc = self.pc.wrapping_add(offset as u32);
-                    return true;
+                    return Ok(true);
                 }
             }
 
@@ -769,82 +825,93 @@ impl CPU {
             // ===== RV32A (Atomics) =====
             Instruction::AmoswapW { rd, rs1, rs2 } => {
                 let addr = self.regs[rs1] as usize;
-                let orig = memory.borrow().load_u32(addr);
-                memory.borrow_mut().store_u32(addr, self.regs[rs2]);
+                Self::check_aligned(addr, 4, AccessKind::Store)?;
+                let orig = memory.borrow().load_u32(addr)?;
+                memory.borrow_mut().store_u32(addr, self.regs[rs2])?;
                 self.write_reg(rd, orig);
             }
             Instruction::AmoaddW { rd, rs1, rs2 } => {
                 let addr = self.regs[rs1] as usize;
-                let orig = memory.borrow().load_u32(addr);
+                Self::check_aligned(addr, 4, AccessKind::Store)?;
+                let orig = memory.borrow().load_u32(addr)?;
                 let new_val = orig.wrapping_add(self.regs[rs2]);
-                memory.borrow_mut().store_u32(addr, new_val);
+                memory.borrow_mut().store_u32(addr, new_val)?;
                 self.write_reg(rd, orig);
             }
             Instruction::AmoandW { rd, rs1, rs2 } => {
                 let addr = self.regs[rs1] as usize;
-                let orig = memory.borrow().load_u32(addr);
+                Self::check_aligned(addr, 4, AccessKind::Store)?;
+                let orig = memory.borrow().load_u32(addr)?;
                 let new_val = orig & self.regs[rs2];
-                memory.borrow_mut().store_u32(addr, new_val);
+                memory.borrow_mut().store_u32(addr, new_val)?;
                 self.write_reg(rd, orig);
             }
             Instruction::AmoorW { rd, rs1, rs2 } => {
                 let addr = self.regs[rs1] as usize;
-                let orig = memory.borrow().load_u32(addr);
+                Self::check_aligned(addr, 4, AccessKind::Store)?;
+                let orig = memory.borrow().load_u32(addr)?;
                 let new_val = orig | self.regs[rs2];
-                memory.borrow_mut().store_u32(addr, new_val);
+                memory.borrow_mut().store_u32(addr, new_val)?;
                 self.write_reg(rd, orig);
             }
             Instruction::AmoxorW { rd, rs1, rs2 } => {
                 let addr = self.regs[rs1] as usize;
-                let orig = memory.borrow().load_u32(addr);
+                Self::check_aligned(addr, 4, AccessKind::Store)?;
+                let orig = memory.borrow().load_u32(addr)?;
                 let new_val = orig ^ self.regs[rs2];
-                memory.borrow_mut().store_u32(addr, new_val);
+                memory.borrow_mut().store_u32(addr, new_val)?;
                 self.write_reg(rd, orig);
             }
             Instruction::AmomaxW { rd, rs1, rs2 } => {
                 let addr = self.regs[rs1] as usize;
-                let orig = memory.borrow().load_u32(addr);
+                Self::check_aligned(addr, 4, AccessKind::Store)?;
+                let orig = memory.borrow().load_u32(addr)?;
                 let new_val = if (orig as i32) > (self.regs[rs2] as i32) { orig } else { self.regs[rs2] };
-                memory.borrow_mut().store_u32(addr, new_val);
+                memory.borrow_mut().store_u32(addr, new_val)?;
                 self.write_reg(rd, orig);
             }
             Instruction::AmominW { rd, rs1, rs2 } => {
                 let addr = self.regs[rs1] as usize;
-                let orig = memory.borrow().load_u32(addr);
+                Self::check_aligned(addr, 4, AccessKind::Store)?;
+                let orig = memory.borrow().load_u32(addr)?;
                 let new_val = if (orig as i32) < (self.regs[rs2] as i32) { orig } else { self.regs[rs2] };
-                memory.borrow_mut().store_u32(addr, new_val);
+                memory.borrow_mut().store_u32(addr, new_val)?;
                 self.write_reg(rd, orig);
             }
             Instruction::AmomaxuW { rd, rs1, rs2 } => {
                 let addr = self.regs[rs1] as usize;
-                let orig = memory.borrow().load_u32(addr);
+                Self::check_aligned(addr, 4, AccessKind::Store)?;
+                let orig = memory.borrow().load_u32(addr)?;
                 let new_val = if orig > self.regs[rs2] { orig } else { self.regs[rs2] };
-                memory.borrow_mut().store_u32(addr, new_val);
+                memory.borrow_mut().store_u32(addr, new_val)?;
                 self.write_reg(rd, orig);
             }
             Instruction::AmominuW { rd, rs1, rs2 } => {
                 let addr = self.regs[rs1] as usize;
-                let orig = memory.borrow().load_u32(addr);
+                Self::check_aligned(addr, 4, AccessKind::Store)?;
+                let orig = memory.borrow().load_u32(addr)?;
                 let new_val = if orig < self.regs[rs2] { orig } else { self.regs[rs2] };
-                memory.borrow_mut().store_u32(addr, new_val);
+                memory.borrow_mut().store_u32(addr, new_val)?;
                 self.write_reg(rd, orig);
             }
             // ===== RV32A (LR/SC) =====
             Instruction::LrW { rd, rs1 } => {
                 let addr = self.regs[rs1] as usize;
-                let value = memory.borrow().load_u32(addr);
+                Self::check_aligned(addr, 4, AccessKind::Load)?;
+                let value = memory.borrow().load_u32(addr)?;
                 self.write_reg(rd, value);
                 // Set reservation for this address
                 self.reservation_addr = Some(addr);
             }
             Instruction::ScW { rd, rs1, rs2 } => {
                 let addr = self.regs[rs1] as usize;
+                Self::check_aligned(addr, 4, AccessKind::Store)?;
                 let value_to_store = self.regs[rs2];
                 
                 // Check if we have a valid reservation for this address
                 if self.reservation_addr == Some(addr) {
                     // Reservation is valid, perform the store
-                    memory.borrow_mut().store_u32(addr, value_to_store);
+                    memory.borrow_mut().store_u32(addr, value_to_store)?;
                     self.write_reg(rd, 0); // 0 = success
                     // Clear the reservation (it's consumed)
                     self.reservation_addr = None;
@@ -853,9 +920,12 @@ impl CPU {
                     self.write_reg(rd, 1); // 1 = failure
                 }
             }
-            _ => todo!("unhandled instruction"),
+            _ => {
+                let bytes = memory.borrow().mem_slice(pc as usize, pc as usize + 4).map(|slice| slice.to_vec()).unwrap_or_default();
+                return Err(VmTrap::IllegalInstruction { pc, bytes });
+            }
         }
-        true
+        Ok(true)
 
     }               
 }
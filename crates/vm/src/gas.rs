@@ -0,0 +1,254 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::instruction::Instruction;
+use crate::registers::Register;
+use crate::sys_call::{
+    SYSCALL_CALL_PROGRAM, SYSCALL_FIRE_EVENT, SYSCALL_LOG, SYSCALL_PANIC, SYSCALL_STORAGE_DELETE,
+    SYSCALL_STORAGE_GET, SYSCALL_STORAGE_NEXT, SYSCALL_STORAGE_SET,
+};
+
+/// Gas cost table used by the CPU to price every executed instruction.
+///
+/// EDUCATIONAL PURPOSE: Blockchains cannot rely on a program terminating on
+/// its own, so every step of execution has a price. The interpreter charges
+/// the cost of an instruction *before* executing it; once the budget is used
+/// up, execution stops with an out-of-gas outcome instead of looping forever.
+///
+/// PRICING MODEL:
+/// - Instructions are grouped into classes (ALU, load/store, branch, mul/div, ecall)
+/// - System calls additionally pay a base cost plus a cost per byte of
+///   guest memory they read (keys, values, log payloads, call input, events)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GasCosts {
+    /// Arithmetic, logic, shifts, moves and other register-only operations
+    pub alu: u64,
+    /// Loads, stores and atomic memory operations
+    pub load_store: u64,
+    /// Conditional branches and jumps
+    pub branch: u64,
+    /// Multiplication, division and remainder
+    pub mul_div: u64,
+    /// The `ecall` instruction itself
+    pub ecall: u64,
+    /// Flat cost paid by every system call on top of `ecall`
+    pub syscall_base: u64,
+    /// Cost per byte of guest memory consumed by a system call
+    pub syscall_per_byte: u64,
+}
+
+impl Default for GasCosts {
+    fn default() -> Self {
+        Self {
+            alu: 1,
+            load_store: 3,
+            branch: 2,
+            mul_div: 5,
+            ecall: 10,
+            syscall_base: 100,
+            syscall_per_byte: 1,
+        }
+    }
+}
+
+/// Instruction classes that share a price in [`GasCosts`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InstructionClass {
+    Alu,
+    LoadStore,
+    Branch,
+    MulDiv,
+    Ecall,
+}
+
+impl InstructionClass {
+    /// Classifies a decoded instruction for gas accounting.
+    pub fn of(instr: &Instruction) -> Self {
+        match instr {
+            Instruction::Lw { .. }
+            | Instruction::Ld { .. }
+            | Instruction::Lb { .. }
+            | Instruction::Lbu { .. }
+            | Instruction::Lh { .. }
+            | Instruction::Lhu { .. }
+            | Instruction::Sh { .. }
+            | Instruction::Sw { .. }
+            | Instruction::Sb { .. }
+            | Instruction::AmoswapW { .. }
+            | Instruction::AmoaddW { .. }
+            | Instruction::AmoandW { .. }
+            | Instruction::AmoorW { .. }
+            | Instruction::AmoxorW { .. }
+            | Instruction::AmomaxW { .. }
+            | Instruction::AmominW { .. }
+            | Instruction::AmomaxuW { .. }
+            | Instruction::AmominuW { .. }
+            | Instruction::LrW { .. }
+            | Instruction::ScW { .. } => InstructionClass::LoadStore,
+
+            Instruction::Beq { .. }
+            | Instruction::Bne { .. }
+            | Instruction::Blt { .. }
+            | Instruction::Bge { .. }
+            | Instruction::Bltu { .. }
+            | Instruction::Bgeu { .. }
+            | Instruction::Beqz { .. }
+            | Instruction::Bnez { .. }
+            | Instruction::Jal { .. }
+            | Instruction::Jalr { .. }
+            | Instruction::Jr { .. }
+            | Instruction::Ret => InstructionClass::Branch,
+
+            Instruction::Mul { .. }
+            | Instruction::Mulh { .. }
+            | Instruction::Mulhu { .. }
+            | Instruction::Mulhsu { .. }
+            | Instruction::Div { .. }
+            | Instruction::Divu { .. }
+            | Instruction::Rem { .. }
+            | Instruction::Remu { .. } => InstructionClass::MulDiv,
+
+            Instruction::Ecall => InstructionClass::Ecall,
+
+            _ => InstructionClass::Alu,
+        }
+    }
+}
+
+impl GasCosts {
+    /// Returns the cost of executing a single instruction of the given class.
+    pub fn instruction_cost(&self, class: InstructionClass) -> u64 {
+        match class {
+            InstructionClass::Alu => self.alu,
+            InstructionClass::LoadStore => self.load_store,
+            InstructionClass::Branch => self.branch,
+            InstructionClass::MulDiv => self.mul_div,
+            InstructionClass::Ecall => self.ecall,
+        }
+    }
+
+    /// Returns the cost of a system call that consumes `payload_len` bytes.
+    pub fn syscall_cost(&self, payload_len: u64) -> u64 {
+        self.syscall_base
+            .saturating_add(self.syscall_per_byte.saturating_mul(payload_len))
+    }
+}
+
+/// Number of guest bytes a system call reads, used for per-byte pricing.
+///
+/// `args` are the syscall arguments (a1..a6); `regs` is consulted for the few
+/// syscalls that take their length outside of that range.
+pub fn syscall_payload_len(call_id: u32, args: &[u32; 6], regs: &[u32; 32]) -> u64 {
+    match call_id {
+        // domain_len + key_len
+        SYSCALL_STORAGE_GET | SYSCALL_STORAGE_DELETE => args[1] as u64 + args[3] as u64,
+        // domain_len + cursor_len; the batch written back is charged by the
+        // syscall itself through `SyscallGas`, once its size is known
+        SYSCALL_STORAGE_NEXT => args[1] as u64 + if args[4] != 0 { args[3] as u64 } else { 0 },
+        // domain_len + key_len + value_len
+        SYSCALL_STORAGE_SET => args[1] as u64 + args[3] as u64 + args[5] as u64,
+        // message pointer/length are passed in a0/a1
+        SYSCALL_PANIC => regs[Register::A1 as usize] as u64,
+        // fmt_len + args_len
+        SYSCALL_LOG => args[1] as u64 + args[3] as u64,
+        // input_len
+        SYSCALL_CALL_PROGRAM => args[2] as u64,
+        // event_len
+        SYSCALL_FIRE_EVENT => args[1] as u64,
+        _ => 0,
+    }
+}
+
+/// Raised when a charge exceeds the remaining gas budget.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OutOfGas {
+    /// Gas the failing operation asked for
+    pub requested: u64,
+    /// Gas that was left when the charge was attempted
+    pub remaining: u64,
+}
+
+/// Tracks gas consumption against a limit.
+///
+/// EDUCATIONAL: A single meter is shared (through `Rc<RefCell<..>>`) between
+/// a VM and every nested VM it spawns through program calls, so the whole
+/// call tree draws from one budget.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GasMeter {
+    limit: u64,
+    used: u64,
+}
+
+impl GasMeter {
+    /// Creates a meter with the given gas limit.
+    pub fn new(limit: u64) -> Self {
+        Self { limit, used: 0 }
+    }
+
+    /// Creates a meter that never runs out.
+    pub fn unlimited() -> Self {
+        Self::new(u64::MAX)
+    }
+
+    /// Charges `amount` gas. On failure the remaining budget is burned, so
+    /// any caller sharing this meter stops at its next charge as well.
+    pub fn charge(&mut self, amount: u64) -> Result<(), OutOfGas> {
+        let remaining = self.remaining();
+        if amount > remaining {
+            self.used = self.limit;
+            return Err(OutOfGas { requested: amount, remaining });
+        }
+        self.used += amount;
+        Ok(())
+    }
+
+    pub fn limit(&self) -> u64 {
+        self.limit
+    }
+
+    pub fn used(&self) -> u64 {
+        self.used
+    }
+
+    pub fn remaining(&self) -> u64 {
+        self.limit - self.used
+    }
+
+    pub fn is_exhausted(&self) -> bool {
+        self.used >= self.limit
+    }
+}
+
+impl Default for GasMeter {
+    fn default() -> Self {
+        Self::unlimited()
+    }
+}
+
+/// Lets a system call pay for work whose size it only learns while running.
+///
+/// EDUCATIONAL: Input is priced up front from the argument lengths, but a
+/// call like `SYSCALL_STORAGE_NEXT` only knows how many bytes it hands back
+/// once it has read them from storage. It charges `syscall_per_byte` for
+/// those through this before copying anything into guest memory.
+#[derive(Debug, Clone)]
+pub struct SyscallGas {
+    meter: Rc<RefCell<GasMeter>>,
+    per_byte: u64,
+}
+
+impl SyscallGas {
+    pub fn new(meter: Rc<RefCell<GasMeter>>, costs: &GasCosts) -> Self {
+        Self { meter, per_byte: costs.syscall_per_byte }
+    }
+
+    /// An unlimited budget, for driving syscall handlers outside a CPU.
+    pub fn unlimited() -> Self {
+        Self::new(Rc::new(RefCell::new(GasMeter::unlimited())), &GasCosts::default())
+    }
+
+    /// Charges the per-byte cost of `len` bytes written back to the guest.
+    pub fn charge_output(&self, len: usize) -> Result<(), OutOfGas> {
+        self.meter.borrow_mut().charge(self.per_byte.saturating_mul(len as u64))
+    }
+}
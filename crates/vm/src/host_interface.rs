@@ -1,11 +1,15 @@
 use std::fmt::Debug;
 
 pub trait HostInterface: Debug {
-    // calls another program, returns result ptr and page index
-    fn call_program(&mut self, from: [u8; 20], to: [u8; 20], input_data: Vec<u8>) -> (u32, usize);
+    // calls another program on behalf of the currently executing one, returns result ptr and page index.
+    // the caller identity is not a parameter: the host derives it from its own execution context.
+    // `value` native tokens are moved from the caller to `to` as part of the call.
+    fn call_program(&mut self, to: [u8; 20], input_data: Vec<u8>, value: u64) -> (u32, usize);
     fn read_memory_page(&mut self, page_index: usize, guest_ptr: u32, len: usize) -> Option<Vec<u8>>;
     fn fire_event(&mut self, event: Vec<u8>);
     fn transfer(&mut self, to: [u8; 20], value: u64) -> bool;
+    // value attached to the currently executing call
+    fn call_value(&mut self) -> u64;
     fn balance(&mut self, addr: [u8; 20]) -> u128;
 }
 
@@ -13,7 +17,7 @@ pub trait HostInterface: Debug {
 pub struct NoopHost;
 
 impl HostInterface for NoopHost {
-    fn call_program(&mut self, _from: [u8; 20], _to: [u8; 20], _input_data: Vec<u8>) -> (u32, usize) {
+    fn call_program(&mut self, _to: [u8; 20], _input_data: Vec<u8>, _value: u64) -> (u32, usize) {
         (0, 0)
     }
     fn read_memory_page(&mut self, _page_index: usize, _guest_ptr: u32, _len: usize) -> Option<Vec<u8>> {
@@ -25,6 +29,9 @@ impl HostInterface for NoopHost {
     fn transfer(&mut self, _to: [u8; 20], _value: u64) -> bool {
         false
     }
+    fn call_value(&mut self) -> u64 {
+        0
+    }
     fn balance(&mut self, _addr: [u8; 20]) -> u128 {
         0
     }
@@ -4,6 +4,8 @@ pub mod instruction;
 pub mod decoder;
 pub mod vm;
 pub mod cpu;
+pub mod gas;
+pub mod trap;
 pub mod registers;
 pub mod memory_page;
 pub mod sys_call;
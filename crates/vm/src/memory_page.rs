@@ -1,6 +1,7 @@
 use std::rc::Rc;
 use std::cell::{RefCell, Cell};
 use std::convert::TryInto;
+use crate::trap::{AccessKind, VmTrap};
 
 #[derive(Debug, Clone)]
 pub struct MemoryPage {
@@ -27,90 +28,86 @@ impl MemoryPage {
         self.mem.borrow()
     }
 
+    pub fn mem_mut(&self) -> std::cell::RefMut<'_, Vec<u8>> {
+        self.mem.borrow_mut()
+    }
+
     pub fn size(&self) -> usize {
         let mem = self.mem();
         mem.len()
     }
 
-    pub fn offset(&self, addr: usize) -> usize {
-        addr.checked_sub(self.base_address).expect("Address below base_address")
+    /// Translates a guest address into an offset into this page.
+    /// Returns an `OutOfBounds` trap for addresses outside the page.
+    pub fn offset(&self, addr: usize) -> Result<usize, VmTrap> {
+        self.checked_offset(addr, 0, AccessKind::Load)
     }
 
-    pub fn store_u16(&self, addr: usize, val: u16) {
-        let offset = self.offset(addr);
-        let mut mem = self.mem.borrow_mut();
-        if offset + 2 > mem.len() {
-            panic!("store u16 out of bounds: addr = 0x{:08x}", addr);
+    /// Translates a guest address into a checked offset for a `size`-byte access.
+    /// Returns an `OutOfBounds` trap instead of panicking on bad addresses.
+    pub fn checked_offset(&self, addr: usize, size: usize, kind: AccessKind) -> Result<usize, VmTrap> {
+        let out_of_bounds = VmTrap::OutOfBounds { addr, size, kind };
+        let offset = addr.checked_sub(self.base_address).ok_or(out_of_bounds.clone())?;
+        match offset.checked_add(size) {
+            Some(end) if end <= self.mem.borrow().len() => Ok(offset),
+            _ => Err(out_of_bounds),
         }
-        mem[offset..offset + 2].copy_from_slice(&val.to_le_bytes());
+    }
+
+    pub fn store_u16(&self, addr: usize, val: u16) -> Result<(), VmTrap> {
+        let offset = self.checked_offset(addr, 2, AccessKind::Store)?;
+        self.mem.borrow_mut()[offset..offset + 2].copy_from_slice(&val.to_le_bytes());
+        Ok(())
     }
     
-    pub fn store_u32(&self, addr: usize, val: u32) {
-        let offset = self.offset(addr);
-        let mut mem = self.mem.borrow_mut();
-        if offset + 4 > mem.len() {
-            panic!("store u32 out of bounds: addr = 0x{:08x}", addr);
-        }
-        mem[offset..offset + 4].copy_from_slice(&val.to_le_bytes());
+    pub fn store_u32(&self, addr: usize, val: u32) -> Result<(), VmTrap> {
+        let offset = self.checked_offset(addr, 4, AccessKind::Store)?;
+        self.mem.borrow_mut()[offset..offset + 4].copy_from_slice(&val.to_le_bytes());
+        Ok(())
     }
 
-    pub fn store_u8(&self, addr: usize, val: u8) {
-        let offset = self.offset(addr);
-        let mut mem = self.mem.borrow_mut();
-        if offset >= mem.len() {
-            panic!("store u8 out of bounds: addr = 0x{:08x}", addr);
-        }
-        mem[offset] = val;
+    pub fn store_u8(&self, addr: usize, val: u8) -> Result<(), VmTrap> {
+        let offset = self.checked_offset(addr, 1, AccessKind::Store)?;
+        self.mem.borrow_mut()[offset] = val;
+        Ok(())
     }
 
-    pub fn load_u32(&self, addr: usize) -> u32 {
-        let offset = self.offset(addr);
+    pub fn load_u32(&self, addr: usize) -> Result<u32, VmTrap> {
+        let offset = self.checked_offset(addr, 4, AccessKind::Load)?;
         let mem = self.mem.borrow();
-        if offset + 4 > mem.len() {
-            panic!("load u32 out of bounds: addr = 0x{:08x}", addr);
-        }
-        u32::from_le_bytes(mem[offset..offset + 4].try_into().unwrap())
+        Ok(u32::from_le_bytes(mem[offset..offset + 4].try_into().unwrap()))
     }
 
-    pub fn load_byte(&self, addr: usize) -> u8 {
-        let offset = self.offset(addr);
-        let mem = self.mem.borrow();
-        mem[offset]
+    pub fn load_byte(&self, addr: usize) -> Result<u8, VmTrap> {
+        let offset = self.checked_offset(addr, 1, AccessKind::Load)?;
+        Ok(self.mem.borrow()[offset])
     }
 
-    pub fn load_halfword(&self, addr: usize) -> u16 {
-        let offset = self.offset(addr);
+    pub fn load_halfword(&self, addr: usize) -> Result<u16, VmTrap> {
+        let offset = self.checked_offset(addr, 2, AccessKind::Load)?;
         let mem = self.mem.borrow();
-        u16::from_le_bytes(mem[offset..offset + 2].try_into().unwrap())
+        Ok(u16::from_le_bytes(mem[offset..offset + 2].try_into().unwrap()))
     }
 
-    pub fn load_word(&self, addr: usize) -> u32 {
-        let offset = self.offset(addr);
-        let mem = self.mem.borrow();
-        u32::from_le_bytes(mem[offset..offset + 4].try_into().unwrap())
+    pub fn load_word(&self, addr: usize) -> Result<u32, VmTrap> {
+        self.load_u32(addr)
     }
 
-    pub fn store_byte(&mut self, addr: usize, value: u8) {
-        let offset = self.offset(addr);
-        let mut mem = self.mem.borrow_mut();
-        mem[offset] = value;
+    pub fn store_byte(&mut self, addr: usize, value: u8) -> Result<(), VmTrap> {
+        self.store_u8(addr, value)
     }
 
-    pub fn store_halfword(&mut self, addr: usize, value: u16) {
-        let offset = self.offset(addr);
-        let mut mem = self.mem.borrow_mut();
-        mem[offset..offset + 2].copy_from_slice(&value.to_le_bytes());
+    pub fn store_halfword(&mut self, addr: usize, value: u16) -> Result<(), VmTrap> {
+        self.store_u16(addr, value)
     }
 
-    pub fn store_word(&mut self, addr: usize, value: u32) {
-        let offset = self.offset(addr);
-        let mut mem = self.mem.borrow_mut();
-        mem[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+    pub fn store_word(&mut self, addr: usize, value: u32) -> Result<(), VmTrap> {
+        self.store_u32(addr, value)
     }
 
     pub fn mem_slice(&self, start: usize, end: usize) -> Option<std::cell::Ref<[u8]>> {
-        let start_offset = self.offset(start);
-        let end_offset = self.offset(end);
+        let start_offset = start.checked_sub(self.base_address)?;
+        let end_offset = end.checked_sub(self.base_address)?;
         let mem_ref = self.mem.borrow();
         if end_offset > mem_ref.len() || start_offset > end_offset {
             return None;
@@ -118,31 +115,40 @@ impl MemoryPage {
         Some(std::cell::Ref::map(mem_ref, move |v| &v[start_offset..end_offset]))
     }
 
-    pub fn write_code(&mut self, start_addr: usize, code: &[u8]) {
-        let start_offset = self.offset(start_addr);
+    /// Copies `code` to `start_addr` and starts the heap right after it.
+    /// Returns an `OutOfBounds` trap if the code does not fit in the page.
+    pub fn write_code(&mut self, start_addr: usize, code: &[u8]) -> Result<(), VmTrap> {
+        let start_offset = self.checked_offset(start_addr, code.len(), AccessKind::Store)?;
         let mut mem = self.mem.borrow_mut();
         let end = start_offset + code.len();
         mem[start_offset..end].copy_from_slice(code);
 
         // set heap pointer
         self.next_heap = Cell::new(start_offset as u32 + code.len() as u32 + HEAP_PTR_OFFSET);
+        Ok(())
     }
 
-    pub fn alloc_on_heap(&self, data: &[u8]) -> u32 {
-        let mut addr = self.next_heap.get();
+    /// Bump-allocates `data` on the heap and returns its address.
+    /// Returns an `OutOfBounds` trap when the page has no room left, so a
+    /// guest asking for too much (e.g. a huge storage value) cannot crash the host.
+    pub fn alloc_on_heap(&self, data: &[u8]) -> Result<u32, VmTrap> {
+        let mut addr = self.next_heap.get() as usize;
 
         // Align to 4 bytes (or 8 if you're storing u64s)
         let align = 8;
         addr = (addr + (align - 1)) & !(align - 1);
-        
-        let end = addr + data.len() as u32;
-        assert!(end as usize <= self.size(), "Out of memory: trying to allocate {} bytes, but only {} bytes available", data.len(), self.size() - addr as usize);
 
-        self.mem.borrow_mut()[addr as usize..end as usize].copy_from_slice(data);
-        self.next_heap.set(end);
+        let out_of_memory = VmTrap::OutOfBounds { addr, size: data.len(), kind: AccessKind::Store };
+        let end = addr.checked_add(data.len()).ok_or(out_of_memory.clone())?;
+        if end > self.size() {
+            return Err(out_of_memory);
+        }
+
+        self.mem.borrow_mut()[addr..end].copy_from_slice(data);
+        self.next_heap.set(end as u32);
 
-        addr
-    }   
+        Ok(addr as u32)
+    }
 
     pub fn stack_top(&self) -> u32 {
         self.size() as u32
@@ -162,25 +168,24 @@ mod tests {
     #[test]
     fn test_offset_zero_base() {
         let mem = MemoryPage::new_with_base(1024, 0);
-        assert_eq!(mem.offset(0), 0);
-        assert_eq!(mem.offset(100), 100);
-        assert_eq!(mem.offset(1023), 1023);
+        assert_eq!(mem.offset(0), Ok(0));
+        assert_eq!(mem.offset(100), Ok(100));
+        assert_eq!(mem.offset(1023), Ok(1023));
     }
 
     #[test]
     fn test_offset_high_base() {
         let base = 0x80000000;
         let mem = MemoryPage::new_with_base(1024, base);
-        assert_eq!(mem.offset(base), 0);
-        assert_eq!(mem.offset(base + 100), 100);
-        assert_eq!(mem.offset(base + 1023), 1023);
+        assert_eq!(mem.offset(base), Ok(0));
+        assert_eq!(mem.offset(base + 100), Ok(100));
+        assert_eq!(mem.offset(base + 1023), Ok(1023));
     }
 
     #[test]
-    #[should_panic(expected = "Address below base_address")]
-    fn test_offset_below_base_panics() {
+    fn test_offset_below_base_traps() {
         let base = 0x80000000;
         let mem = MemoryPage::new_with_base(1024, base);
-        mem.offset(base - 1);
+        assert!(matches!(mem.offset(base - 1), Err(VmTrap::OutOfBounds { .. })));
     }
 }
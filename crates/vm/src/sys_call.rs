@@ -1,5 +1,5 @@
 use crate::memory_page::{MemoryPage, HEAP_PTR_OFFSET};
-use storage::Storage;
+use storage::{Storage, StorageKey};
 use crate::registers::Register;
 use std::rc::Rc;
 use core::cell::RefCell;
@@ -7,6 +7,8 @@ use crate::host_interface::HostInterface;
 use std::any::Any;
 use types::result::RESULT_SIZE;
 use core::fmt::Write;
+use crate::trap::{AccessKind, VmTrap};
+use crate::gas::SyscallGas;
 
 /// System call IDs for the VM.
 pub const SYSCALL_STORAGE_GET: u32 = 1;
@@ -19,6 +21,13 @@ pub const SYSCALL_ALLOC: u32 = 7;
 pub const SYSCALL_DEALLOC: u32 = 8;
 pub const SYSCALL_TRANSFER: u32 = 9;
 pub const SYSCALL_BALANCE: u32 = 10;
+pub const SYSCALL_CALL_VALUE: u32 = 11;
+pub const SYSCALL_STORAGE_DELETE: u32 = 12;
+pub const SYSCALL_STORAGE_NEXT: u32 = 13;
+
+/// Most entries a single `SYSCALL_STORAGE_NEXT` call returns, whatever the
+/// guest asks for.
+pub const MAX_STORAGE_NEXT_ENTRIES: u32 = 32;
 /// Represents different types of arguments that can be passed to system calls.
 /// 
 /// EDUCATIONAL: This enum demonstrates how to handle different data types
@@ -33,6 +42,14 @@ enum Arg {
 }
 
 pub trait SyscallHandler: std::fmt::Debug {
+    /// Handles a single `ecall`.
+    /// 
+    /// `gas` pays for output whose size is only known while the call runs;
+    /// the input has already been charged by the CPU.
+    /// 
+    /// RETURN VALUE: Ok((a0, continue)) on success, or Err(trap) to stop the
+    /// VM (unknown syscall, guest panic, invalid guest memory, out of gas).
+    #[allow(clippy::too_many_arguments)]
     fn handle_syscall(
         &mut self,
         call_id: u32,
@@ -41,7 +58,8 @@ pub trait SyscallHandler: std::fmt::Debug {
         storage: Rc<RefCell<Storage>>,
         host: &mut Box<dyn HostInterface>,
         regs: &mut [u32; 32],
-    ) -> (u32, bool);
+        gas: &SyscallGas,
+    ) -> Result<(u32, bool), VmTrap>;
     fn as_any(&self) -> &dyn Any;
 }
 
@@ -80,23 +98,25 @@ impl SyscallHandler for DefaultSyscallHandler {
         storage: Rc<RefCell<Storage>>,
         host: &mut Box<dyn HostInterface>,
         regs: &mut [u32; 32],
-    ) -> (u32, bool) {
+        gas: &SyscallGas,
+    ) -> Result<(u32, bool), VmTrap> {
         let result = match call_id {
-            SYSCALL_STORAGE_GET => self.sys_storage_get(args, memory, storage),
-            SYSCALL_STORAGE_SET => self.sys_storage_set(args, memory, storage),
-            SYSCALL_PANIC => self.sys_panic_with_message(regs, memory),
+            SYSCALL_STORAGE_GET => self.sys_storage_get(args, memory, storage)?,
+            SYSCALL_STORAGE_SET => self.sys_storage_set(args, memory, storage)?,
+            SYSCALL_STORAGE_DELETE => self.sys_storage_delete(args, memory, storage)?,
+            SYSCALL_STORAGE_NEXT => self.sys_storage_next(args, memory, storage, gas)?,
+            SYSCALL_PANIC => self.sys_panic_with_message(regs, memory)?,
             SYSCALL_LOG => self.sys_log(args, memory),
-            SYSCALL_CALL_PROGRAM => self.sys_call_program(args, memory, host),
-            SYSCALL_FIRE_EVENT => self.sys_fire_event(args, memory, host),
+            SYSCALL_CALL_PROGRAM => self.sys_call_program(args, memory, host)?,
+            SYSCALL_FIRE_EVENT => self.sys_fire_event(args, memory, host)?,
             SYSCALL_ALLOC => self.sys_alloc(args, memory),
             SYSCALL_DEALLOC => self.sys_dealloc(args, memory),
-            SYSCALL_TRANSFER => self.sys_transfer(args, memory, host),
-            SYSCALL_BALANCE => self.sys_balance(args, memory, host),
-            _ => {
-                panic!("Unknown syscall: {}", call_id);
-            }
+            SYSCALL_TRANSFER => self.sys_transfer(args, memory, host)?,
+            SYSCALL_BALANCE => self.sys_balance(args, memory, host)?,
+            SYSCALL_CALL_VALUE => self.sys_call_value(memory, host)?,
+            _ => return Err(VmTrap::UnknownSyscall(call_id)),
         };
-        (result, true)
+        Ok((result, true))
     }
     fn as_any(&self) -> &dyn Any {
         self
@@ -104,7 +124,7 @@ impl SyscallHandler for DefaultSyscallHandler {
 }
 
 impl DefaultSyscallHandler {
-	pub fn sys_fire_event(&mut self, args: [u32; 6], memory: Rc<RefCell<MemoryPage>>, host: &mut Box<dyn HostInterface>,) -> u32 {
+	pub fn sys_fire_event(&mut self, args: [u32; 6], memory: Rc<RefCell<MemoryPage>>, host: &mut Box<dyn HostInterface>,) -> Result<u32, VmTrap> {
         // EDUCATIONAL: Extract key pointer and length from arguments
         let ptr = args[0] as usize;
         let len = args[1] as usize;
@@ -115,14 +135,19 @@ impl DefaultSyscallHandler {
         // EDUCATIONAL: Create a limited scope to avoid borrow checker issues
         let event_bytes = match borrowed_memory.mem_slice(ptr, ptr + len) {
             Some(r) => r,
-            None => panic!("invalid memory access"),  // Invalid memory access
+            None => return Err(VmTrap::OutOfBounds { addr: ptr, size: len, kind: AccessKind::Load }),
         };
 
         host.fire_event(event_bytes.to_vec());
-        0
+        Ok(0)
     }
 
-    fn sys_storage_get(&mut self, args: [u32; 6], memory: Rc<RefCell<MemoryPage>>, storage: Rc<RefCell<Storage>>) -> u32 {
+    /// Reads a storage slot into the guest heap as `len u32 | value` and
+    /// returns its address, or 0 if the slot is empty.
+    /// 
+    /// TRAPS: A domain or key outside guest memory traps; returning 0 would
+    /// pass for an empty slot.
+    fn sys_storage_get(&mut self, args: [u32; 6], memory: Rc<RefCell<MemoryPage>>, storage: Rc<RefCell<Storage>>) -> Result<u32, VmTrap> {
         let domain_ptr = args[0] as usize;
         let domain_len = args[1] as usize;
         let key_ptr = args[2] as usize;
@@ -130,64 +155,33 @@ impl DefaultSyscallHandler {
         
         let borrowed_memory = memory.borrow();
         
-        // Parse domain
-        let domain_slice = {
-            let domain_slice_ref = match borrowed_memory.mem_slice(domain_ptr, domain_ptr + domain_len) {
-                Some(r) => r,
-                None => {
-                    println!("❌ Storage GET - Invalid domain memory access: ptr={}, len={}", domain_ptr, domain_len);
-                    return 0;
-                }
-            };
-            domain_slice_ref.as_ref().to_vec()
-        };
-        let domain = match core::str::from_utf8(&domain_slice) {
-            Ok(s) => s,
-            Err(_) => {
-                println!("❌ Storage GET - Invalid UTF-8 in domain: {:?}", domain_slice);
-                return 0;
-            }
-        };
-        
-        // Parse key
-        let key_slice = {
-            let key_slice_ref = match borrowed_memory.mem_slice(key_ptr, key_ptr + key_len) {
-                Some(r) => r,
-                None => {
-                    println!("❌ Storage GET - Invalid key memory access: ptr={}, len={}", key_ptr, key_len);
-                    return 0;
-                }
-            };
-            key_slice_ref.as_ref().to_vec()
-        };
-        // Convert binary key to hex string for storage
-        let key = key_slice.iter().map(|b| format!("{:02x}", b)).collect::<Vec<_>>().join("");
-        
-        // Format key for display based on domain
-        let display_key = if domain == "P" {
-            // For persistent domain, try to display key as ASCII
-            match core::str::from_utf8(&key_slice) {
-                Ok(s) => s.to_string(),
-                Err(_) => key.clone(), // fallback to hex if not valid UTF-8
-            }
-        } else {
-            // For other domains, show domain as ASCII and key as hex
-            format!("{}:{}", domain, key)
+        // EDUCATIONAL: Domain and key are used as raw bytes - no decoding or
+        // re-encoding on the way to storage
+        let slot = {
+            let domain = borrowed_memory.mem_slice(domain_ptr, domain_ptr + domain_len)
+                .ok_or(VmTrap::OutOfBounds { addr: domain_ptr, size: domain_len, kind: AccessKind::Load })?;
+            let key = borrowed_memory.mem_slice(key_ptr, key_ptr + key_len)
+                .ok_or(VmTrap::OutOfBounds { addr: key_ptr, size: key_len, kind: AccessKind::Load })?;
+            StorageKey::new(domain.as_ref(), key.as_ref())
         };
         
-        if let Some(value) = storage.borrow().get(domain, &key) {
+        if let Some(value) = storage.borrow().get(&slot) {
             let mut buf = (value.len() as u32).to_le_bytes().to_vec();
             buf.extend_from_slice(value.as_slice());
-            let addr = borrowed_memory.alloc_on_heap(&buf);
-            println!("✅ Found value for domain: '{}', Key: '{}'", domain, display_key);
-            return addr;
+            let addr = borrowed_memory.alloc_on_heap(&buf)?;
+            println!("✅ Found value for {}", slot);
+            Ok(addr)
         } else {
-            println!("❌ No value found for domain: '{}', key: '{}'", domain, display_key);
-            0
+            println!("❌ No value found for {}", slot);
+            Ok(0)
         }
     }
 
-    fn sys_storage_set(&mut self, args: [u32; 6], memory: Rc<RefCell<MemoryPage>>, storage: Rc<RefCell<Storage>>) -> u32 {
+    /// Writes a storage slot.
+    /// 
+    /// TRAPS: A domain, key or value outside guest memory traps rather than
+    /// silently skipping the write.
+    fn sys_storage_set(&mut self, args: [u32; 6], memory: Rc<RefCell<MemoryPage>>, storage: Rc<RefCell<Storage>>) -> Result<u32, VmTrap> {
         let domain_ptr = args[0] as usize;
         let domain_len = args[1] as usize;
         let key_ptr = args[2] as usize;
@@ -198,64 +192,105 @@ impl DefaultSyscallHandler {
         let borrowed_memory = memory.borrow();
         
         // Parse domain
-        let domain_slice_ref = match borrowed_memory.mem_slice(domain_ptr, domain_ptr + domain_len) {
-            Some(r) => r,
-            None => {
-                println!("❌ Storage SET - Invalid domain memory access: ptr={}, len={}", domain_ptr, domain_len);
-                return 0;
-            }
-        };
-        let domain_slice = domain_slice_ref.as_ref();
-        let domain = match core::str::from_utf8(domain_slice) {
-            Ok(s) => s,
-            Err(_) => {
-                println!("❌ Storage SET - Invalid UTF-8 in domain: {:?}", domain_slice);
-                return 0;
-            }
-        };
+        let domain = borrowed_memory.mem_slice(domain_ptr, domain_ptr + domain_len)
+            .ok_or(VmTrap::OutOfBounds { addr: domain_ptr, size: domain_len, kind: AccessKind::Load })?;
         
         // Parse key
-        let key_slice_ref = match borrowed_memory.mem_slice(key_ptr, key_ptr + key_len) {
-            Some(r) => r,
-            None => {
-                println!("❌ Storage SET - Invalid key memory access: ptr={}, len={}", key_ptr, key_len);
-                return 0;
-            }
-        };
-        let key_slice = key_slice_ref.as_ref();
-        // Convert binary key to hex string for storage
-        let key = key_slice.iter().map(|b| format!("{:02x}", b)).collect::<Vec<_>>().join("");
-        
-        // Format key for display based on domain
-        let display_key = if domain == "P" {
-            // For persistent domain, try to display key as ASCII
-            match core::str::from_utf8(key_slice) {
-                Ok(s) => s.to_string(),
-                Err(_) => key.clone(), // fallback to hex if not valid UTF-8
-            }
-        } else {
-            // For other domains, show domain as ASCII and key as hex
-            format!("{}:{}", domain, key)
-        };
+        let key = borrowed_memory.mem_slice(key_ptr, key_ptr + key_len)
+            .ok_or(VmTrap::OutOfBounds { addr: key_ptr, size: key_len, kind: AccessKind::Load })?;
+        let slot = StorageKey::new(domain.as_ref(), key.as_ref());
         
         // Parse value
-        let value_slice_ref = match borrowed_memory.mem_slice(val_ptr, val_ptr + val_len) {
-            Some(r) => r,
-            None => {
-                println!("❌ Storage SET - Invalid value memory access: ptr={}, len={}", val_ptr, val_len);
-                return 0;
-            }
-        };
+        let value_slice_ref = borrowed_memory.mem_slice(val_ptr, val_ptr + val_len)
+            .ok_or(VmTrap::OutOfBounds { addr: val_ptr, size: val_len, kind: AccessKind::Load })?;
         let value_slice = value_slice_ref.as_ref();
         
-        println!("💾 Storage SET - Domain: '{}', Key: '{}', Value: {:?} ({} bytes)", 
-                domain, display_key, value_slice, value_slice.len());
+        println!("💾 Storage SET - {}, Value: {:?} ({} bytes)", slot, value_slice, value_slice.len());
         
-        storage.borrow_mut().set(domain, &key, value_slice.to_vec());
-        0
+        storage.borrow_mut().set(slot, value_slice.to_vec());
+        Ok(0)
+    }
+
+    /// Removes a storage slot. Returns 1 if the slot existed, 0 otherwise.
+    /// 
+    /// TRAPS: A domain or key outside guest memory traps; returning 0 would
+    /// pass for "there was no such slot".
+    /// 
+    /// EDUCATIONAL: Deleting is not the same as writing an empty value - the
+    /// slot disappears from the account's storage (and from the state root),
+    /// so contracts can clean up after themselves instead of leaving zeroed
+    /// entries behind forever.
+    fn sys_storage_delete(&mut self, args: [u32; 6], memory: Rc<RefCell<MemoryPage>>, storage: Rc<RefCell<Storage>>) -> Result<u32, VmTrap> {
+        let domain_ptr = args[0] as usize;
+        let domain_len = args[1] as usize;
+        let key_ptr = args[2] as usize;
+        let key_len = args[3] as usize;
+        
+        let borrowed_memory = memory.borrow();
+        
+        let domain = borrowed_memory.mem_slice(domain_ptr, domain_ptr + domain_len)
+            .ok_or(VmTrap::OutOfBounds { addr: domain_ptr, size: domain_len, kind: AccessKind::Load })?;
+        let key = borrowed_memory.mem_slice(key_ptr, key_ptr + key_len)
+            .ok_or(VmTrap::OutOfBounds { addr: key_ptr, size: key_len, kind: AccessKind::Load })?;
+        let slot = StorageKey::new(domain.as_ref(), key.as_ref());
+        
+        Ok(storage.borrow().remove(&slot).is_some() as u32)
     }
 
-    fn sys_panic_with_message(&mut self, regs: &mut [u32; 32], memory: Rc<RefCell<MemoryPage>>) -> u32 {
+    /// Returns the next batch of entries under a domain, after a cursor key.
+    /// 
+    /// Arguments: domain ptr/len, cursor ptr/len, a flag telling whether the
+    /// cursor is set (0 starts at the first key) and the maximum number of
+    /// entries, capped at `MAX_STORAGE_NEXT_ENTRIES`.
+    /// 
+    /// The batch is written to the guest heap as
+    /// `count u32 | (key_len u32 | key | value_len u32 | value) * count`
+    /// and its address is returned. Every byte of it is charged at the
+    /// per-byte syscall rate before it is allocated.
+    /// 
+    /// TRAPS: Guests treat a 0 result as the end of the domain, so anything
+    /// that prevents a batch from being returned (invalid domain or cursor
+    /// memory, not enough gas, no room on the heap) traps instead.
+    /// 
+    /// EDUCATIONAL: Iteration is bounded on purpose. A contract cannot pull a
+    /// whole domain in one call - it walks it batch by batch, paying for each
+    /// call, and can stop (or continue in a later transaction) at any point.
+    fn sys_storage_next(&mut self, args: [u32; 6], memory: Rc<RefCell<MemoryPage>>, storage: Rc<RefCell<Storage>>, gas: &SyscallGas) -> Result<u32, VmTrap> {
+        let domain_ptr = args[0] as usize;
+        let domain_len = args[1] as usize;
+        let cursor_ptr = args[2] as usize;
+        let cursor_len = args[3] as usize;
+        let has_cursor = args[4] != 0;
+        let limit = args[5].min(MAX_STORAGE_NEXT_ENTRIES) as usize;
+        
+        let borrowed_memory = memory.borrow();
+        
+        let domain = borrowed_memory.mem_slice(domain_ptr, domain_ptr + domain_len)
+            .ok_or(VmTrap::OutOfBounds { addr: domain_ptr, size: domain_len, kind: AccessKind::Load })?
+            .to_vec();
+        let cursor = if has_cursor {
+            let cursor = borrowed_memory.mem_slice(cursor_ptr, cursor_ptr + cursor_len)
+                .ok_or(VmTrap::OutOfBounds { addr: cursor_ptr, size: cursor_len, kind: AccessKind::Load })?;
+            Some(cursor.to_vec())
+        } else {
+            None
+        };
+        
+        let entries = storage.borrow().entries_after(&domain, cursor.as_deref(), limit);
+        println!("🔎 Storage NEXT - {} entries from domain {}", entries.len(), String::from_utf8_lossy(&domain));
+        
+        let mut buf = (entries.len() as u32).to_le_bytes().to_vec();
+        for (key, value) in &entries {
+            buf.extend_from_slice(&(key.len() as u32).to_le_bytes());
+            buf.extend_from_slice(key);
+            buf.extend_from_slice(&(value.len() as u32).to_le_bytes());
+            buf.extend_from_slice(value);
+        }
+        gas.charge_output(buf.len()).map_err(VmTrap::OutOfGas)?;
+        borrowed_memory.alloc_on_heap(&buf)
+    }
+
+    fn sys_panic_with_message(&mut self, regs: &mut [u32; 32], memory: Rc<RefCell<MemoryPage>>) -> Result<u32, VmTrap> {
         let msg_ptr = regs[Register::A0 as usize] as usize;
         let msg_len = regs[Register::A1 as usize] as usize;
         let msg = memory
@@ -265,7 +300,7 @@ impl DefaultSyscallHandler {
                 String::from_utf8_lossy(&bytes).into_owned()
             })
             .unwrap_or_else(|| "<invalid memory access>".to_string());
-        panic!("🔥 Guest panic: {}", msg);
+        Err(VmTrap::GuestPanic(msg))
     }
 
     fn sys_log(&mut self, args: [u32; 6], memory: Rc<RefCell<MemoryPage>>) -> u32 {
@@ -450,39 +485,36 @@ impl DefaultSyscallHandler {
         0
     }
 
-    fn sys_call_program(&mut self, args: [u32; 6], memory: Rc<RefCell<MemoryPage>>, host: &mut Box<dyn HostInterface>) -> u32 {
+    fn sys_call_program(&mut self, args: [u32; 6], memory: Rc<RefCell<MemoryPage>>, host: &mut Box<dyn HostInterface>) -> Result<u32, VmTrap> {
+        // The callee sees the currently executing program as its caller; the
+        // guest only names the target and the input, it cannot pick a sender.
+        // A target or input outside guest memory traps: 0 means the call
+        // itself failed, not that the guest passed a bad pointer.
         let to_ptr = args[0] as usize;
-        let from_ptr = args[1] as usize;
-        let input_ptr = args[2] as usize;
-        let input_len = args[3] as usize;
+        let input_ptr = args[1] as usize;
+        let input_len = args[2] as usize;
+        let value = args[3] as u64 | ((args[4] as u64) << 32);
         let result_ptr: u32;
         let page_index: usize;
         {
             let borrowed_memory = memory.borrow();
-            let to_slice = match borrowed_memory.mem_slice(to_ptr, to_ptr + 20) {
-                Some(r) => r,
-                None => return 0,
-            };
-            let from_slice = match borrowed_memory.mem_slice(from_ptr, from_ptr + 20) {
-                Some(r) => r,
-                None => return 0,
-            };
-            let input_slice = match borrowed_memory.mem_slice(input_ptr, input_ptr + input_len) {
-                Some(r) => r,
-                None => return 0,
-            };
+            let to_slice = borrowed_memory.mem_slice(to_ptr, to_ptr + 20)
+                .ok_or(VmTrap::OutOfBounds { addr: to_ptr, size: 20, kind: AccessKind::Load })?;
+            let input_slice = borrowed_memory.mem_slice(input_ptr, input_ptr + input_len)
+                .ok_or(VmTrap::OutOfBounds { addr: input_ptr, size: input_len, kind: AccessKind::Load })?;
             let mut to_bytes = [0u8; 20];
-            let mut from_bytes = [0u8; 20];
             to_bytes.copy_from_slice(&to_slice);
-            from_bytes.copy_from_slice(&from_slice);
             let input_vec = input_slice.to_vec();
-            (result_ptr, page_index) = host.call_program(from_bytes, to_bytes, input_vec);
+            (result_ptr, page_index) = host.call_program(to_bytes, input_vec, value);
+        }
+        if result_ptr == 0 {
+            return Ok(0);
         }
         {
             let borrowed_memory = memory.borrow_mut();
             let result_bytes = match host.read_memory_page(page_index, result_ptr, RESULT_SIZE) {
                 Some(b) => b,
-                None => return 0,
+                None => return Ok(0),
             };
             borrowed_memory.alloc_on_heap(&result_bytes)
         }
@@ -512,23 +544,26 @@ impl DefaultSyscallHandler {
         
         // Allocate aligned memory on heap
         let data = vec![0u8; size];
-        let ptr = memory.borrow().alloc_on_heap(&data);
-        
-        if ptr == 0 {
-            println!("VM Alloc: Out of memory, failed to allocate {} bytes", size);
-            return 0;
-        }
+        let ptr = match memory.borrow().alloc_on_heap(&data) {
+            Ok(ptr) => ptr,
+            Err(_) => {
+                println!("VM Alloc: Out of memory, failed to allocate {} bytes", size);
+                return 0;
+            }
+        };
         
         // Check if allocated address meets alignment requirements
         if (ptr as usize) % align != 0 {
             // Re-allocate with enough space for alignment
             let total_size = size + align - 1;
             let padded_data = vec![0u8; total_size];
-            let padded_ptr = memory.borrow().alloc_on_heap(&padded_data);
-            if padded_ptr == 0 {
-                println!("VM Alloc: Out of memory, failed to allocate {} bytes for alignment", total_size);
-                return 0;
-            }
+            let padded_ptr = match memory.borrow().alloc_on_heap(&padded_data) {
+                Ok(ptr) => ptr,
+                Err(_) => {
+                    println!("VM Alloc: Out of memory, failed to allocate {} bytes for alignment", total_size);
+                    return 0;
+                }
+            };
             // Return properly aligned pointer within the allocated region
             let aligned_ptr = ((padded_ptr as usize + align - 1) & !(align - 1)) as u32;
             return aligned_ptr;
@@ -544,7 +579,7 @@ impl DefaultSyscallHandler {
         0
     }
 
-    fn sys_transfer(&mut self, args: [u32; 6], memory: Rc<RefCell<MemoryPage>>, host: &mut Box<dyn HostInterface>) -> u32 {
+    fn sys_transfer(&mut self, args: [u32; 6], memory: Rc<RefCell<MemoryPage>>, host: &mut Box<dyn HostInterface>) -> Result<u32, VmTrap> {
         // args: a2=to ptr, a3=value_lo, a4=value_hi
         let to_ptr = args[1] as usize;
         let value_lo = args[2] as u64;
@@ -552,20 +587,22 @@ impl DefaultSyscallHandler {
         let value = value_lo | (value_hi << 32);
 
         let borrowed = memory.borrow();
-        let to_slice = borrowed.mem_slice(to_ptr, to_ptr + 20).expect("invalid to ptr");
+        let to_slice = borrowed.mem_slice(to_ptr, to_ptr + 20)
+            .ok_or(VmTrap::OutOfBounds { addr: to_ptr, size: 20, kind: AccessKind::Load })?;
 
         let mut to = [0u8; 20];
         to.copy_from_slice(to_slice.as_ref());
 
-        if host.transfer(to, value) { 0 } else { 1 }
+        Ok(if host.transfer(to, value) { 0 } else { 1 })
     }
 
-    fn sys_balance(&mut self, args: [u32; 6], memory: Rc<RefCell<MemoryPage>>, host: &mut Box<dyn HostInterface>) -> u32 {
+    fn sys_balance(&mut self, args: [u32; 6], memory: Rc<RefCell<MemoryPage>>, host: &mut Box<dyn HostInterface>) -> Result<u32, VmTrap> {
         // args: a1 = address pointer (20 bytes)
         let addr_ptr = args[0] as usize;
         let addr = {
             let borrowed = memory.borrow();
-            let addr_slice = borrowed.mem_slice(addr_ptr, addr_ptr + 20).expect("invalid addr ptr");
+            let addr_slice = borrowed.mem_slice(addr_ptr, addr_ptr + 20)
+                .ok_or(VmTrap::OutOfBounds { addr: addr_ptr, size: 20, kind: AccessKind::Load })?;
             let mut addr = [0u8; 20];
             addr.copy_from_slice(addr_slice.as_ref());
             addr
@@ -574,4 +611,10 @@ impl DefaultSyscallHandler {
         let bal = host.balance(addr);
         memory.borrow().alloc_on_heap(&bal.to_le_bytes())
     }
+
+    fn sys_call_value(&mut self, memory: Rc<RefCell<MemoryPage>>, host: &mut Box<dyn HostInterface>) -> Result<u32, VmTrap> {
+        // returns a pointer to the call's value as 8 little-endian bytes
+        let value = host.call_value();
+        memory.borrow().alloc_on_heap(&value.to_le_bytes())
+    }
 }
@@ -0,0 +1,94 @@
+use crate::gas::OutOfGas;
+use core::fmt;
+
+/// Kind of memory access that caused a trap.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccessKind {
+    Load,
+    Store,
+    Fetch,
+}
+
+impl fmt::Display for AccessKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AccessKind::Load => write!(f, "load"),
+            AccessKind::Store => write!(f, "store"),
+            AccessKind::Fetch => write!(f, "fetch"),
+        }
+    }
+}
+
+/// Reasons the VM stops executing a guest program abnormally.
+///
+/// EDUCATIONAL PURPOSE: Real CPUs do not crash the machine when a program
+/// misbehaves; they raise a *trap* that transfers control to the operating
+/// system, which decides what to do. Our VM mirrors this: instead of calling
+/// `panic!` inside the interpreter, every failure is returned as a `VmTrap`
+/// value that flows up through `CPU::step` and `VM::run` to the embedder.
+///
+/// WHY NOT PANIC: Unwinding is not available when the host is built with
+/// `panic = "abort"`, so a panicking VM would take the entire node down with
+/// it. Returning a value keeps failures reportable in every build profile.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VmTrap {
+    /// The bytes at `pc` do not decode to a supported instruction
+    IllegalInstruction { pc: u32, bytes: Vec<u8> },
+    /// An access that requires natural alignment was not aligned
+    MisalignedAccess { addr: usize, size: usize, kind: AccessKind },
+    /// A load, store or fetch touched memory outside the page
+    OutOfBounds { addr: usize, size: usize, kind: AccessKind },
+    /// The guest requested a syscall id the handler does not know
+    UnknownSyscall(u32),
+    /// The guest aborted through the panic syscall
+    GuestPanic(String),
+    /// The gas budget was exhausted
+    OutOfGas(OutOfGas),
+}
+
+impl VmTrap {
+    /// Error code reported in a `Result` when execution ends with this trap.
+    ///
+    /// The codes live in a range guest programs are not expected to use, so
+    /// callers can tell a trap apart from a regular application error.
+    pub fn error_code(&self) -> u32 {
+        match self {
+            VmTrap::OutOfGas(_) => 0xFFFF_0001,
+            VmTrap::IllegalInstruction { .. } => 0xFFFF_0002,
+            VmTrap::MisalignedAccess { .. } => 0xFFFF_0003,
+            VmTrap::OutOfBounds { .. } => 0xFFFF_0004,
+            VmTrap::UnknownSyscall(_) => 0xFFFF_0005,
+            VmTrap::GuestPanic(_) => 0xFFFF_0006,
+        }
+    }
+}
+
+impl fmt::Display for VmTrap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VmTrap::IllegalInstruction { pc, bytes } => {
+                let hex = bytes.iter().map(|b| format!("{:02x}", b)).collect::<Vec<_>>().join(" ");
+                write!(f, "illegal instruction at PC = 0x{:08x} (bytes: [{}])", pc, hex)
+            }
+            VmTrap::MisalignedAccess { addr, size, kind } => {
+                write!(f, "misaligned {}-byte {} at 0x{:08x}", size, kind, addr)
+            }
+            VmTrap::OutOfBounds { addr, size, kind } => {
+                write!(f, "{}-byte {} out of bounds at 0x{:08x}", size, kind, addr)
+            }
+            VmTrap::UnknownSyscall(id) => write!(f, "unknown syscall: {}", id),
+            VmTrap::GuestPanic(msg) => write!(f, "guest panic: {}", msg),
+            VmTrap::OutOfGas(err) => {
+                write!(f, "out of gas: requested {}, remaining {}", err.requested, err.remaining)
+            }
+        }
+    }
+}
+
+impl std::error::Error for VmTrap {}
+
+impl From<OutOfGas> for VmTrap {
+    fn from(err: OutOfGas) -> Self {
+        VmTrap::OutOfGas(err)
+    }
+}
@@ -6,6 +6,8 @@ use crate::memory_page::{MemoryPage};
 use storage::{Storage};
 use crate::host_interface::HostInterface;
 use crate::sys_call::{SyscallHandler, DefaultSyscallHandler};
+use crate::gas::GasMeter;
+use crate::trap::VmTrap;
 
 /// Represents a complete RISC-V virtual machine.
 /// 
@@ -82,12 +84,15 @@ impl VM {
     /// 
     /// MEMORY LAYOUT: Programs are typically loaded at specific addresses
     /// to ensure proper alignment and to avoid conflicts with system memory.
-    pub fn set_code(&mut self, alloc_add: u32, start_addr: u32, code: &[u8]) {
+    /// 
+    /// Returns an `OutOfBounds` trap if the code does not fit in the page.
+    pub fn set_code(&mut self, alloc_add: u32, start_addr: u32, code: &[u8]) -> Result<(), VmTrap> {
         // EDUCATIONAL: Write the program code to memory starting at address 0
-        self.memory.borrow_mut().write_code(alloc_add as usize, code);
+        self.memory.borrow_mut().write_code(alloc_add as usize, code)?;
         
         // EDUCATIONAL: Set the program counter to the starting address
         self.cpu.pc = start_addr;
+        Ok(())
     }
 
     /// Allocates memory on the heap and writes data to it.
@@ -99,8 +104,9 @@ impl VM {
     /// HEAP MANAGEMENT: The VM maintains a heap pointer that moves forward
     /// as memory is allocated. This is a simple but effective allocation strategy.
     /// 
-    /// RETURN VALUE: Returns the address where the data was written
-    pub fn alloc_and_write(&mut self, data: &[u8]) -> u32 {
+    /// RETURN VALUE: Returns the address where the data was written, or an
+    /// `OutOfBounds` trap when the heap is full
+    pub fn alloc_and_write(&mut self, data: &[u8]) -> Result<u32, VmTrap> {
         self.memory.borrow_mut().alloc_on_heap(data)
     }
 
@@ -116,9 +122,9 @@ impl VM {
     /// 
     /// DEBUG OUTPUT: The function prints information about what it's doing,
     /// which is helpful for understanding VM behavior during development.
-    pub fn set_reg_to_data(&mut self, reg: Register, data: &[u8]) -> u32 {
+    pub fn set_reg_to_data(&mut self, reg: Register, data: &[u8]) -> Result<u32, VmTrap> {
         // EDUCATIONAL: Allocate memory and write the data
-        let addr = self.alloc_and_write(data);
+        let addr = self.alloc_and_write(data)?;
         
         // EDUCATIONAL: Set the register to point to the data
         self.cpu.regs[reg as usize] = addr;
@@ -136,7 +142,7 @@ impl VM {
             data
         );
 
-        addr
+        Ok(addr)
     }
 
     /// Sets a register to a 32-bit value.
@@ -150,6 +156,28 @@ impl VM {
         self.cpu.regs[reg as usize] = data;
     }
 
+    /// Gives this VM a fresh gas budget of `limit` units.
+    /// 
+    /// EDUCATIONAL PURPOSE: Without a limit an infinite loop in the guest
+    /// would hang the host. Once the budget is used up, `run` returns
+    /// `VmTrap::OutOfGas`.
+    pub fn set_gas_limit(&mut self, limit: u64) {
+        self.cpu.gas = Rc::new(RefCell::new(GasMeter::new(limit)));
+    }
+
+    /// Makes this VM draw from an existing gas meter.
+    /// 
+    /// USAGE: Nested program calls share their caller's meter so the whole
+    /// call tree is charged against a single budget.
+    pub fn set_gas_meter(&mut self, meter: Rc<RefCell<GasMeter>>) {
+        self.cpu.gas = meter;
+    }
+
+    /// Gas consumed so far from this VM's meter.
+    pub fn gas_used(&self) -> u64 {
+        self.cpu.gas.borrow().used()
+    }
+
     /// Dumps the entire memory contents for debugging.
     /// 
     /// EDUCATIONAL PURPOSE: This demonstrates memory inspection tools that
@@ -252,14 +280,21 @@ impl VM {
     /// program halts or encounters an error.
     /// 
     /// EXECUTION LOOP: This implements the classic fetch-decode-execute cycle
-    /// that all CPUs follow. The loop continues until the CPU returns false,
-    /// indicating that execution should stop.
+    /// that all CPUs follow. The loop continues until the CPU reports that
+    /// execution should stop, either normally or with a trap.
     /// 
     /// ASSUMPTIONS: This function assumes the VM is already properly configured
     /// with code loaded and registers set up. For a complete VM, you'd typically
     /// call this after setting up the initial state.
-    pub fn raw_run(&mut self) {
+    /// 
+    /// GAS: Every step is charged against the CPU's gas meter. Running out of
+    /// gas is reported as `VmTrap::OutOfGas`.
+    /// 
+    /// RETURN VALUE: Ok(()) when the program halts normally, or the `VmTrap`
+    /// that stopped it (illegal instruction, memory fault, guest panic, ...)
+    pub fn run(&mut self) -> Result<(), VmTrap> {
         // EDUCATIONAL: Main execution loop - fetch, decode, execute
-        while self.cpu.step(Rc::clone(&self.memory), Rc::clone(&self.storage), &mut self.host) {}
+        while self.cpu.step(Rc::clone(&self.memory), Rc::clone(&self.storage), &mut self.host)? {}
+        Ok(())
     }
-} 
\ No newline at end of file
+}
\ No newline at end of file
@@ -1,3 +1,4 @@
+use vm::gas::SyscallGas;
 use vm::sys_call::{SyscallHandler, DefaultSyscallHandler, SYSCALL_ALLOC, SYSCALL_DEALLOC};
 use vm::{memory_page, host_interface};
 use storage::Storage;
@@ -25,7 +26,8 @@ fn test_allocator_syscalls() {
         storage.clone(),
         &mut host,
         &mut regs,
-    );
+        &SyscallGas::unlimited(),
+    ).unwrap();
 
     println!("✅ SYSCALL_ALLOC returned pointer: 0x{:08x}", result);
     assert_ne!(result, 0); // Should return valid pointer
@@ -43,7 +45,8 @@ fn test_allocator_syscalls() {
         storage.clone(),
         &mut host,
         &mut regs,
-    );
+        &SyscallGas::unlimited(),
+    ).unwrap();
 
     println!("✅ SYSCALL_DEALLOC returned: {}", dealloc_result);
     assert_eq!(dealloc_result, 0); // Should return 0 (success)
@@ -71,7 +74,8 @@ fn test_multiple_allocations() {
             storage.clone(),
             &mut host,
             &mut regs,
-        );
+            &SyscallGas::unlimited(),
+        ).unwrap();
         
         println!("✅ Allocation {}: size={}, ptr=0x{:08x}", i, size, ptr);
         assert_ne!(ptr, 0);
@@ -111,7 +115,8 @@ fn test_alignment_requirements() {
             storage.clone(),
             &mut host,
             &mut regs,
-        );
+            &SyscallGas::unlimited(),
+        ).unwrap();
         
         println!("✅ Alignment test: align={}, ptr=0x{:08x}", align, ptr);
         assert_ne!(ptr, 0);
@@ -140,7 +145,8 @@ fn test_invalid_alignment() {
             storage.clone(),
             &mut host,
             &mut regs,
-        );
+            &SyscallGas::unlimited(),
+        ).unwrap();
         
         println!("✅ Invalid alignment test: align={}, ptr=0x{:08x}", align, ptr);
         assert_eq!(ptr, 0, "Should return null for invalid alignment {}", align);
@@ -0,0 +1,62 @@
+use vm::gas::{GasCosts, GasMeter};
+use vm::trap::VmTrap;
+use vm::vm::VM;
+use vm::{host_interface, memory_page};
+use storage::Storage;
+use std::rc::Rc;
+use std::cell::RefCell;
+
+// addi x5, x5, 1 ; jal x0, -4  (loops forever)
+const INFINITE_LOOP: [u8; 8] = [0x93, 0x82, 0x12, 0x00, 0x6f, 0xf0, 0xdf, 0xff];
+// addi x5, x0, 7 ; ebreak
+const ADDI_EBREAK: [u8; 8] = [0x93, 0x02, 0x70, 0x00, 0x73, 0x00, 0x10, 0x00];
+
+fn new_vm(code: &[u8]) -> VM {
+    let memory = Rc::new(RefCell::new(memory_page::MemoryPage::new(8192)));
+    let storage = Rc::new(RefCell::new(Storage::new()));
+    let host: Box<dyn host_interface::HostInterface> = Box::new(host_interface::NoopHost);
+    let mut vm = VM::new(memory, storage, host);
+    vm.set_code(0, 0, code).unwrap();
+    vm
+}
+
+#[test]
+fn test_infinite_loop_runs_out_of_gas() {
+    let mut vm = new_vm(&INFINITE_LOOP);
+    vm.set_gas_limit(1_000);
+
+    match vm.run() {
+        Err(VmTrap::OutOfGas(err)) => assert!(err.requested > err.remaining),
+        other => panic!("expected OutOfGas, got {:?}", other),
+    }
+    assert_eq!(vm.gas_used(), 1_000);
+}
+
+#[test]
+fn test_gas_charged_per_instruction_class() {
+    let mut vm = new_vm(&ADDI_EBREAK);
+    vm.set_gas_limit(1_000);
+
+    assert_eq!(vm.run(), Ok(()));
+    assert_eq!(vm.cpu.regs[5], 7);
+
+    // addi (ALU) + ebreak (ALU)
+    let costs = GasCosts::default();
+    assert_eq!(vm.gas_used(), costs.alu * 2);
+}
+
+#[test]
+fn test_shared_meter_is_exhausted_across_vms() {
+    let meter = Rc::new(RefCell::new(GasMeter::new(500)));
+
+    let mut inner = new_vm(&INFINITE_LOOP);
+    inner.set_gas_meter(meter.clone());
+    assert!(matches!(inner.run(), Err(VmTrap::OutOfGas(_))));
+
+    // A second VM on the same budget cannot execute anything.
+    let mut outer = new_vm(&ADDI_EBREAK);
+    outer.set_gas_meter(meter.clone());
+    assert!(matches!(outer.run(), Err(VmTrap::OutOfGas(_))));
+    assert_eq!(outer.cpu.regs[5], 0);
+    assert!(meter.borrow().is_exhausted());
+}
@@ -1,59 +1,77 @@
 use vm::memory_page::MemoryPage;
 use vm::vm::VM;
 use vm::sys_call::DefaultSyscallHandler;
+use vm::trap::VmTrap;
 
 #[test]
 fn test_offset_zero_base() {
     let mem = MemoryPage::new_with_base(1024, 0);
-    assert_eq!(mem.offset(0), 0);
-    assert_eq!(mem.offset(100), 100);
-    assert_eq!(mem.offset(1023), 1023);
+    assert_eq!(mem.offset(0), Ok(0));
+    assert_eq!(mem.offset(100), Ok(100));
+    assert_eq!(mem.offset(1023), Ok(1023));
 }
 
 #[test]
 fn test_offset_high_base() {
     let base = 0x80000000;
     let mem = MemoryPage::new_with_base(1024, base);
-    assert_eq!(mem.offset(base), 0);
-    assert_eq!(mem.offset(base + 100), 100);
-    assert_eq!(mem.offset(base + 1023), 1023);
+    assert_eq!(mem.offset(base), Ok(0));
+    assert_eq!(mem.offset(base + 100), Ok(100));
+    assert_eq!(mem.offset(base + 1023), Ok(1023));
 }
 
 #[test]
-#[should_panic(expected = "Address below base_address")]
-fn test_offset_below_base_panics() {
+fn test_offset_below_base_traps() {
     let base = 0x80000000;
     let mem = MemoryPage::new_with_base(1024, base);
-    mem.offset(base - 1);
+    assert!(matches!(mem.offset(base - 1), Err(VmTrap::OutOfBounds { .. })));
 }
 
 #[test]
 fn test_store_and_load_zero_base() {
     let mem = MemoryPage::new_with_base(1024, 0);
-    mem.store_u8(10, 0xAB);
-    assert_eq!(mem.load_byte(10), 0xAB);
-    mem.store_u16(20, 0xCDEF);
-    assert_eq!(mem.load_halfword(20), 0xCDEF);
-    mem.store_u32(30, 0x12345678);
-    assert_eq!(mem.load_u32(30), 0x12345678);
+    mem.store_u8(10, 0xAB).unwrap();
+    assert_eq!(mem.load_byte(10).unwrap(), 0xAB);
+    mem.store_u16(20, 0xCDEF).unwrap();
+    assert_eq!(mem.load_halfword(20).unwrap(), 0xCDEF);
+    mem.store_u32(30, 0x12345678).unwrap();
+    assert_eq!(mem.load_u32(30).unwrap(), 0x12345678);
 }
 
 #[test]
 fn test_store_and_load_high_base() {
     let base = 0x80000000;
     let mem = MemoryPage::new_with_base(1024, base);
-    mem.store_u8(base + 10, 0xAB);
-    assert_eq!(mem.load_byte(base + 10), 0xAB);
-    mem.store_u16(base + 20, 0xCDEF);
-    assert_eq!(mem.load_halfword(base + 20), 0xCDEF);
-    mem.store_u32(base + 30, 0x12345678);
-    assert_eq!(mem.load_u32(base + 30), 0x12345678);
+    mem.store_u8(base + 10, 0xAB).unwrap();
+    assert_eq!(mem.load_byte(base + 10).unwrap(), 0xAB);
+    mem.store_u16(base + 20, 0xCDEF).unwrap();
+    assert_eq!(mem.load_halfword(base + 20).unwrap(), 0xCDEF);
+    mem.store_u32(base + 30, 0x12345678).unwrap();
+    assert_eq!(mem.load_u32(base + 30).unwrap(), 0x12345678);
 }
 
 #[test]
 fn test_store_and_load_at_offset_zero() {
     let base = 0x80000000;
     let mem = MemoryPage::new_with_base(1024, base);
-    mem.store_u8(base, 0xAA);
-    assert_eq!(mem.load_byte(base), 0xAA);
-} 
\ No newline at end of file
+    mem.store_u8(base, 0xAA).unwrap();
+    assert_eq!(mem.load_byte(base).unwrap(), 0xAA);
+}
+
+#[test]
+fn test_out_of_bounds_access_traps() {
+    let base = 0x80000000;
+    let mem = MemoryPage::new_with_base(1024, base);
+    assert!(matches!(mem.load_u32(base + 1022), Err(VmTrap::OutOfBounds { .. })));
+    assert!(matches!(mem.store_u8(base - 1, 0xAA), Err(VmTrap::OutOfBounds { .. })));
+    assert!(matches!(mem.load_byte(base + 1024), Err(VmTrap::OutOfBounds { .. })));
+}
+
+#[test]
+fn test_alloc_on_heap_traps_when_full() {
+    let mem = MemoryPage::new(64);
+    assert_eq!(mem.alloc_on_heap(&[1; 16]), Ok(0));
+    assert!(matches!(mem.alloc_on_heap(&[2; 64]), Err(VmTrap::OutOfBounds { .. })));
+    // A failed allocation does not move the heap pointer
+    assert_eq!(mem.alloc_on_heap(&[3; 8]), Ok(16));
+}
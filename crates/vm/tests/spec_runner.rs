@@ -84,21 +84,21 @@ fn run_single_test(elf_path: &str) -> Result<(), Box<dyn std::error::Error>> {
         syscall_handler,
     );
     vm.cpu.verbose = false; // Set to false to reduce output for multiple tests
-    vm.set_code(code_start as u32, code_start as u32, &code);
+    vm.set_code(code_start as u32, code_start as u32, &code).unwrap();
 
     if !rodata.is_empty() {
         println!("Writing rodata to memory: addr=0x{:x}, size=0x{:x}", rodata_start, rodata.len());
-        memory.borrow_mut().write_code(rodata_start as usize, &rodata);
+        memory.borrow_mut().write_code(rodata_start as usize, &rodata).unwrap();
     }
 
     if !data.is_empty() {
         println!("Writing data to memory: addr=0x{:x}, size=0x{:x}", data_start, data.len());
-        memory.borrow_mut().write_code(data_start as usize, &data);
+        memory.borrow_mut().write_code(data_start as usize, &data).unwrap();
     }
 
     // Run the VM
     println!("Running test...");
-    vm.raw_run();
+    vm.run()?;
     println!("Test completed.");
     
     Ok(())
@@ -0,0 +1,122 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use storage::{Storage, StorageKey};
+use vm::gas::{GasCosts, GasMeter, SyscallGas};
+use vm::host_interface::{HostInterface, NoopHost};
+use vm::memory_page::{MemoryPage, HEAP_PTR_OFFSET};
+use vm::sys_call::{DefaultSyscallHandler, SyscallHandler, MAX_STORAGE_NEXT_ENTRIES, SYSCALL_STORAGE_NEXT};
+use vm::trap::{AccessKind, VmTrap};
+
+const DOMAIN: u32 = 0x10;
+const CURSOR: u32 = 0x20;
+
+/// Calls SYSCALL_STORAGE_NEXT on domain "D", returning the batch address and
+/// the memory it was written to.
+fn call_next(storage: &Rc<RefCell<Storage>>, cursor: Option<&[u8]>, limit: u32, gas: &SyscallGas) -> (Result<u32, VmTrap>, Rc<RefCell<MemoryPage>>) {
+    let memory = Rc::new(RefCell::new(MemoryPage::new(8192)));
+    memory.borrow().next_heap.set(HEAP_PTR_OFFSET);
+    memory.borrow().mem_mut()[DOMAIN as usize] = b'D';
+    let cursor_len = cursor.map_or(0, |c| c.len());
+    if let Some(cursor) = cursor {
+        memory.borrow().mem_mut()[CURSOR as usize..CURSOR as usize + cursor_len].copy_from_slice(cursor);
+    }
+
+    let mut host: Box<dyn HostInterface> = Box::new(NoopHost);
+    let args = [DOMAIN, 1, CURSOR, cursor_len as u32, cursor.is_some() as u32, limit];
+    let outcome = DefaultSyscallHandler::new()
+        .handle_syscall(SYSCALL_STORAGE_NEXT, args, memory.clone(), storage.clone(), &mut host, &mut [0; 32], gas);
+    (outcome.map(|(ptr, _)| ptr), memory)
+}
+
+/// Calls SYSCALL_STORAGE_NEXT on domain "D" and decodes the returned batch.
+fn next(storage: &Rc<RefCell<Storage>>, cursor: Option<&[u8]>, limit: u32) -> Vec<(Vec<u8>, Vec<u8>)> {
+    let (ptr, memory) = call_next(storage, cursor, limit, &SyscallGas::unlimited());
+    let ptr = ptr.unwrap();
+    assert_ne!(ptr, 0);
+
+    let page = memory.borrow();
+    let mem = page.mem();
+    let mut at = ptr as usize;
+    let mut take = |len: usize| {
+        let bytes = mem[at..at + len].to_vec();
+        at += len;
+        bytes
+    };
+    let count = u32::from_le_bytes(take(4).try_into().unwrap());
+    (0..count)
+        .map(|_| {
+            let key_len = u32::from_le_bytes(take(4).try_into().unwrap()) as usize;
+            let key = take(key_len);
+            let value_len = u32::from_le_bytes(take(4).try_into().unwrap()) as usize;
+            (key, take(value_len))
+        })
+        .collect()
+}
+
+#[test]
+fn test_storage_next_pages_through_domain() {
+    let storage = Rc::new(RefCell::new(Storage::new()));
+    for key in [&b"b"[..], b"a", b"c", b""] {
+        storage.borrow().set(StorageKey::new(b"D", key), key.to_vec());
+    }
+    storage.borrow().set(StorageKey::new(b"C", b"z"), vec![]);
+    storage.borrow().set(StorageKey::new(b"DD", b"a"), vec![]);
+
+    let first = next(&storage, None, 2);
+    assert_eq!(first, vec![(vec![], vec![]), (b"a".to_vec(), b"a".to_vec())]);
+
+    let second = next(&storage, Some(b"a"), 2);
+    assert_eq!(second, vec![(b"b".to_vec(), b"b".to_vec()), (b"c".to_vec(), b"c".to_vec())]);
+
+    // The cursor need not exist, and other domains never leak in
+    assert_eq!(next(&storage, Some(b"bb"), 10), vec![(b"c".to_vec(), b"c".to_vec())]);
+    assert!(next(&storage, Some(b"c"), 10).is_empty());
+}
+
+#[test]
+fn test_storage_next_is_bounded() {
+    let storage = Rc::new(RefCell::new(Storage::new()));
+    for i in 0..100u8 {
+        storage.borrow().set(StorageKey::new(b"D", &[i]), vec![i]);
+    }
+    assert_eq!(next(&storage, None, u32::MAX).len(), MAX_STORAGE_NEXT_ENTRIES as usize);
+    assert!(next(&storage, None, 0).is_empty());
+}
+
+#[test]
+fn test_storage_next_charges_for_the_batch() {
+    let storage = Rc::new(RefCell::new(Storage::new()));
+    storage.borrow().set(StorageKey::new(b"D", b"k"), vec![0; 100]);
+    // count + key_len + key + value_len + value
+    let batch_len = 4 + 4 + 1 + 4 + 100;
+
+    let meter = Rc::new(RefCell::new(GasMeter::new(1_000)));
+    let gas = SyscallGas::new(meter.clone(), &GasCosts { syscall_per_byte: 2, ..GasCosts::default() });
+    assert!(call_next(&storage, None, 1, &gas).0.is_ok());
+    assert_eq!(meter.borrow().used(), 2 * batch_len);
+
+    // A batch the budget cannot cover traps before touching the heap
+    let meter = Rc::new(RefCell::new(GasMeter::new(batch_len - 1)));
+    let gas = SyscallGas::new(meter, &GasCosts::default());
+    let (outcome, memory) = call_next(&storage, None, 1, &gas);
+    assert!(matches!(outcome, Err(VmTrap::OutOfGas(_))));
+    assert_eq!(memory.borrow().next_heap.get(), HEAP_PTR_OFFSET);
+}
+
+#[test]
+fn test_storage_next_traps_instead_of_returning_zero() {
+    // 0 would read as "domain exhausted" to the guest
+    let storage = Rc::new(RefCell::new(Storage::new()));
+    storage.borrow().set(StorageKey::new(b"D", b"k"), vec![0; 10_000]);
+    let (outcome, _) = call_next(&storage, None, 1, &SyscallGas::unlimited());
+    assert!(matches!(outcome, Err(VmTrap::OutOfBounds { kind: AccessKind::Store, .. })));
+
+    // So does a domain outside guest memory
+    let mut host: Box<dyn HostInterface> = Box::new(NoopHost);
+    let memory = Rc::new(RefCell::new(MemoryPage::new(8192)));
+    let args = [0x10_0000, 1, 0, 0, 0, 1];
+    let outcome = DefaultSyscallHandler::new()
+        .handle_syscall(SYSCALL_STORAGE_NEXT, args, memory, storage, &mut host, &mut [0; 32], &SyscallGas::unlimited());
+    assert!(matches!(outcome, Err(VmTrap::OutOfBounds { kind: AccessKind::Load, .. })));
+}
@@ -3,8 +3,10 @@ use core::cell::RefCell;
 use vm::memory_page::MemoryPage;
 use storage::Storage;
 use vm::host_interface::HostInterface;
+use vm::gas::SyscallGas;
 use vm::sys_call::SyscallHandler;
 use vm::registers::Register;
+use vm::trap::VmTrap;
 use std::any::Any;
 
 /// Map RISC-V test exit codes to test case numbers
@@ -54,13 +56,14 @@ impl SyscallHandler for TestSyscallHandler {
         _storage: Rc<RefCell<Storage>>,
         _host: &mut Box<dyn HostInterface>,
         regs: &mut [u32; 32],
-    ) -> (u32, bool) {
+        _gas: &SyscallGas,
+    ) -> Result<(u32, bool), VmTrap> {
         let mut result = 0;
         match call_id {
             SYSCALL_TEST_DONE => {
                 // Read .tohost value
                 let mem_ref = self.memory.as_ref().unwrap_or(&memory);
-                let offset = mem_ref.borrow().offset(self.tohost_addr as usize);
+                let offset = mem_ref.borrow().offset(self.tohost_addr as usize)?;
                 let mem_guard = mem_ref.borrow();
                 let mem = mem_guard.mem();
                 if offset + 8 <= mem.len() {
@@ -71,7 +74,7 @@ impl SyscallHandler for TestSyscallHandler {
                     panic!("[TestSyscallHandler] .tohost address out of bounds");
                 }
                 if result == 0 {
-                    return (result, true);
+                    return Ok((result, true));
                 } 
                 panic!("[spec-test] FAIL: .tohost value = 0x{:x}", result);
             },
@@ -85,11 +88,9 @@ impl SyscallHandler for TestSyscallHandler {
                         panic!("[spec-test] FAIL: Test failed with exit code {}", exit_code);
                     }
                 }
-                return (exit_code, false); // halt VM
+                return Ok((exit_code, false)); // halt VM
             },
-            _ => {
-                panic!("Unknown syscall ID: {}", call_id);
-            }
+            _ => Err(VmTrap::UnknownSyscall(call_id)),
         }
     }
     fn as_any(&self) -> &dyn Any {
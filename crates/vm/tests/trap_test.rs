@@ -0,0 +1,122 @@
+use vm::gas::SyscallGas;
+use vm::trap::{AccessKind, VmTrap};
+use vm::vm::VM;
+use vm::sys_call::{
+    DefaultSyscallHandler, SyscallHandler, SYSCALL_CALL_PROGRAM, SYSCALL_STORAGE_DELETE, SYSCALL_STORAGE_GET, SYSCALL_STORAGE_SET,
+};
+use vm::{host_interface, memory_page};
+use storage::{Storage, StorageKey};
+use std::rc::Rc;
+use std::cell::RefCell;
+
+fn new_vm(code: &[u8]) -> VM {
+    let memory = Rc::new(RefCell::new(memory_page::MemoryPage::new(8192)));
+    let storage = Rc::new(RefCell::new(Storage::new()));
+    let host: Box<dyn host_interface::HostInterface> = Box::new(host_interface::NoopHost);
+    let mut vm = VM::new(memory, storage, host);
+    vm.set_code(0, 0, code).unwrap();
+    vm
+}
+
+#[test]
+fn test_illegal_instruction_traps() {
+    let mut vm = new_vm(&[0xff, 0xff, 0xff, 0xff]);
+    match vm.run() {
+        Err(VmTrap::IllegalInstruction { pc, bytes }) => {
+            assert_eq!(pc, 0);
+            assert_eq!(bytes, vec![0xff; 4]);
+        }
+        other => panic!("expected IllegalInstruction, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_out_of_bounds_load_traps() {
+    // lui x5, 0x10 ; lw x6, 0(x5)   (0x10000 is past the 8KB page)
+    let mut vm = new_vm(&[0xb7, 0x02, 0x01, 0x00, 0x03, 0xa3, 0x02, 0x00]);
+    assert_eq!(
+        vm.run(),
+        Err(VmTrap::OutOfBounds { addr: 0x10000, size: 4, kind: AccessKind::Load })
+    );
+}
+
+#[test]
+fn test_misaligned_atomic_traps() {
+    // addi x5, x0, 2 ; amoadd.w x6, x0, (x5)
+    let mut vm = new_vm(&[0x93, 0x02, 0x20, 0x00, 0x2f, 0xa3, 0x02, 0x00]);
+    assert!(matches!(vm.run(), Err(VmTrap::MisalignedAccess { addr: 2, size: 4, .. })));
+}
+
+#[test]
+fn test_unknown_syscall_traps() {
+    // addi a7, x0, 999 ; ecall
+    let mut vm = new_vm(&[0x93, 0x08, 0x70, 0x3e, 0x73, 0x00, 0x00, 0x00]);
+    assert_eq!(vm.run(), Err(VmTrap::UnknownSyscall(999)));
+}
+
+#[test]
+fn test_guest_panic_carries_message() {
+    // addi a0, x0, 0x100 ; addi a1, x0, 4 ; addi a7, x0, 3 ; ecall
+    let code = [
+        0x13, 0x05, 0x00, 0x10,
+        0x93, 0x05, 0x40, 0x00,
+        0x93, 0x08, 0x30, 0x00,
+        0x73, 0x00, 0x00, 0x00,
+    ];
+    let mut vm = new_vm(&code);
+    vm.memory.borrow_mut().mem_mut()[0x100..0x104].copy_from_slice(b"boom");
+    assert_eq!(vm.run(), Err(VmTrap::GuestPanic("boom".to_string())));
+}
+
+#[test]
+fn test_storage_value_larger_than_heap_traps() {
+    let vm = new_vm(&[]);
+    vm.storage.borrow().set(StorageKey::new(b"D", b"k"), vec![7; 10_000]);
+    vm.memory.borrow_mut().mem_mut()[0x10..0x12].copy_from_slice(b"Dk");
+
+    let mut host: Box<dyn host_interface::HostInterface> = Box::new(host_interface::NoopHost);
+    let args = [0x10, 1, 0x11, 1, 0, 0];
+    let outcome = DefaultSyscallHandler::new().handle_syscall(
+        SYSCALL_STORAGE_GET, args, vm.memory.clone(), vm.storage.clone(), &mut host, &mut [0; 32], &SyscallGas::unlimited(),
+    );
+    assert!(matches!(outcome, Err(VmTrap::OutOfBounds { size: 10_004, kind: AccessKind::Store, .. })));
+}
+
+#[test]
+fn test_storage_delete_outside_memory_traps() {
+    let vm = new_vm(&[]);
+    vm.storage.borrow().set(StorageKey::new(b"D", b"k"), vec![7]);
+    vm.memory.borrow_mut().mem_mut()[0x10..0x12].copy_from_slice(b"Dk");
+
+    let mut host: Box<dyn host_interface::HostInterface> = Box::new(host_interface::NoopHost);
+    let mut delete = |args: [u32; 6]| {
+        DefaultSyscallHandler::new()
+            .handle_syscall(
+                SYSCALL_STORAGE_DELETE, args, vm.memory.clone(), vm.storage.clone(), &mut host, &mut [0; 32], &SyscallGas::unlimited(),
+            )
+            .map(|(result, _)| result)
+    };
+    assert!(matches!(delete([0x10, 1, 0x10_0000, 1, 0, 0]), Err(VmTrap::OutOfBounds { kind: AccessKind::Load, .. })));
+    assert_eq!(delete([0x10, 1, 0x11, 1, 0, 0]), Ok(1));
+    assert_eq!(delete([0x10, 1, 0x11, 1, 0, 0]), Ok(0));
+}
+
+#[test]
+fn test_guest_pointers_outside_memory_trap() {
+    let vm = new_vm(&[]);
+    let mut host: Box<dyn host_interface::HostInterface> = Box::new(host_interface::NoopHost);
+    let mut syscall = |call_id: u32, args: [u32; 6]| {
+        DefaultSyscallHandler::new()
+            .handle_syscall(call_id, args, vm.memory.clone(), vm.storage.clone(), &mut host, &mut [0; 32], &SyscallGas::unlimited())
+            .map(|(result, _)| result)
+    };
+    let out_of_bounds = |outcome| matches!(outcome, Err(VmTrap::OutOfBounds { addr: 0x10_0000, kind: AccessKind::Load, .. }));
+
+    assert!(out_of_bounds(syscall(SYSCALL_STORAGE_GET, [0x10_0000, 1, 0x10, 1, 0, 0])));
+    assert!(out_of_bounds(syscall(SYSCALL_STORAGE_GET, [0x10, 1, 0x10_0000, 1, 0, 0])));
+    assert!(out_of_bounds(syscall(SYSCALL_STORAGE_SET, [0x10_0000, 1, 0x10, 1, 0x10, 1])));
+    assert!(out_of_bounds(syscall(SYSCALL_STORAGE_SET, [0x10, 1, 0x10, 1, 0x10_0000, 1])));
+    assert!(out_of_bounds(syscall(SYSCALL_CALL_PROGRAM, [0x10_0000, 0x10, 1, 0, 0, 0])));
+    assert!(out_of_bounds(syscall(SYSCALL_CALL_PROGRAM, [0x10, 0x10_0000, 1, 0, 0, 0])));
+    assert!(vm.storage.borrow().get(&StorageKey::new(&[0], &[0])).is_none());
+}